@@ -20,7 +20,8 @@ use bevy_gaussian_splatting::{
     GaussianCamera,
     GaussianCloud,
     GaussianCloudHandle,
-    GaussianCloudSettings,
+    GaussianCloudAppearance,
+    GaussianCloudRasterSettings,
     GaussianMode,
     GaussianSplattingPlugin,
     gaussian::f32::Rotation,
@@ -165,11 +166,14 @@ pub fn setup_surfel_compare(
         GaussianCloudHandle(
             gaussian_assets.add(GaussianCloud::from_gaussians(red_gaussians))
         ),
-        GaussianCloudSettings {
-            aabb: true,
+        GaussianCloudAppearance {
             gaussian_mode: GaussianMode::GaussianSurfel,
             ..default()
         },
+        GaussianCloudRasterSettings {
+            aabb: true,
+            ..default()
+        },
         Name::new("gaussian_cloud_2dgs"),
     ));
 
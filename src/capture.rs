@@ -0,0 +1,599 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    core_pipeline::tonemapping::Tonemapping,
+    render::{
+        camera::RenderTarget,
+        graph::CameraDriverLabel,
+        render_asset::{
+            RenderAssets,
+            RenderAssetUsages,
+        },
+        render_graph::{
+            Node,
+            NodeRunError,
+            RenderGraph,
+            RenderGraphContext,
+            RenderLabel,
+        },
+        render_resource::*,
+        renderer::{
+            RenderContext,
+            RenderDevice,
+        },
+        texture::GpuImage,
+        Extract,
+        ExtractSchedule,
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+};
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GaussianCaptureLabel;
+
+
+/// renders `GaussianCapture` cameras into images without a window
+#[derive(Default)]
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        let readbacks = GaussianCaptureReadbacks::default();
+
+        app.register_type::<GaussianCapture>();
+        app.register_type::<GaussianCaptureOutput>();
+
+        app.add_event::<GaussianCaptureComplete>();
+
+        app.insert_resource(readbacks.clone());
+
+        app.add_systems(Update, (
+            start_captures,
+            advance_captures,
+            receive_captures,
+        ).chain());
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(readbacks)
+                .init_resource::<GaussianCaptureRequests>()
+                .init_resource::<GaussianCaptureBuffers>()
+                .add_systems(ExtractSchedule, extract_capture_requests)
+                .add_systems(
+                    Render,
+                    (
+                        prepare_capture_buffers.in_set(RenderSet::PrepareResources),
+                        map_capture_buffers.in_set(RenderSet::Cleanup),
+                    ),
+                );
+
+            let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+            graph.add_node(GaussianCaptureLabel, GaussianCaptureNode);
+            graph.add_node_edge(CameraDriverLabel, GaussianCaptureLabel);
+        }
+    }
+}
+
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCaptureOutput {
+    /// frames are returned as images in `GaussianCaptureComplete`
+    #[default]
+    Memory,
+    /// frames are written as `{index:04}.png` into the directory
+    Png(PathBuf),
+    /// frames are rendered in hdr without tonemapping and written as `{index:04}.exr` into the directory
+    #[cfg(feature = "capture_exr")]
+    Exr(PathBuf),
+}
+
+impl GaussianCaptureOutput {
+    pub fn frame_path(&self, index: usize) -> Option<PathBuf> {
+        match self {
+            GaussianCaptureOutput::Memory => None,
+            GaussianCaptureOutput::Png(directory) => Some(directory.join(format!("{index:04}.png"))),
+            #[cfg(feature = "capture_exr")]
+            GaussianCaptureOutput::Exr(directory) => Some(directory.join(format!("{index:04}.exr"))),
+        }
+    }
+
+    pub fn texture_format(&self) -> TextureFormat {
+        match self {
+            #[cfg(feature = "capture_exr")]
+            GaussianCaptureOutput::Exr(_) => TextureFormat::Rgba32Float,
+            _ => TextureFormat::Rgba8UnormSrgb,
+        }
+    }
+}
+
+
+/// renders the camera from each pose in turn, sending `GaussianCaptureComplete` when done
+///
+/// the camera target is replaced by an offscreen image. the component is removed once every
+/// pose is captured, insert it again to start another capture.
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianCapture {
+    pub poses: Vec<Transform>,
+    pub size: UVec2,
+    pub output: GaussianCaptureOutput,
+    /// frames rendered at each pose before it is read back, covers asset loading and sorting
+    pub pre_roll_frames: u32,
+}
+
+impl Default for GaussianCapture {
+    fn default() -> Self {
+        Self {
+            poses: Vec::new(),
+            size: UVec2::new(1920, 1080),
+            output: GaussianCaptureOutput::default(),
+            pre_roll_frames: 15,
+        }
+    }
+}
+
+impl GaussianCapture {
+    /// poses on a horizontal circle around `target`, all looking at it
+    pub fn turntable(
+        target: Vec3,
+        radius: f32,
+        height: f32,
+        count: usize,
+    ) -> Vec<Transform> {
+        (0..count)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / count as f32;
+                let offset = Vec3::new(angle.sin() * radius, height, angle.cos() * radius);
+
+                Transform::from_translation(target + offset)
+                    .looking_at(target, Vec3::Y)
+            })
+            .collect()
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct GaussianCapturedFrame {
+    pub index: usize,
+    pub pose: Transform,
+    /// set for `GaussianCaptureOutput::Memory`
+    pub image: Option<Image>,
+    /// set when the frame was written to disk
+    pub path: Option<PathBuf>,
+    /// set when the frame could not be read back or saved
+    pub error: Option<String>,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct GaussianCaptureComplete {
+    pub camera: Entity,
+    pub frames: Vec<GaussianCapturedFrame>,
+}
+
+
+/// failed readbacks of a pose before it is skipped
+const MAX_CAPTURE_READBACK_ATTEMPTS: u32 = 8;
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CapturePhase {
+    Render(u32),
+    Readback,
+    Waiting,
+}
+
+#[derive(Component, Debug)]
+struct GaussianCaptureState {
+    target: Handle<Image>,
+    pose: usize,
+    phase: CapturePhase,
+    readback_attempts: u32,
+    frames: Vec<GaussianCapturedFrame>,
+}
+
+
+#[derive(Clone, Debug)]
+struct CaptureRequest {
+    camera: Entity,
+    pose: usize,
+    target: Handle<Image>,
+}
+
+struct CaptureReadback {
+    camera: Entity,
+    pose: usize,
+    data: Option<Vec<u8>>,
+}
+
+/// completed readbacks, written by buffer map callbacks in the render world
+#[derive(Resource, Clone, Default)]
+struct GaussianCaptureReadbacks(Arc<Mutex<Vec<CaptureReadback>>>);
+
+
+fn start_captures(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut cameras: Query<
+        (
+            Entity,
+            &GaussianCapture,
+            &mut Camera,
+            &mut Transform,
+        ),
+        Added<GaussianCapture>,
+    >,
+) {
+    for (
+        entity,
+        capture,
+        mut camera,
+        mut transform,
+    ) in cameras.iter_mut() {
+        let format = capture.output.texture_format();
+        let size = capture.size.max(UVec2::ONE);
+
+        let mut target = Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &vec![0; format.block_copy_size(None).unwrap_or(4) as usize],
+            format,
+            RenderAssetUsages::default(),
+        );
+        target.texture_descriptor.usage = TextureUsages::COPY_SRC
+            | TextureUsages::COPY_DST
+            | TextureUsages::TEXTURE_BINDING
+            | TextureUsages::RENDER_ATTACHMENT;
+
+        let target = images.add(target);
+
+        camera.target = RenderTarget::Image(target.clone());
+        if format == TextureFormat::Rgba32Float {
+            camera.hdr = true;
+            commands.entity(entity).insert(Tonemapping::None);
+        }
+
+        if let Some(pose) = capture.poses.first() {
+            *transform = *pose;
+        }
+
+        commands.entity(entity).insert(GaussianCaptureState {
+            target,
+            pose: 0,
+            phase: CapturePhase::Render(capture.pre_roll_frames),
+            readback_attempts: 0,
+            frames: Vec::with_capacity(capture.poses.len()),
+        });
+    }
+}
+
+fn advance_captures(
+    mut commands: Commands,
+    mut captured: EventWriter<GaussianCaptureComplete>,
+    mut cameras: Query<(
+        Entity,
+        &GaussianCapture,
+        &mut GaussianCaptureState,
+    )>,
+) {
+    for (
+        entity,
+        capture,
+        mut state,
+    ) in cameras.iter_mut() {
+        if state.pose >= capture.poses.len() {
+            captured.send(GaussianCaptureComplete {
+                camera: entity,
+                frames: std::mem::take(&mut state.frames),
+            });
+
+            commands.entity(entity).remove::<(GaussianCapture, GaussianCaptureState)>();
+            continue;
+        }
+
+        state.phase = match state.phase {
+            CapturePhase::Render(0) => CapturePhase::Readback,
+            CapturePhase::Render(n) => CapturePhase::Render(n - 1),
+            // the request was extracted last frame
+            CapturePhase::Readback => CapturePhase::Waiting,
+            CapturePhase::Waiting => CapturePhase::Waiting,
+        };
+    }
+}
+
+fn receive_captures(
+    readbacks: Res<GaussianCaptureReadbacks>,
+    mut cameras: Query<(
+        &GaussianCapture,
+        &mut GaussianCaptureState,
+        &mut Transform,
+    )>,
+) {
+    let completed = std::mem::take(&mut *readbacks.0.lock().unwrap());
+
+    for readback in completed {
+        let Ok((
+            capture,
+            mut state,
+            mut transform,
+        )) = cameras.get_mut(readback.camera) else {
+            continue;
+        };
+
+        if readback.pose != state.pose || state.phase != CapturePhase::Waiting {
+            continue;
+        }
+
+        let Some(data) = readback.data else {
+            state.readback_attempts += 1;
+            if state.readback_attempts < MAX_CAPTURE_READBACK_ATTEMPTS {
+                state.phase = CapturePhase::Readback;
+                continue;
+            }
+
+            error!("failed to read back capture frame {} after {} attempts", state.pose, state.readback_attempts);
+
+            let frame = GaussianCapturedFrame {
+                index: state.pose,
+                pose: capture.poses[state.pose],
+                image: None,
+                path: None,
+                error: Some(format!("readback failed after {} attempts", state.readback_attempts)),
+            };
+            next_capture_pose(capture, &mut state, &mut transform, frame);
+            continue;
+        };
+
+        let size = capture.size.max(UVec2::ONE);
+        let image = Image::new(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            capture.output.texture_format(),
+            RenderAssetUsages::default(),
+        );
+
+        let mut error = None;
+        let path = capture.output
+            .frame_path(state.pose)
+            .filter(|path| match save_frame(&image, path) {
+                Ok(_) => true,
+                Err(err) => {
+                    error!("failed to save capture frame {:?}: {}", path, err);
+                    error = Some(format!("failed to save {:?}: {}", path, err));
+                    false
+                },
+            });
+
+        let frame = GaussianCapturedFrame {
+            index: state.pose,
+            pose: capture.poses[state.pose],
+            image: (capture.output == GaussianCaptureOutput::Memory).then_some(image),
+            path,
+            error,
+        };
+        next_capture_pose(capture, &mut state, &mut transform, frame);
+    }
+}
+
+fn next_capture_pose(
+    capture: &GaussianCapture,
+    state: &mut GaussianCaptureState,
+    transform: &mut Transform,
+    frame: GaussianCapturedFrame,
+) {
+    state.frames.push(frame);
+
+    state.pose += 1;
+    if let Some(pose) = capture.poses.get(state.pose) {
+        *transform = *pose;
+    }
+    state.phase = CapturePhase::Render(capture.pre_roll_frames);
+    state.readback_attempts = 0;
+}
+
+fn save_frame(
+    image: &Image,
+    path: &std::path::Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+
+    match image.texture_descriptor.format {
+        TextureFormat::Rgba32Float => {
+            let size = image.size();
+            let pixels = image.data
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect::<Vec<f32>>();
+            let buffer = image::Rgba32FImage::from_raw(size.x, size.y, pixels)
+                .ok_or("capture frame size does not match its data")?;
+
+            image::DynamicImage::ImageRgba32F(buffer).save(path)?;
+        },
+        _ => {
+            image.clone()
+                .try_into_dynamic()?
+                .to_rgba8()
+                .save(path)?;
+        },
+    }
+
+    Ok(())
+}
+
+
+#[derive(Resource, Default)]
+struct GaussianCaptureRequests(Vec<CaptureRequest>);
+
+fn extract_capture_requests(
+    mut requests: ResMut<GaussianCaptureRequests>,
+    cameras: Extract<Query<(Entity, &GaussianCaptureState)>>,
+) {
+    requests.0 = cameras.iter()
+        .filter(|(_, state)| state.phase == CapturePhase::Readback)
+        .map(|(camera, state)| CaptureRequest {
+            camera,
+            pose: state.pose,
+            target: state.target.clone(),
+        })
+        .collect();
+}
+
+
+struct CaptureBuffer {
+    request: CaptureRequest,
+    buffer: Buffer,
+    size: UVec2,
+    bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+}
+
+#[derive(Resource, Default)]
+struct GaussianCaptureBuffers(Vec<CaptureBuffer>);
+
+fn prepare_capture_buffers(
+    render_device: Res<RenderDevice>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    requests: Res<GaussianCaptureRequests>,
+    readbacks: Res<GaussianCaptureReadbacks>,
+    mut buffers: ResMut<GaussianCaptureBuffers>,
+) {
+    for request in requests.0.iter() {
+        let Some(image) = gpu_images.get(&request.target) else {
+            readbacks.0.lock().unwrap().push(CaptureReadback {
+                camera: request.camera,
+                pose: request.pose,
+                data: None,
+            });
+            continue;
+        };
+
+        let block_size = image.texture_format.block_copy_size(None).unwrap_or(4);
+        let bytes_per_row = image.size.x * block_size;
+        let padded_bytes_per_row = RenderDevice::align_copy_bytes_per_row(bytes_per_row as usize) as u32;
+
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("gaussian capture readback buffer"),
+            size: padded_bytes_per_row as u64 * image.size.y as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        buffers.0.push(CaptureBuffer {
+            request: request.clone(),
+            buffer,
+            size: image.size,
+            bytes_per_row,
+            padded_bytes_per_row,
+        });
+    }
+}
+
+fn map_capture_buffers(
+    render_device: Res<RenderDevice>,
+    readbacks: Res<GaussianCaptureReadbacks>,
+    mut buffers: ResMut<GaussianCaptureBuffers>,
+) {
+    for capture in buffers.0.drain(..) {
+        let readbacks = readbacks.clone();
+        let mapped_buffer = capture.buffer.clone();
+
+        capture.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let data = result.ok().map(|_| {
+                let data = {
+                    let padded = mapped_buffer.slice(..).get_mapped_range();
+                    padded
+                        .chunks(capture.padded_bytes_per_row as usize)
+                        .take(capture.size.y as usize)
+                        .flat_map(|row| &row[..capture.bytes_per_row as usize])
+                        .copied()
+                        .collect::<Vec<u8>>()
+                };
+                mapped_buffer.unmap();
+
+                data
+            });
+
+            readbacks.0.lock().unwrap().push(CaptureReadback {
+                camera: capture.request.camera,
+                pose: capture.request.pose,
+                data,
+            });
+        });
+    }
+
+    render_device.poll(wgpu::Maintain::Poll);
+}
+
+
+#[derive(Default)]
+pub struct GaussianCaptureNode;
+
+impl Node for GaussianCaptureNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let buffers = world.resource::<GaussianCaptureBuffers>();
+        let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+
+        for capture in buffers.0.iter() {
+            let Some(image) = gpu_images.get(&capture.request.target) else {
+                continue;
+            };
+
+            render_context.command_encoder().copy_texture_to_buffer(
+                image.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &capture.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(capture.padded_bytes_per_row),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: capture.size.x,
+                    height: capture.size.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::animation::{
+    animated_field,
+    prelude::{
+        AnimatableCurve,
+        AnimatableKeyframeCurve,
+        AnimatedField,
+        AnimationCurve,
+    },
+};
+
+use crate::{
+    gaussian::settings::GaussianCloudAppearance,
+    render::reveal::GaussianCloudReveal,
+};
+
+
+// keyframes are (time in seconds, value), add the returned curves to an `AnimationClip` with `add_curve_to_target`
+
+
+/// animates `GaussianCloudAppearance::global_opacity`, none if fewer than two keyframes are given
+pub fn global_opacity_curve(
+    keyframes: impl IntoIterator<Item = (f32, f32)>,
+) -> Option<impl AnimationCurve> {
+    Some(AnimatableCurve::new(
+        animated_field!(GaussianCloudAppearance::global_opacity),
+        AnimatableKeyframeCurve::new(keyframes).ok()?,
+    ))
+}
+
+/// animates `GaussianCloudAppearance::global_scale`, none if fewer than two keyframes are given
+pub fn global_scale_curve(
+    keyframes: impl IntoIterator<Item = (f32, f32)>,
+) -> Option<impl AnimationCurve> {
+    Some(AnimatableCurve::new(
+        animated_field!(GaussianCloudAppearance::global_scale),
+        AnimatableKeyframeCurve::new(keyframes).ok()?,
+    ))
+}
+
+/// animates `GaussianCloudReveal::progress`, none if fewer than two keyframes are given
+pub fn reveal_progress_curve(
+    keyframes: impl IntoIterator<Item = (f32, f32)>,
+) -> Option<impl AnimationCurve> {
+    Some(AnimatableCurve::new(
+        animated_field!(GaussianCloudReveal::progress),
+        AnimatableKeyframeCurve::new(keyframes).ok()?,
+    ))
+}
+
+/// linear reveal from hidden to fully revealed over `duration` seconds
+pub fn reveal_curve(duration: f32) -> Option<impl AnimationCurve> {
+    reveal_progress_curve([(0.0, 0.0), (duration, 1.0)])
+}
@@ -5,6 +5,7 @@ use rand::{
 
 use bevy::{
     prelude::*,
+    math::Affine3A,
     render::{
         primitives::Aabb,
         sync_world::SyncToRenderWorld,
@@ -26,6 +27,11 @@ use rayon::prelude::*;
 #[allow(unused_imports)]
 use crate::{
     gaussian::{
+        covariance::{
+            compute_covariance_3d,
+            decompose_covariance_3d,
+            transform_covariance_3d,
+        },
         f32::{
             Covariance3dOpacity,
             Position,
@@ -34,12 +40,20 @@ use crate::{
             ScaleOpacity,
         },
         packed::Gaussian,
-        settings::GaussianCloudSettings,
+        settings::{
+            GaussianCloudAppearance,
+            GaussianCloudDebugSettings,
+            GaussianCloudRasterSettings,
+            GaussianCloudSortSettings,
+        },
     },
-    material::spherical_harmonics::{
-        HALF_SH_COEFF_COUNT,
-        SH_COEFF_COUNT,
-        SphericalHarmonicCoefficients,
+    material::{
+        pbr::PbrMaterial,
+        spherical_harmonics::{
+            HALF_SH_COEFF_COUNT,
+            SH_COEFF_COUNT,
+            SphericalHarmonicCoefficients,
+        },
     },
 };
 
@@ -104,7 +118,10 @@ pub fn calculate_bounds(
 )]
 #[reflect(Component, Default)]
 #[require(
-    GaussianCloudSettings,
+    GaussianCloudAppearance,
+    GaussianCloudDebugSettings,
+    GaussianCloudRasterSettings,
+    GaussianCloudSortSettings,
     SyncToRenderWorld,
     Transform,
     Visibility,
@@ -151,6 +168,15 @@ pub struct GaussianCloud {
 
     #[cfg(feature = "precompute_covariance_3d")]
     pub covariance_3d_opacity_packed128: Vec<Covariance3dOpacityPacked128>,
+
+    // appended after the gaussian attributes, positional codecs decode older clouds without them
+    /// empty unless the source provides pbr attributes, otherwise one per gaussian
+    #[serde(default)]
+    pub pbr_material: Vec<PbrMaterial>,
+
+    /// mip-splatting 3d filter standard deviation, empty unless the source provides it
+    #[serde(default)]
+    pub filter_3d: Vec<f32>,
 }
 
 #[cfg(feature = "f32")]
@@ -176,6 +202,15 @@ pub struct GaussianCloud {
     pub rotation: Vec<Rotation>,
     #[cfg(not(feature = "precompute_covariance_3d"))]
     pub scale_opacity: Vec<ScaleOpacity>,
+
+    // appended after the gaussian attributes, positional codecs decode older clouds without them
+    /// empty unless the source provides pbr attributes, otherwise one per gaussian
+    #[serde(default)]
+    pub pbr_material: Vec<PbrMaterial>,
+
+    /// mip-splatting 3d filter standard deviation, empty unless the source provides it
+    #[serde(default)]
+    pub filter_3d: Vec<f32>,
 }
 
 impl GaussianCloud {
@@ -223,6 +258,169 @@ impl GaussianCloud {
         &mut self.position_visibility[index].visibility
     }
 
+    /// largest standard deviation along any axis and opacity of a gaussian
+    pub fn max_scale_opacity(&self, index: usize) -> (f32, f32) {
+        #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+        let (max_scale, opacity) = {
+            let scale_opacity = self.rotation_scale_opacity_packed128[index].scale_opacity();
+            (Vec3::from(scale_opacity.scale).max_element(), scale_opacity.opacity)
+        };
+
+        #[cfg(all(feature = "f16", feature = "precompute_covariance_3d"))]
+        let (max_scale, opacity) = {
+            let cov3d_opacity = self.covariance_3d_opacity_packed128[index].covariance_3d_opacity();
+            let cov3d = cov3d_opacity.cov3d;
+            (cov3d[0].max(cov3d[3]).max(cov3d[5]).sqrt(), cov3d_opacity.opacity)
+        };
+
+        #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+        let (max_scale, opacity) = {
+            let scale_opacity = &self.scale_opacity[index];
+            (Vec3::from(scale_opacity.scale).max_element(), scale_opacity.opacity)
+        };
+
+        #[cfg(all(feature = "f32", feature = "precompute_covariance_3d"))]
+        let (max_scale, opacity) = {
+            let cov3d = self.covariance_3d[index].cov3d;
+            (cov3d[0].max(cov3d[3]).max(cov3d[5]).sqrt(), self.covariance_3d[index].opacity)
+        };
+
+        (max_scale, opacity)
+    }
+
+    /// upper triangle of the 3d covariance (xx, xy, xz, yy, yz, zz) of a gaussian
+    pub fn covariance_3d(&self, index: usize) -> [f32; 6] {
+        #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+        let cov3d = {
+            let rso = self.rotation_scale_opacity_packed128[index];
+            compute_covariance_3d(
+                Vec4::from(rso.rotation().rotation),
+                Vec3::from(rso.scale_opacity().scale),
+            )
+        };
+
+        #[cfg(all(feature = "f16", feature = "precompute_covariance_3d"))]
+        let cov3d = self.covariance_3d_opacity_packed128[index].covariance_3d_opacity().cov3d;
+
+        #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+        let cov3d = compute_covariance_3d(
+            Vec4::from(self.rotation[index].rotation),
+            Vec3::from(self.scale_opacity[index].scale),
+        );
+
+        #[cfg(all(feature = "f32", feature = "precompute_covariance_3d"))]
+        let cov3d = self.covariance_3d[index].cov3d;
+
+        cov3d
+    }
+
+    /// rotation (w, x, y, z), per-axis standard deviation and opacity of a gaussian
+    ///
+    /// precomputed covariances are decomposed, so the rotation is only unique up to axis permutation.
+    pub fn rotation_scale_opacity(&self, index: usize) -> (Vec4, Vec3, f32) {
+        #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+        let (rotation, scale) = {
+            let rso = self.rotation_scale_opacity_packed128[index];
+            (Vec4::from(rso.rotation().rotation), Vec3::from(rso.scale_opacity().scale))
+        };
+
+        #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+        let (rotation, scale) = (
+            Vec4::from(self.rotation[index].rotation),
+            Vec3::from(self.scale_opacity[index].scale),
+        );
+
+        #[cfg(feature = "precompute_covariance_3d")]
+        let (rotation, scale) = decompose_covariance_3d(self.covariance_3d(index));
+
+        let (_, opacity) = self.max_scale_opacity(index);
+
+        (rotation, scale, opacity)
+    }
+
+    /// applies an affine transform to a gaussian, carrying its rotation and scale (or covariance) with the position
+    ///
+    /// view dependent spherical harmonic bands are left untouched.
+    pub fn transform_gaussian(&mut self, index: usize, transform: &Affine3A) {
+        let position = transform.transform_point3(Vec3::from(*self.position(index)));
+        *self.position_mut(index) = position.to_array();
+
+        let linear = Mat3::from(transform.matrix3);
+
+        #[cfg(feature = "precompute_covariance_3d")]
+        {
+            let cov3d = transform_covariance_3d(self.covariance_3d(index), linear);
+
+            #[cfg(feature = "f16")]
+            {
+                let packed = &mut self.covariance_3d_opacity_packed128[index];
+                packed.cov3d = [
+                    pack_f32s_to_u32(cov3d[0], cov3d[1]),
+                    pack_f32s_to_u32(cov3d[2], cov3d[3]),
+                    pack_f32s_to_u32(cov3d[4], cov3d[5]),
+                ];
+            }
+
+            #[cfg(feature = "f32")]
+            {
+                self.covariance_3d[index].cov3d = cov3d;
+            }
+        }
+
+        #[cfg(not(feature = "precompute_covariance_3d"))]
+        {
+            #[cfg(feature = "f16")]
+            let (rotation, scale_opacity) = {
+                let rso = self.rotation_scale_opacity_packed128[index];
+                (rso.rotation().rotation, rso.scale_opacity())
+            };
+
+            #[cfg(feature = "f32")]
+            let (rotation, scale_opacity) = (self.rotation[index].rotation, self.scale_opacity[index]);
+
+            let scale = Vec3::from(scale_opacity.scale);
+
+            let [w, x, y, z] = rotation;
+            let quaternion = Quat::from_xyzw(x, y, z, w);
+
+            // similarity transforms compose directly with unit rotations, anything else goes through the covariance
+            let similarity = similarity_rotation_scale(linear)
+                .filter(|_| (quaternion.length_squared() - 1.0).abs() < 1e-2);
+
+            let (rotation, scale) = match similarity {
+                Some((transform_rotation, uniform_scale)) => {
+                    let rotation = (transform_rotation * quaternion.normalize()).normalize();
+
+                    (Vec4::new(rotation.w, rotation.x, rotation.y, rotation.z), scale * uniform_scale)
+                },
+                None => decompose_covariance_3d(transform_covariance_3d(
+                    compute_covariance_3d(Vec4::from(rotation), scale),
+                    linear,
+                )),
+            };
+
+            #[cfg(feature = "f16")]
+            {
+                self.rotation_scale_opacity_packed128[index] = [
+                    rotation.x,
+                    rotation.y,
+                    rotation.z,
+                    rotation.w,
+                    scale.x,
+                    scale.y,
+                    scale.z,
+                    scale_opacity.opacity,
+                ].into();
+            }
+
+            #[cfg(feature = "f32")]
+            {
+                self.rotation[index].rotation = rotation.to_array();
+                self.scale_opacity[index].scale = scale.to_array();
+            }
+        }
+    }
+
     pub fn compute_aabb(&self) -> Option<Aabb> {
         if self.is_empty() {
             return None;
@@ -349,11 +547,67 @@ impl GaussianCloud {
         &mut self.spherical_harmonic[index]
     }
 
+    pub fn pbr_material(&self, index: usize) -> Option<&PbrMaterial> {
+        self.pbr_material.get(index)
+            .filter(|material| material.is_present())
+    }
+
+    /// sets the pbr attributes of every gaussian, missing trailing materials are absent
+    pub fn set_pbr_material(&mut self, mut pbr_material: Vec<PbrMaterial>) {
+        if !pbr_material.is_empty() {
+            pbr_material.resize(self.len(), PbrMaterial::default());
+        }
+
+        self.pbr_material = pbr_material;
+    }
+
+    fn pbr_material_subset(&self, indicies: &[usize]) -> Vec<PbrMaterial> {
+        if self.pbr_material.is_empty() {
+            return Vec::new();
+        }
+
+        indicies.iter()
+            .map(|&index| self.pbr_material[index])
+            .collect()
+    }
+
+    /// 3d smoothing filter of a gaussian in cloud space, zero when the cloud is unfiltered
+    pub fn filter_3d(&self, index: usize) -> f32 {
+        self.filter_3d.get(index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// sets the 3d filter of every gaussian, missing trailing filters are zero
+    pub fn set_filter_3d(&mut self, mut filter_3d: Vec<f32>) {
+        if !filter_3d.is_empty() {
+            filter_3d.resize(self.len(), 0.0);
+        }
+
+        self.filter_3d = filter_3d;
+    }
+
+    fn filter_3d_subset(&self, indicies: &[usize]) -> Vec<f32> {
+        if self.filter_3d.is_empty() {
+            return Vec::new();
+        }
+
+        indicies.iter()
+            .map(|&index| self.filter_3d[index])
+            .collect()
+    }
+
     pub fn resize_to_square(&mut self) {
         #[cfg(all(feature = "buffer_texture", feature = "f16"))]
         {
             self.position_visibility.resize(self.square_len(), PositionVisibility::default());
             self.spherical_harmonic.resize(self.square_len(), SphericalHarmonicCoefficients::default());
+            if !self.pbr_material.is_empty() {
+                self.pbr_material.resize(self.square_len(), PbrMaterial::default());
+            }
+            if !self.filter_3d.is_empty() {
+                self.filter_3d.resize(self.square_len(), 0.0);
+            }
 
             #[cfg(feature = "precompute_covariance_3d")]
             self.covariance_3d_opacity_packed128.resize(self.square_len(), Covariance3dOpacityPacked128::default());
@@ -365,6 +619,12 @@ impl GaussianCloud {
         {
             self.position_visibility.resize(self.square_len(), PositionVisibility::default());
             self.spherical_harmonic.resize(self.square_len(), SphericalHarmonicCoefficients::default());
+            if !self.pbr_material.is_empty() {
+                self.pbr_material.resize(self.square_len(), PbrMaterial::default());
+            }
+            if !self.filter_3d.is_empty() {
+                self.filter_3d.resize(self.square_len(), 0.0);
+            }
             self.rotation.resize(self.square_len(), Rotation::default());
             self.scale_opacity.resize(self.square_len(), ScaleOpacity::default());
             self.covariance_3d.resize(self.square_len(), Covariance3dOpacity::default());
@@ -399,6 +659,8 @@ impl GaussianCloud {
         Self {
             position_visibility,
             spherical_harmonic,
+            pbr_material: self.pbr_material_subset(indicies),
+            filter_3d: self.filter_3d_subset(indicies),
 
             #[cfg(feature = "precompute_covariance_3d")]
             covariance_3d_opacity_packed128,
@@ -424,11 +686,101 @@ impl GaussianCloud {
         Self {
             position_visibility,
             spherical_harmonic,
+            pbr_material: self.pbr_material_subset(indicies),
+            filter_3d: self.filter_3d_subset(indicies),
             rotation,
             scale_opacity,
         }
     }
 
+    /// appends every gaussian of `other` to the end of the cloud
+    pub fn append(&mut self, other: &Self) {
+        // a cloud without pbr attributes contributes absent materials
+        if !self.pbr_material.is_empty() || !other.pbr_material.is_empty() {
+            self.pbr_material.resize(self.len(), PbrMaterial::default());
+
+            match other.pbr_material.is_empty() {
+                true => self.pbr_material.resize(self.len() + other.len(), PbrMaterial::default()),
+                false => self.pbr_material.extend_from_slice(&other.pbr_material),
+            }
+        }
+
+        // likewise an unfiltered cloud contributes zero filters
+        if !self.filter_3d.is_empty() || !other.filter_3d.is_empty() {
+            self.filter_3d.resize(self.len(), 0.0);
+
+            match other.filter_3d.is_empty() {
+                true => self.filter_3d.resize(self.len() + other.len(), 0.0),
+                false => self.filter_3d.extend_from_slice(&other.filter_3d),
+            }
+        }
+
+        self.position_visibility.extend_from_slice(&other.position_visibility);
+        self.spherical_harmonic.extend_from_slice(&other.spherical_harmonic);
+
+        #[cfg(all(feature = "f16", feature = "precompute_covariance_3d"))]
+        self.covariance_3d_opacity_packed128.extend_from_slice(&other.covariance_3d_opacity_packed128);
+
+        #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+        self.rotation_scale_opacity_packed128.extend_from_slice(&other.rotation_scale_opacity_packed128);
+
+        #[cfg(all(feature = "f32", feature = "precompute_covariance_3d"))]
+        self.covariance_3d.extend_from_slice(&other.covariance_3d);
+
+        #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+        {
+            self.rotation.extend_from_slice(&other.rotation);
+            self.scale_opacity.extend_from_slice(&other.scale_opacity);
+        }
+    }
+
+    /// overwrites the gaussians at `indicies` with the gaussians of `subset`, in order
+    pub fn set_subset(&mut self, indicies: &[usize], subset: &Self) {
+        if !subset.pbr_material.is_empty() {
+            self.pbr_material.resize(self.len(), PbrMaterial::default());
+        }
+
+        if !subset.filter_3d.is_empty() {
+            self.filter_3d.resize(self.len(), 0.0);
+        }
+
+        for (source, &index) in indicies.iter().enumerate() {
+            self.position_visibility[index] = subset.position_visibility[source];
+            self.spherical_harmonic[index] = subset.spherical_harmonic[source];
+
+            if !self.pbr_material.is_empty() {
+                self.pbr_material[index] = subset.pbr_material.get(source)
+                    .copied()
+                    .unwrap_or_default();
+            }
+
+            if !self.filter_3d.is_empty() {
+                self.filter_3d[index] = subset.filter_3d(source);
+            }
+
+            #[cfg(all(feature = "f16", feature = "precompute_covariance_3d"))]
+            {
+                self.covariance_3d_opacity_packed128[index] = subset.covariance_3d_opacity_packed128[source];
+            }
+
+            #[cfg(all(feature = "f16", not(feature = "precompute_covariance_3d")))]
+            {
+                self.rotation_scale_opacity_packed128[index] = subset.rotation_scale_opacity_packed128[source];
+            }
+
+            #[cfg(all(feature = "f32", feature = "precompute_covariance_3d"))]
+            {
+                self.covariance_3d[index] = subset.covariance_3d[source];
+            }
+
+            #[cfg(all(feature = "f32", not(feature = "precompute_covariance_3d")))]
+            {
+                self.rotation[index] = subset.rotation[source];
+                self.scale_opacity[index] = subset.scale_opacity[source];
+            }
+        }
+    }
+
     #[cfg(feature = "f32")]
     pub fn to_packed(&self) -> Vec<Gaussian> {
         let mut gaussians = Vec::with_capacity(self.len());
@@ -469,6 +821,8 @@ impl GaussianCloud {
         let mut cloud = GaussianCloud {
             position_visibility,
             spherical_harmonic,
+            pbr_material: Vec::new(),
+            filter_3d: Vec::new(),
 
             #[cfg(feature = "precompute_covariance_3d")]
             covariance_3d_opacity_packed128,
@@ -499,6 +853,8 @@ impl GaussianCloud {
         Self {
             position_visibility,
             spherical_harmonic,
+            pbr_material: Vec::new(),
+            filter_3d: Vec::new(),
             rotation,
             scale_opacity,
         }
@@ -581,3 +937,18 @@ impl FromIterator<Gaussian> for GaussianCloud {
         GaussianCloud::from_gaussians(gaussians)
     }
 }
+
+
+/// rotation and uniform scale of a linear map, `None` when it shears, scales non-uniformly or mirrors
+#[cfg(not(feature = "precompute_covariance_3d"))]
+fn similarity_rotation_scale(linear: Mat3) -> Option<(Quat, f32)> {
+    let scale = linear.determinant().cbrt();
+    if scale <= 0.0 {
+        return None;
+    }
+
+    let rotation = linear * scale.recip();
+    let orthonormal = (rotation.transpose() * rotation).abs_diff_eq(Mat3::IDENTITY, 1e-4);
+
+    orthonormal.then(|| (Quat::from_mat3(&rotation), scale))
+}
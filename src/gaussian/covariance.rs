@@ -1,5 +1,6 @@
 use bevy::math::{
     Mat3,
+    Quat,
     Vec3,
     Vec4,
 };
@@ -47,3 +48,75 @@ pub fn compute_covariance_3d(
         Sigma.row(2).z,
     ]
 }
+
+
+fn covariance_matrix(cov3d: [f32; 6]) -> Mat3 {
+    Mat3::from_cols(
+        Vec3::new(cov3d[0], cov3d[1], cov3d[2]),
+        Vec3::new(cov3d[1], cov3d[3], cov3d[4]),
+        Vec3::new(cov3d[2], cov3d[4], cov3d[5]),
+    )
+}
+
+/// covariance of a gaussian after the linear map `transform` is applied to it
+pub fn transform_covariance_3d(
+    cov3d: [f32; 6],
+    transform: Mat3,
+) -> [f32; 6] {
+    let sigma = transform * covariance_matrix(cov3d) * transform.transpose();
+
+    [
+        sigma.x_axis.x,
+        sigma.y_axis.x,
+        sigma.z_axis.x,
+        sigma.y_axis.y,
+        sigma.z_axis.y,
+        sigma.z_axis.z,
+    ]
+}
+
+/// rotation (w, x, y, z) and scale whose `compute_covariance_3d` reproduces `cov3d`, via jacobi eigen decomposition
+pub fn decompose_covariance_3d(cov3d: [f32; 6]) -> (Vec4, Vec3) {
+    let mut a = covariance_matrix(cov3d).to_cols_array_2d();
+    let mut v = Mat3::IDENTITY.to_cols_array_2d();
+
+    for _ in 0..16 {
+        let off_diagonal = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        let diagonal = a[0][0] * a[0][0] + a[1][1] * a[1][1] + a[2][2] * a[2][2];
+        if off_diagonal <= diagonal * 1e-14 {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+
+            for row in a.iter_mut().chain(v.iter_mut()) {
+                let (rp, rq) = (row[p], row[q]);
+                row[p] = c * rp - s * rq;
+                row[q] = s * rp + c * rq;
+            }
+
+            let (ap, aq) = (a[p], a[q]);
+            a[p] = std::array::from_fn(|k| c * ap[k] - s * aq[k]);
+            a[q] = std::array::from_fn(|k| s * ap[k] + c * aq[k]);
+        }
+    }
+
+    // v holds the eigenvectors as rows, transposing gives the rotation with eigenvector columns
+    let mut rotation = Mat3::from_cols_array_2d(&v).transpose();
+    if rotation.determinant() < 0.0 {
+        rotation.z_axis = -rotation.z_axis;
+    }
+
+    let scale = Vec3::new(a[0][0], a[1][1], a[2][2]).max(Vec3::ZERO).map(f32::sqrt);
+    let quat = Quat::from_mat3(&rotation).normalize();
+
+    (Vec4::new(quat.w, quat.x, quat.y, quat.z), scale)
+}
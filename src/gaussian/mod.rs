@@ -7,6 +7,9 @@ pub mod packed;
 pub mod rand;
 pub mod settings;
 
+#[cfg(feature = "animation")]
+pub mod animation;
+
 #[cfg(feature = "f16")]
 pub mod f16;
 
@@ -5,7 +5,10 @@ use bevy_args::{
     ValueEnum,
 };
 
-use crate::sort::SortMode;
+use crate::{
+    material::spherical_harmonics::SH_DEGREE,
+    sort::SortMode,
+};
 
 
 #[derive(
@@ -64,33 +67,297 @@ pub enum GaussianCloudRasterize {
 }
 
 
-// TODO: breakdown into components
-#[derive(Component, Reflect, Clone)]
-#[reflect(Component)]
-pub struct GaussianCloudSettings {
-    pub aabb: bool,
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudBackend {
+    #[default]
+    Instanced,
+    Tile,
+}
+
+
+/// how the gaussians of a cloud are depth sorted
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianCloudSortSettings {
+    pub sort_mode: SortMode,
+}
+
+
+/// color adjustments applied to the shaded color of every gaussian of a cloud
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Default)]
+pub struct GaussianColorGrading {
+    /// exposure in stops, the color is scaled by `2^exposure`
+    pub exposure: f32,
+    /// white balance shift along the blue (negative) to yellow (positive) axis
+    pub temperature: f32,
+    /// white balance shift along the green (negative) to magenta (positive) axis
+    pub tint: f32,
+    /// power around middle grey, 1.0 leaves the color unchanged
+    pub contrast: f32,
+    /// 0.0 is greyscale, 1.0 leaves the color unchanged
+    pub saturation: f32,
+}
+
+impl Default for GaussianColorGrading {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            temperature: 0.0,
+            tint: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+        }
+    }
+}
+
+impl GaussianColorGrading {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+
+/// global modifiers applied to every gaussian of a cloud
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianCloudAppearance {
     pub global_opacity: f32,
     pub global_scale: f32,
+    pub gaussian_mode: GaussianMode,
+    pub color_grading: GaussianColorGrading,
+    /// highest spherical harmonic band evaluated when shading, bands above it are ignored
+    ///
+    /// values above the compiled `SH_DEGREE` have no effect.
+    pub max_sh_band: u32,
+}
+
+impl Default for GaussianCloudAppearance {
+    fn default() -> Self {
+        Self {
+            global_opacity: 1.0,
+            global_scale: 1.0,
+            gaussian_mode: GaussianMode::default(),
+            color_grading: GaussianColorGrading::default(),
+            max_sh_band: SH_DEGREE as u32,
+        }
+    }
+}
+
+
+/// which gaussians of a cloud are drawn and how they are rasterized
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianCloudRasterSettings {
+    pub aabb: bool,
     pub opacity_adaptive_radius: bool,
-    pub visualize_bounding_box: bool,
-    pub sort_mode: SortMode,
     pub draw_mode: GaussianCloudDrawMode,
-    pub gaussian_mode: GaussianMode,
     pub rasterize_mode: GaussianCloudRasterize,
+    pub backend: GaussianCloudBackend,
+    /// write splat depth before the opaque pass so meshes and splats occlude each other
+    pub depth_prepass: bool,
+    /// minimum splat alpha at a pixel for it to write depth in the prepass
+    pub depth_alpha_threshold: f32,
+    /// mip-splatting anti-aliasing, replaces the fixed screen space dilation with an energy preserving
+    /// 2d mip filter and applies the per gaussian 3d smoothing filter of clouds trained with it.
+    /// the 3d filter is a world space standard deviation, so it does not scale with the cloud transform or
+    /// `global_scale`. it is only read with the unpacked `buffer_storage` layout, other layouts apply the 2d filter alone
+    pub mip_filter: bool,
 }
 
-impl Default for GaussianCloudSettings {
+impl Default for GaussianCloudRasterSettings {
     fn default() -> Self {
         Self {
             aabb: false,
-            global_opacity: 1.0,
-            global_scale: 1.0,
             opacity_adaptive_radius: true,
-            visualize_bounding_box: false,
-            sort_mode: SortMode::default(),
             draw_mode: GaussianCloudDrawMode::default(),
-            gaussian_mode: GaussianMode::default(),
             rasterize_mode: GaussianCloudRasterize::default(),
+            backend: GaussianCloudBackend::default(),
+            depth_prepass: false,
+            depth_alpha_threshold: 0.9,
+            mip_filter: false,
+        }
+    }
+}
+
+
+/// debug visualizations of a cloud
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianCloudDebugSettings {
+    pub visualize_bounding_box: bool,
+}
+
+
+
+#[allow(deprecated)]
+pub use legacy::GaussianCloudSettings;
+
+// derived impls of the deprecated struct need the allow on an enclosing module
+#[allow(deprecated)]
+mod legacy {
+    use bevy::{
+        prelude::*,
+        ecs::{
+            component::ComponentId,
+            world::DeferredWorld,
+        },
+    };
+
+    use crate::sort::SortMode;
+
+    use super::{
+        GaussianCloudAppearance,
+        GaussianCloudBackend,
+        GaussianCloudDebugSettings,
+        GaussianCloudDrawMode,
+        GaussianCloudRasterize,
+        GaussianCloudRasterSettings,
+        GaussianCloudSortSettings,
+        GaussianMode,
+    };
+
+
+    /// combined settings from before the split into `GaussianCloudSortSettings`, `GaussianCloudAppearance`,
+    /// `GaussianCloudRasterSettings` and `GaussianCloudDebugSettings`
+    ///
+    /// inserting it writes those components and removes it again.
+    #[deprecated(note = "use GaussianCloudSortSettings, GaussianCloudAppearance, GaussianCloudRasterSettings and GaussianCloudDebugSettings")]
+    #[derive(
+        Component,
+        Clone,
+        Debug,
+        PartialEq,
+    )]
+    #[component(on_insert = split_gaussian_cloud_settings)]
+    pub struct GaussianCloudSettings {
+        pub aabb: bool,
+        pub global_opacity: f32,
+        pub global_scale: f32,
+        pub opacity_adaptive_radius: bool,
+        pub visualize_bounding_box: bool,
+        pub sort_mode: SortMode,
+        pub draw_mode: GaussianCloudDrawMode,
+        pub gaussian_mode: GaussianMode,
+        pub rasterize_mode: GaussianCloudRasterize,
+        pub backend: GaussianCloudBackend,
+        /// write splat depth before the opaque pass so meshes and splats occlude each other
+        pub depth_prepass: bool,
+        /// minimum splat alpha at a pixel for it to write depth in the prepass
+        pub depth_alpha_threshold: f32,
+    }
+
+    impl Default for GaussianCloudSettings {
+        fn default() -> Self {
+            let appearance = GaussianCloudAppearance::default();
+            let raster_settings = GaussianCloudRasterSettings::default();
+
+            Self {
+                aabb: raster_settings.aabb,
+                global_opacity: appearance.global_opacity,
+                global_scale: appearance.global_scale,
+                opacity_adaptive_radius: raster_settings.opacity_adaptive_radius,
+                visualize_bounding_box: false,
+                sort_mode: SortMode::default(),
+                draw_mode: raster_settings.draw_mode,
+                gaussian_mode: appearance.gaussian_mode,
+                rasterize_mode: raster_settings.rasterize_mode,
+                backend: raster_settings.backend,
+                depth_prepass: raster_settings.depth_prepass,
+                depth_alpha_threshold: raster_settings.depth_alpha_threshold,
+            }
         }
     }
+
+    impl GaussianCloudSettings {
+        /// the split components, fields added after the split keep their defaults
+        pub fn split(&self) -> (
+            GaussianCloudSortSettings,
+            GaussianCloudAppearance,
+            GaussianCloudRasterSettings,
+            GaussianCloudDebugSettings,
+        ) {
+            (
+                GaussianCloudSortSettings {
+                    sort_mode: self.sort_mode.clone(),
+                },
+                GaussianCloudAppearance {
+                    global_opacity: self.global_opacity,
+                    global_scale: self.global_scale,
+                    gaussian_mode: self.gaussian_mode,
+                    ..default()
+                },
+                GaussianCloudRasterSettings {
+                    aabb: self.aabb,
+                    opacity_adaptive_radius: self.opacity_adaptive_radius,
+                    draw_mode: self.draw_mode,
+                    rasterize_mode: self.rasterize_mode,
+                    backend: self.backend,
+                    depth_prepass: self.depth_prepass,
+                    depth_alpha_threshold: self.depth_alpha_threshold,
+                    ..default()
+                },
+                GaussianCloudDebugSettings {
+                    visualize_bounding_box: self.visualize_bounding_box,
+                },
+            )
+        }
+    }
+
+    fn split_gaussian_cloud_settings(
+        mut world: DeferredWorld,
+        entity: Entity,
+        _component_id: ComponentId,
+    ) {
+        let Some(settings) = world.get::<GaussianCloudSettings>(entity) else {
+            return;
+        };
+
+        let components = settings.split();
+        world.commands()
+            .entity(entity)
+            .insert(components)
+            .remove::<GaussianCloudSettings>();
+    }
 }
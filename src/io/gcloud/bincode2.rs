@@ -1,3 +1,5 @@
+use std::io::Read;
+
 use bincode2::{
     deserialize_from,
     serialize_into,
@@ -9,17 +11,29 @@ use flate2::{
 };
 
 use crate::{
-    gaussian::GaussianCloud,
+    GaussianCloud,
     io::codec::GaussianCloudCodec,
 };
 
 
+/// leads the decompressed stream of versioned clouds, unversioned clouds start with the position count
+const GCLOUD_MAGIC: [u8; 4] = *b"GCLD";
+
+/// version 1 appended `pbr_material` and `filter_3d` to the cloud
+const GCLOUD_VERSION: u32 = 1;
+
+/// bincode encoding of the empty vecs appended since the unversioned format
+const GCLOUD_V0_APPENDED: [u8; 16] = [0; 16];
+
+
 impl GaussianCloudCodec for GaussianCloud {
     fn encode(&self) -> Vec<u8> {
         let mut output = Vec::new();
 
         {
             let mut gz_encoder = GzEncoder::new(&mut output, Compression::default());
+            serialize_into(&mut gz_encoder, &GCLOUD_MAGIC).expect("failed to encode cloud");
+            serialize_into(&mut gz_encoder, &GCLOUD_VERSION).expect("failed to encode cloud");
             serialize_into(&mut gz_encoder, &self).expect("failed to encode cloud");
         }
 
@@ -27,8 +41,21 @@ impl GaussianCloudCodec for GaussianCloud {
     }
 
     fn decode(data: &[u8]) -> Self {
-        let decompressed = GzDecoder::new(data);
-        let cloud: GaussianCloud = deserialize_from(decompressed).expect("failed to decode cloud");
+        let mut decompressed = GzDecoder::new(data);
+
+        let mut magic = [0; 4];
+        decompressed.read_exact(&mut magic).expect("failed to decode cloud");
+
+        let cloud: GaussianCloud = if magic == GCLOUD_MAGIC {
+            let version: u32 = deserialize_from(&mut decompressed).expect("failed to decode cloud");
+            assert!(version <= GCLOUD_VERSION, "unsupported gcloud version {}", version);
+
+            deserialize_from(decompressed).expect("failed to decode cloud")
+        } else {
+            // the fields of unversioned clouds are a prefix of the current ones
+            let unversioned = (&magic[..]).chain(decompressed).chain(&GCLOUD_V0_APPENDED[..]);
+            deserialize_from(unversioned).expect("failed to decode cloud")
+        };
 
         cloud
     }
@@ -41,9 +41,7 @@ impl AssetLoader for GaussianCloudLoader {
                     let cursor = Cursor::new(bytes);
                     let mut f = BufReader::new(cursor);
 
-                    let gaussians = crate::io::ply::parse_ply(&mut f)?;
-
-                    Ok(GaussianCloud::from_gaussians(gaussians))
+                    crate::io::ply::parse_ply_cloud(&mut f)
                 }
 
                 #[cfg(not(feature = "io_ply"))]
@@ -5,3 +5,6 @@ pub mod writer;
 
 #[cfg(feature = "io_ply")]
 pub mod ply;
+
+#[cfg(feature = "io_spz")]
+pub mod spz;
@@ -1,4 +1,7 @@
-use std::io::BufRead;
+use std::io::{
+    BufRead,
+    Write,
+};
 
 use ply_rs::{
     ply::{
@@ -9,12 +12,16 @@ use ply_rs::{
 };
 
 use crate::{
-    material::spherical_harmonics::{
-        SH_CHANNELS,
-        SH_COEFF_COUNT,
-        SH_COEFF_COUNT_PER_CHANNEL,
+    material::{
+        pbr::PbrMaterial,
+        spherical_harmonics::{
+            SH_CHANNELS,
+            SH_COEFF_COUNT,
+            SH_COEFF_COUNT_PER_CHANNEL,
+        },
     },
     gaussian::packed::Gaussian,
+    GaussianCloud,
 };
 
 
@@ -50,11 +57,13 @@ impl PropertyAccess for Gaussian {
                 // }
 
                 // planar
-                let channel = i / SH_COEFF_COUNT_PER_CHANNEL;
-                let coefficient = if SH_COEFF_COUNT_PER_CHANNEL == 1 {
-                    1
+                let (channel, coefficient) = if SH_COEFF_COUNT_PER_CHANNEL == 1 {
+                    (i, 1)
                 } else {
-                    (i % (SH_COEFF_COUNT_PER_CHANNEL - 1)) + 1
+                    (
+                        i / (SH_COEFF_COUNT_PER_CHANNEL - 1),
+                        (i % (SH_COEFF_COUNT_PER_CHANNEL - 1)) + 1,
+                    )
                 };
 
                 let interleaved_idx = coefficient * SH_CHANNELS + channel;
@@ -70,18 +79,120 @@ impl PropertyAccess for Gaussian {
     }
 }
 
-pub fn parse_ply(mut reader: &mut dyn BufRead) -> Result<Vec<Gaussian>, std::io::Error> {
-    let gaussian_parser = Parser::<Gaussian>::new();
+
+/// gaussian with the pbr properties written by relightable 3dgs trainers and the mip-splatting 3d filter
+///
+/// attributes are expected activated, i.e. a linear base color, roughness and metallic in 0.0..=1.0
+#[derive(Clone, Copy, Debug, Default)]
+struct PlyGaussian {
+    gaussian: Gaussian,
+    base_color: [f32; 3],
+    roughness: f32,
+    metallic: f32,
+    has_material: bool,
+    filter_3d: f32,
+    has_filter_3d: bool,
+}
+
+impl PropertyAccess for PlyGaussian {
+    fn new() -> Self {
+        Self {
+            roughness: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn set_property(&mut self, key: String, property: Property) {
+        let Property::Float(v) = property else {
+            return self.gaussian.set_property(key, property);
+        };
+
+        if key == "filter_3D" || key == "filter_3d" {
+            self.filter_3d = v;
+            self.has_filter_3d = true;
+            return;
+        }
+
+        match key.as_ref() {
+            "base_color_0" | "albedo_0"     => self.base_color[0] = v,
+            "base_color_1" | "albedo_1"     => self.base_color[1] = v,
+            "base_color_2" | "albedo_2"     => self.base_color[2] = v,
+            "roughness"                     => self.roughness = v,
+            "metallic" | "metalness"        => self.metallic = v,
+            _ => return self.gaussian.set_property(key, property),
+        }
+
+        self.has_material = true;
+    }
+}
+
+impl PlyGaussian {
+    fn pbr_material(&self) -> PbrMaterial {
+        match self.has_material {
+            true => PbrMaterial::new(self.base_color, self.roughness, self.metallic),
+            false => PbrMaterial::default(),
+        }
+    }
+}
+
+
+pub fn parse_ply(reader: &mut dyn BufRead) -> Result<Vec<Gaussian>, std::io::Error> {
+    parse_ply_materials(reader).map(|(gaussians, _)| gaussians)
+}
+
+/// parses gaussians and their pbr materials, materials are empty when the ply has no pbr properties
+pub fn parse_ply_materials(reader: &mut dyn BufRead) -> Result<(Vec<Gaussian>, Vec<PbrMaterial>), std::io::Error> {
+    let vertices = parse_ply_vertices(reader)?;
+    let pbr_material = ply_pbr_material(&vertices);
+
+    Ok((ply_gaussians(vertices), pbr_material))
+}
+
+/// parses a cloud along with every optional per gaussian attribute the ply provides
+pub fn parse_ply_cloud(reader: &mut dyn BufRead) -> Result<GaussianCloud, std::io::Error> {
+    let vertices = parse_ply_vertices(reader)?;
+    let pbr_material = ply_pbr_material(&vertices);
+
+    let filter_3d = match vertices.iter().any(|vertex| vertex.has_filter_3d) {
+        true => vertices.iter().map(|vertex| vertex.filter_3d).collect(),
+        false => Vec::new(),
+    };
+
+    let mut cloud = GaussianCloud::from_gaussians(ply_gaussians(vertices));
+    cloud.set_pbr_material(pbr_material);
+    cloud.set_filter_3d(filter_3d);
+
+    Ok(cloud)
+}
+
+fn parse_ply_vertices(mut reader: &mut dyn BufRead) -> Result<Vec<PlyGaussian>, std::io::Error> {
+    let gaussian_parser = Parser::<PlyGaussian>::new();
     let header = gaussian_parser.read_header(&mut reader)?;
 
-    let mut cloud = Vec::new();
+    let mut vertices = Vec::new();
 
     for (_ignore_key, element) in &header.elements {
         if element.name == "vertex" {
-            cloud = gaussian_parser.read_payload_for_element(&mut reader, element, &header)?;
+            vertices = gaussian_parser.read_payload_for_element(&mut reader, element, &header)?;
         }
     }
 
+    Ok(vertices)
+}
+
+fn ply_pbr_material(vertices: &[PlyGaussian]) -> Vec<PbrMaterial> {
+    match vertices.iter().any(|vertex| vertex.has_material) {
+        true => vertices.iter().map(PlyGaussian::pbr_material).collect(),
+        false => Vec::new(),
+    }
+}
+
+/// activates the raw ply attributes and pads the gaussians to a multiple of 32
+fn ply_gaussians(vertices: Vec<PlyGaussian>) -> Vec<Gaussian> {
+    let mut cloud = vertices.into_iter()
+        .map(|vertex| vertex.gaussian)
+        .collect::<Vec<_>>();
+
     for gaussian in &mut cloud {
         gaussian.position_visibility.visibility = 1.0;
 
@@ -103,5 +214,78 @@ pub fn parse_ply(mut reader: &mut dyn BufRead) -> Result<Vec<Gaussian>, std::io:
     let pad = 32 - (cloud.len() % 32);
     cloud.extend(std::iter::repeat(Gaussian::default()).take(pad));
 
-    Ok(cloud)
+    cloud
+}
+
+
+/// writes the cloud as a binary little endian ply, inverting the activations applied by `parse_ply`
+pub fn write_ply(cloud: &GaussianCloud, writer: &mut dyn Write) -> Result<(), std::io::Error> {
+    let rest_count = (SH_COEFF_COUNT_PER_CHANNEL - 1) * SH_CHANNELS;
+
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format binary_little_endian 1.0")?;
+    writeln!(writer, "element vertex {}", cloud.len())?;
+    for property in ["x", "y", "z", "nx", "ny", "nz", "f_dc_0", "f_dc_1", "f_dc_2"] {
+        writeln!(writer, "property float {}", property)?;
+    }
+    for i in 0..rest_count {
+        writeln!(writer, "property float f_rest_{}", i)?;
+    }
+    for property in ["opacity", "scale_0", "scale_1", "scale_2", "rot_0", "rot_1", "rot_2", "rot_3"] {
+        writeln!(writer, "property float {}", property)?;
+    }
+
+    let has_material = !cloud.pbr_material.is_empty();
+    if has_material {
+        for property in ["base_color_0", "base_color_1", "base_color_2", "roughness", "metallic"] {
+            writeln!(writer, "property float {}", property)?;
+        }
+    }
+
+    let has_filter_3d = !cloud.filter_3d.is_empty();
+    if has_filter_3d {
+        writeln!(writer, "property float filter_3D")?;
+    }
+    writeln!(writer, "end_header")?;
+
+    for index in 0..cloud.len() {
+        let position = cloud.position(index);
+        let spherical_harmonic = cloud.spherical_harmonic(index);
+        let (rotation, scale, opacity) = cloud.rotation_scale_opacity(index);
+
+        let mut properties = Vec::with_capacity(17 + rest_count);
+        properties.extend_from_slice(position);
+        properties.extend_from_slice(&[0.0; 3]);
+        properties.extend((0..SH_CHANNELS).map(|channel| spherical_harmonic.get(channel)));
+
+        // planar, all coefficients of a channel are contiguous
+        for i in 0..rest_count {
+            let channel = i / (SH_COEFF_COUNT_PER_CHANNEL - 1);
+            let coefficient = i % (SH_COEFF_COUNT_PER_CHANNEL - 1) + 1;
+
+            properties.push(spherical_harmonic.get(coefficient * SH_CHANNELS + channel));
+        }
+
+        let opacity = opacity.clamp(1e-6, 1.0 - 1e-6);
+        properties.push((opacity / (1.0 - opacity)).ln());
+        properties.extend(scale.to_array().map(|scale| scale.max(1e-12).ln()));
+        properties.extend(rotation.to_array());
+
+        if has_material {
+            let material = cloud.pbr_material[index];
+
+            properties.extend(material.base_color());
+            properties.extend([material.roughness(), material.metallic()]);
+        }
+
+        if has_filter_3d {
+            properties.push(cloud.filter_3d(index));
+        }
+
+        for property in properties {
+            writer.write_all(&property.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
 }
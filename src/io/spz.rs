@@ -0,0 +1,106 @@
+use std::io::Write;
+
+use bevy::prelude::*;
+use flate2::{
+    write::GzEncoder,
+    Compression,
+};
+
+use crate::{
+    GaussianCloud,
+    material::spherical_harmonics::{
+        SH_CHANNELS,
+        SH_DEGREE,
+    },
+};
+
+
+pub const SPZ_MAGIC: u32 = 0x5053474e;
+pub const SPZ_VERSION: u32 = 2;
+
+/// spz stores spherical harmonics up to degree 3
+pub const SPZ_MAX_SH_DEGREE: usize = 3;
+
+const FRACTIONAL_BITS: u8 = 12;
+const COLOR_SCALE: f32 = 0.15;
+
+
+fn to_u8(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+fn quantize_sh(value: f32, bucket_size: i32) -> u8 {
+    let quantized = (value * 128.0).round() as i32 + 128;
+    let bucketed = (quantized + bucket_size / 2) / bucket_size * bucket_size;
+
+    bucketed.clamp(0, 255) as u8
+}
+
+
+/// writes the cloud as a gzip compressed spz (version 2) stream
+///
+/// positions are written as stored, without any coordinate system conversion.
+pub fn write_spz(cloud: &GaussianCloud, writer: &mut dyn Write) -> Result<(), std::io::Error> {
+    let sh_degree = SH_DEGREE.min(SPZ_MAX_SH_DEGREE);
+    let sh_rest_count = (sh_degree + 1) * (sh_degree + 1) - 1;
+
+    let count = cloud.len();
+
+    let mut positions = Vec::with_capacity(count * 9);
+    let mut alphas = Vec::with_capacity(count);
+    let mut colors = Vec::with_capacity(count * 3);
+    let mut scales = Vec::with_capacity(count * 3);
+    let mut rotations = Vec::with_capacity(count * 3);
+    let mut spherical_harmonics = Vec::with_capacity(count * sh_rest_count * SH_CHANNELS);
+
+    for index in 0..count {
+        for &coordinate in cloud.position(index) {
+            let fixed = (coordinate * (1 << FRACTIONAL_BITS) as f32).round() as i32;
+            positions.extend_from_slice(&fixed.to_le_bytes()[..3]);
+        }
+
+        let (rotation, scale, opacity) = cloud.rotation_scale_opacity(index);
+
+        alphas.push(to_u8(opacity * 255.0));
+
+        let spherical_harmonic = cloud.spherical_harmonic(index);
+        colors.extend((0..SH_CHANNELS).map(|channel| {
+            to_u8(spherical_harmonic.get(channel) * (COLOR_SCALE * 255.0) + 0.5 * 255.0)
+        }));
+
+        scales.extend(scale.to_array().map(|scale| to_u8((scale.max(1e-12).ln() + 10.0) * 16.0)));
+
+        // xyz of the unit quaternion with a positive w, w is recovered on load
+        let [w, x, y, z] = rotation.normalize_or(Vec4::X).to_array();
+        let sign = if w < 0.0 { -1.0 } else { 1.0 };
+        rotations.extend([x, y, z].map(|v| to_u8(v * sign * 127.5 + 127.5)));
+
+        // coefficient major, rgb interleaved, matching the in-memory layout
+        for coefficient in 1..=sh_rest_count {
+            let bucket_size = if coefficient < 4 { 8 } else { 16 };
+
+            for channel in 0..SH_CHANNELS {
+                let value = spherical_harmonic.get(coefficient * SH_CHANNELS + channel);
+                spherical_harmonics.push(quantize_sh(value, bucket_size));
+            }
+        }
+    }
+
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+
+    encoder.write_all(&SPZ_MAGIC.to_le_bytes())?;
+    encoder.write_all(&SPZ_VERSION.to_le_bytes())?;
+    encoder.write_all(&(count as u32).to_le_bytes())?;
+    encoder.write_all(&[sh_degree as u8, FRACTIONAL_BITS, 0, 0])?;
+
+    encoder.write_all(&positions)?;
+    encoder.write_all(&alphas)?;
+    encoder.write_all(&colors)?;
+    encoder.write_all(&scales)?;
+    encoder.write_all(&rotations)?;
+    encoder.write_all(&spherical_harmonics)?;
+
+    encoder.finish()?;
+
+    Ok(())
+}
@@ -1,4 +1,9 @@
-use std::io::Write;
+use std::{
+    io::Write,
+    path::Path,
+};
+
+use bevy::prelude::*;
 
 use crate::{
     GaussianCloud,
@@ -6,13 +11,81 @@ use crate::{
 };
 
 
+/// on-disk representation a gaussian cloud can be written as
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudFormat {
+    #[default]
+    Gcloud,
+    /// binary little endian ply with the property layout of the reference 3dgs implementation, requires `io_ply`
+    Ply,
+    /// gzip compressed, quantized spz (version 2), requires `io_spz`
+    Spz,
+}
+
+impl GaussianCloudFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref()
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase();
+
+        match extension.as_str() {
+            "gcloud" => Some(Self::Gcloud),
+            "ply" => Some(Self::Ply),
+            "spz" => Some(Self::Spz),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Gcloud => "gcloud",
+            Self::Ply => "ply",
+            Self::Spz => "spz",
+        }
+    }
+}
+
+
+pub fn write_gaussian_cloud(
+    cloud: &GaussianCloud,
+    writer: &mut dyn Write,
+    format: GaussianCloudFormat,
+) -> std::io::Result<()> {
+    match format {
+        GaussianCloudFormat::Gcloud => writer.write_all(cloud.encode().as_slice()),
+
+        #[cfg(feature = "io_ply")]
+        GaussianCloudFormat::Ply => crate::io::ply::write_ply(cloud, writer),
+
+        #[cfg(feature = "io_spz")]
+        GaussianCloudFormat::Spz => crate::io::spz::write_spz(cloud, writer),
+
+        #[allow(unreachable_patterns)]
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("{} output is not enabled", format.extension()),
+        )),
+    }
+}
+
 pub fn write_gaussian_cloud_to_file(
     cloud: &GaussianCloud,
-    path: &str,
-) {
-    let gcloud_file = std::fs::File::create(path).expect("failed to create file");
-    let mut gcloud_writer = std::io::BufWriter::new(gcloud_file);
+    path: impl AsRef<Path>,
+    format: GaussianCloudFormat,
+) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    let mut writer = std::io::BufWriter::new(file);
 
-    let data = cloud.encode();
-    gcloud_writer.write_all(data.as_slice()).expect("failed to write to gcloud file");
+    write_gaussian_cloud(cloud, &mut writer, format)?;
+    writer.flush()
 }
@@ -2,6 +2,9 @@ use bevy::prelude::*;
 
 pub use camera::GaussianCamera;
 
+#[allow(deprecated)]
+pub use gaussian::settings::GaussianCloudSettings;
+
 pub use gaussian::{
     packed::Gaussian,
     cloud::{
@@ -10,8 +13,13 @@ pub use gaussian::{
     },
     rand::random_gaussians,
     settings::{
+        GaussianCloudAppearance,
+        GaussianCloudBackend,
+        GaussianCloudDebugSettings,
+        GaussianCloudRasterSettings,
         GaussianCloudRasterize,
-        GaussianCloudSettings,
+        GaussianCloudSortSettings,
+        GaussianColorGrading,
         GaussianMode,
     },
 };
@@ -23,6 +31,7 @@ use io::loader::GaussianCloudLoader;
 pub mod camera;
 pub mod gaussian;
 pub mod io;
+pub mod lighting;
 pub mod material;
 pub mod morph;
 pub mod query;
@@ -30,6 +39,9 @@ pub mod render;
 pub mod sort;
 pub mod utils;
 
+#[cfg(feature = "capture")]
+pub mod capture;
+
 #[cfg(feature = "noise")]
 pub mod noise;
 
@@ -47,17 +59,25 @@ impl Plugin for GaussianSplattingPlugin {
 
         app.init_asset_loader::<GaussianCloudLoader>();
 
-        app.register_type::<GaussianCloudSettings>();
+        app.register_type::<GaussianCloudSortSettings>();
+        app.register_type::<GaussianCloudAppearance>();
+        app.register_type::<GaussianColorGrading>();
+        app.register_type::<GaussianCloudRasterSettings>();
+        app.register_type::<GaussianCloudDebugSettings>();
 
         app.add_plugins((
             camera::GaussianCameraPlugin,
             gaussian::cloud::GaussianCloudPlugin,
             render::RenderPipelinePlugin,
+            lighting::LightingPlugin,
             material::MaterialPlugin,
             query::QueryPlugin,
         ));
 
         #[cfg(feature = "noise")]
         app.add_plugins(noise::NoisePlugin);
+
+        #[cfg(feature = "capture")]
+        app.add_plugins(capture::CapturePlugin);
     }
 }
@@ -0,0 +1,606 @@
+use bevy::{
+    prelude::*,
+    color::ColorToComponents,
+    core_pipeline::core_3d::graph::{
+        Core3d,
+        Node3d,
+    },
+    ecs::{
+        query::QueryItem,
+        system::SystemParam,
+    },
+    pbr::{
+        graph::NodePbr,
+        AmbientLight,
+        ExtractedDirectionalLight,
+        ExtractedPointLight,
+        LightEntity,
+        ShadowView,
+        ViewLightEntities,
+        ViewShadowBindings,
+    },
+    render::{
+        render_graph::{
+            NodeRunError,
+            RenderGraph,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+            ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::*,
+        renderer::{
+            RenderContext,
+            RenderDevice,
+            RenderQueue,
+        },
+        view::{
+            ExtractedView,
+            ViewUniformOffset,
+        },
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+};
+
+use crate::{
+    camera::GaussianCamera,
+    gaussian::settings::{
+        GaussianCloudAppearance,
+        GaussianCloudBackend,
+        GaussianCloudDebugSettings,
+        GaussianCloudRasterSettings,
+    },
+    lighting::{
+        environmental::{
+            GaussianViewEnvironment,
+            ENVIRONMENT_SH_COEFFICIENTS,
+        },
+        GaussianCloudLighting,
+    },
+    render::{
+        prepass::draw_depth_phase,
+        GaussianCloudPass,
+        GaussianCloudPipeline,
+        GaussianCloudPipelineKey,
+        GaussianUniformBindGroups,
+        GaussianViewBindGroup,
+    },
+    sort::{
+        GlobalSortMember,
+        SortTrigger,
+    },
+};
+
+
+pub const MAX_GAUSSIAN_DIRECTIONAL_LIGHTS: usize = 4;
+pub const MAX_GAUSSIAN_POINT_LIGHTS: usize = 16;
+pub const MAX_GAUSSIAN_SHADOW_CASCADES: usize = 4;
+
+/// shadow layer of a directional light without a shadow map in the view
+pub const NO_SHADOW_LAYER: u32 = u32::MAX;
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GaussianShadowPassLabel;
+
+
+#[derive(Default)]
+pub struct DirectLightingPlugin;
+
+impl Plugin for DirectLightingPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<GaussianLightsBuffer>()
+                .add_systems(
+                    Render,
+                    (
+                        prepare_gaussian_lights.in_set(RenderSet::PrepareResources),
+                        queue_shadow_casters.in_set(RenderSet::Queue),
+                    ),
+                );
+
+            // shadow maps are rendered by the bevy pbr plugin, without it there is nothing to cast into
+            let has_shadow_pass = render_app.world()
+                .resource::<RenderGraph>()
+                .get_sub_graph(Core3d)
+                .is_some_and(|graph| graph.get_node_state(NodePbr::ShadowPass).is_ok());
+
+            if has_shadow_pass {
+                render_app
+                    .add_render_graph_node::<ViewNodeRunner<GaussianShadowPassNode>>(
+                        Core3d,
+                        GaussianShadowPassLabel,
+                    )
+                    .add_render_graph_edges(
+                        Core3d,
+                        (
+                            NodePbr::ShadowPass,
+                            GaussianShadowPassLabel,
+                            Node3d::StartMainPass,
+                        ),
+                    );
+            } else {
+                warn!("bevy pbr plugin is missing, gaussian clouds will not cast shadows");
+            }
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<GaussianShadowSampling>();
+        }
+    }
+}
+
+
+pub fn shader_defs() -> Vec<ShaderDefVal> {
+    vec![
+        "LIGHTING".into(),
+        ShaderDefVal::UInt("MAX_GAUSSIAN_DIRECTIONAL_LIGHTS".into(), MAX_GAUSSIAN_DIRECTIONAL_LIGHTS as u32),
+        ShaderDefVal::UInt("MAX_GAUSSIAN_POINT_LIGHTS".into(), MAX_GAUSSIAN_POINT_LIGHTS as u32),
+        ShaderDefVal::UInt("MAX_GAUSSIAN_SHADOW_CASCADES".into(), MAX_GAUSSIAN_SHADOW_CASCADES as u32),
+    ]
+}
+
+
+#[derive(Debug, Default, Clone, Copy, ShaderType)]
+pub struct GaussianDirectionalLightUniform {
+    pub cascade_clip_from_world: [Mat4; MAX_GAUSSIAN_SHADOW_CASCADES],
+    /// view space distance each cascade covers
+    pub cascade_far_bounds: Vec4,
+    /// world space normal offset of each cascade, the normal bias scaled by the texel size
+    pub cascade_normal_offsets: Vec4,
+    /// linear color premultiplied by illuminance
+    pub color: Vec3,
+    pub shadow_layer: u32,
+    pub direction_to_light: Vec3,
+    pub cascade_count: u32,
+    pub shadow_depth_bias: f32,
+}
+
+#[derive(Debug, Default, Clone, Copy, ShaderType)]
+pub struct GaussianPointLightUniform {
+    pub position: Vec3,
+    pub range: f32,
+    /// linear color premultiplied by luminous intensity
+    pub color: Vec3,
+    pub spot_scale: f32,
+    pub spot_direction: Vec3,
+    pub spot_offset: f32,
+}
+
+impl From<&ExtractedPointLight> for GaussianPointLightUniform {
+    fn from(light: &ExtractedPointLight) -> Self {
+        // point lights use a cone that covers every direction
+        let (spot_scale, spot_offset) = light.spot_light_angles.map_or((0.0, 1.0), |(inner, outer)| {
+            let cos_outer = outer.cos();
+            let spot_scale = 1.0 / (inner.cos() - cos_outer).max(1e-4);
+
+            (spot_scale, -cos_outer * spot_scale)
+        });
+
+        Self {
+            position: light.transform.translation(),
+            range: light.range,
+            color: light.color.to_vec3() * light.intensity,
+            spot_scale,
+            spot_direction: light.transform.forward().into(),
+            spot_offset,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, ShaderType)]
+pub struct GaussianLightsUniform {
+    pub directional_lights: [GaussianDirectionalLightUniform; MAX_GAUSSIAN_DIRECTIONAL_LIGHTS],
+    pub point_lights: [GaussianPointLightUniform; MAX_GAUSSIAN_POINT_LIGHTS],
+    /// l2 spherical harmonics of the view environment map, zero without one
+    pub environment: [Vec4; ENVIRONMENT_SH_COEFFICIENTS],
+    pub environment_rotation: Mat3,
+    pub ambient: Vec3,
+    pub directional_light_count: u32,
+    pub point_light_count: u32,
+}
+
+
+#[derive(Resource, Default)]
+pub struct GaussianLightsBuffer {
+    pub buffer: DynamicUniformBuffer<GaussianLightsUniform>,
+}
+
+/// offset of the view lights in `GaussianLightsBuffer`
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GaussianViewLightsOffset(pub u32);
+
+/// bevy light view a gaussian camera renders shadow casting clouds into
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct GaussianShadowView;
+
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn prepare_gaussian_lights(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut lights_buffer: ResMut<GaussianLightsBuffer>,
+    ambient_light: Res<AmbientLight>,
+    directional_lights: Query<(Entity, &ExtractedDirectionalLight)>,
+    point_lights: Query<&ExtractedPointLight>,
+    views: Query<
+        (
+            Entity,
+            Option<&ViewLightEntities>,
+            Option<&GaussianViewEnvironment>,
+        ),
+        With<GaussianCamera>,
+    >,
+    light_views: Query<(&LightEntity, &ExtractedView)>,
+) {
+    lights_buffer.buffer.clear();
+
+    let mut point_light_uniforms = [GaussianPointLightUniform::default(); MAX_GAUSSIAN_POINT_LIGHTS];
+    let mut point_light_count = 0;
+    for (uniform, light) in point_light_uniforms.iter_mut().zip(point_lights.iter()) {
+        *uniform = light.into();
+        point_light_count += 1;
+    }
+
+    let ambient = ambient_light.color.to_linear().to_vec3() * ambient_light.brightness;
+
+    for (view_entity, view_lights, environment) in views.iter() {
+        let mut uniform = GaussianLightsUniform {
+            point_lights: point_light_uniforms,
+            point_light_count,
+            ambient,
+            ..default()
+        };
+
+        if let Some(environment) = environment {
+            uniform.environment = environment.coefficients;
+            uniform.environment_rotation = environment.rotation;
+        }
+
+        for (index, (_, light)) in directional_lights.iter()
+            .take(MAX_GAUSSIAN_DIRECTIONAL_LIGHTS)
+            .enumerate()
+        {
+            let directional = &mut uniform.directional_lights[index];
+
+            directional.color = light.color.to_vec3() * light.illuminance;
+            directional.direction_to_light = light.transform.back().into();
+            directional.shadow_depth_bias = light.shadow_depth_bias;
+            directional.shadow_layer = NO_SHADOW_LAYER;
+
+            uniform.directional_light_count += 1;
+        }
+
+        // directional shadow map layers are allocated in light view order, one per cascade
+        let directional_indices = directional_lights.iter()
+            .take(MAX_GAUSSIAN_DIRECTIONAL_LIGHTS)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+
+        let mut layer = 0;
+        for light_view_entity in view_lights.iter().flat_map(|view_lights| view_lights.lights.iter()) {
+            let Ok((light_view, extracted_view)) = light_views.get(*light_view_entity) else {
+                continue;
+            };
+
+            commands.entity(*light_view_entity).insert(GaussianShadowView);
+
+            let LightEntity::Directional { light_entity, cascade_index } = *light_view else {
+                continue;
+            };
+
+            let cascade_layer = layer;
+            layer += 1;
+
+            let Some(index) = directional_indices.iter().position(|entity| *entity == light_entity) else {
+                continue;
+            };
+            let Ok((_, light)) = directional_lights.get(light_entity) else {
+                continue;
+            };
+
+            if cascade_index >= MAX_GAUSSIAN_SHADOW_CASCADES {
+                continue;
+            }
+
+            let directional = &mut uniform.directional_lights[index];
+
+            if cascade_index == 0 {
+                directional.shadow_layer = cascade_layer;
+            }
+
+            directional.cascade_clip_from_world[cascade_index] = extracted_view.clip_from_world
+                .unwrap_or_else(|| extracted_view.clip_from_view * extracted_view.world_from_view.compute_matrix().inverse());
+            directional.cascade_far_bounds[cascade_index] = light.cascade_shadow_config.bounds
+                .get(cascade_index)
+                .copied()
+                .unwrap_or(f32::MAX);
+
+            // world space width of a shadow map texel, cascades use an orthographic projection
+            let texel_size = 2.0 / (extracted_view.clip_from_view.x_axis.x * extracted_view.viewport.z as f32).max(f32::EPSILON);
+            directional.cascade_normal_offsets[cascade_index] = light.shadow_normal_bias * texel_size;
+            directional.cascade_count = directional.cascade_count.max(cascade_index as u32 + 1);
+        }
+
+        let offset = lights_buffer.buffer.push(&uniform);
+        commands.entity(view_entity).insert(GaussianViewLightsOffset(offset));
+    }
+
+    lights_buffer.buffer.write_buffer(&render_device, &render_queue);
+}
+
+
+#[derive(Resource)]
+pub struct GaussianShadowSampling {
+    pub sampler: Sampler,
+    /// bound for views without shadow maps, e.g. the light views themselves
+    pub fallback_view: TextureView,
+}
+
+impl FromWorld for GaussianShadowSampling {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("gaussian_shadow_sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Nearest,
+            compare: Some(CompareFunction::GreaterEqual),
+            ..default()
+        });
+
+        let fallback = render_device.create_texture(&TextureDescriptor {
+            label: Some("gaussian_shadow_fallback"),
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Depth32Float,
+            usage: TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let fallback_view = fallback.create_view(&TextureViewDescriptor {
+            label: Some("gaussian_shadow_fallback_view"),
+            dimension: Some(SHADOW_VIEW_DIMENSION),
+            aspect: TextureAspect::DepthOnly,
+            ..default()
+        });
+
+        Self {
+            sampler,
+            fallback_view,
+        }
+    }
+}
+
+
+#[cfg(not(feature = "webgl2"))]
+const SHADOW_VIEW_DIMENSION: TextureViewDimension = TextureViewDimension::D2Array;
+#[cfg(feature = "webgl2")]
+const SHADOW_VIEW_DIMENSION: TextureViewDimension = TextureViewDimension::D2;
+
+/// lights (2), directional shadow maps (3) and their comparison sampler (4) of the gaussian view layout
+pub fn view_layout_entries() -> Vec<BindGroupLayoutEntry> {
+    vec![
+        BindGroupLayoutEntry {
+            binding: 2,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: Some(GaussianLightsUniform::min_size()),
+            },
+            count: None,
+        },
+        BindGroupLayoutEntry {
+            binding: 3,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Texture {
+                sample_type: TextureSampleType::Depth,
+                view_dimension: SHADOW_VIEW_DIMENSION,
+                multisampled: false,
+            },
+            count: None,
+        },
+        BindGroupLayoutEntry {
+            binding: 4,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Sampler(SamplerBindingType::Comparison),
+            count: None,
+        },
+    ]
+}
+
+#[derive(SystemParam)]
+pub struct GaussianViewLighting<'w, 's> {
+    lights_buffer: Res<'w, GaussianLightsBuffer>,
+    shadow_sampling: Res<'w, GaussianShadowSampling>,
+    views: Query<
+        'w,
+        's,
+        (
+            Option<&'static GaussianViewLightsOffset>,
+            Option<&'static ViewShadowBindings>,
+        ),
+    >,
+}
+
+impl GaussianViewLighting<'_, '_> {
+    /// none until the lights of a gaussian camera have been written
+    pub fn bind_group_entries(&self, view: Entity) -> Option<Vec<BindGroupEntry<'_>>> {
+        let buffer = self.lights_buffer.buffer.buffer()?;
+        let (offset, shadow_bindings) = self.views.get(view).ok()?;
+
+        let shadow_view = shadow_bindings.map_or(
+            &self.shadow_sampling.fallback_view,
+            |bindings| &bindings.directional_light_depth_texture_view,
+        );
+
+        Some(vec![
+            BindGroupEntry {
+                binding: 2,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer,
+                    offset: offset.map_or(0, |offset| offset.0 as u64),
+                    size: Some(GaussianLightsUniform::min_size()),
+                }),
+            },
+            BindGroupEntry {
+                binding: 3,
+                resource: BindingResource::TextureView(shadow_view),
+            },
+            BindGroupEntry {
+                binding: 4,
+                resource: BindingResource::Sampler(&self.shadow_sampling.sampler),
+            },
+        ])
+    }
+}
+
+
+/// shadow casting clouds of a gaussian camera, drawn into each of its light views
+#[derive(Component, Default)]
+pub struct GaussianShadowPhase {
+    pub items: Vec<(Entity, CachedRenderPipelineId)>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_shadow_casters(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    custom_pipeline: Res<GaussianCloudPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GaussianCloudPipeline>>,
+    views: Query<Entity, (With<GaussianCamera>, With<ViewLightEntities>)>,
+    gaussian_clouds: Query<
+        (
+            Entity,
+            &GaussianCloudAppearance,
+            &GaussianCloudRasterSettings,
+            &GaussianCloudDebugSettings,
+            &GaussianCloudLighting,
+        ),
+        Without<GlobalSortMember>,
+    >,
+) {
+    let items = gaussian_clouds.iter()
+        .filter(|(_, _, raster_settings, _, lighting)| {
+            lighting.cast_shadows && raster_settings.backend == GaussianCloudBackend::Instanced
+        })
+        .map(|(
+            entity,
+            appearance,
+            raster_settings,
+            debug_settings,
+            _,
+        )| {
+            let key = GaussianCloudPipelineKey {
+                sample_count: 1,
+                pass: GaussianCloudPass::Shadow,
+                ..GaussianCloudPipelineKey::from_settings(appearance, raster_settings, debug_settings)
+            };
+
+            (entity, pipelines.specialize(&pipeline_cache, &custom_pipeline, key))
+        })
+        .collect::<Vec<_>>();
+
+    for view_entity in views.iter() {
+        commands.entity(view_entity).insert(GaussianShadowPhase {
+            items: items.clone(),
+        });
+    }
+}
+
+
+#[derive(Default)]
+pub struct GaussianShadowPassNode;
+
+impl ViewNode for GaussianShadowPassNode {
+    type ViewQuery = (
+        &'static GaussianCamera,
+        &'static SortTrigger,
+        &'static ViewLightEntities,
+        &'static GaussianShadowPhase,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (
+            gaussian_camera,
+            sort_trigger,
+            view_lights,
+            phase,
+        ): QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        if gaussian_camera.warmup || phase.items.is_empty() {
+            return Ok(());
+        }
+
+        let Some(uniform_bind_group) = world.resource::<GaussianUniformBindGroups>().base_bind_group.as_ref() else {
+            return Ok(());
+        };
+
+        for light_view_entity in view_lights.lights.iter() {
+            let Ok(light_view) = world.get_entity(*light_view_entity) else {
+                continue;
+            };
+
+            let (
+                Some(shadow_view),
+                Some(view_bind_group),
+                Some(view_uniform_offset),
+            ) = (
+                light_view.get::<ShadowView>(),
+                light_view.get::<GaussianViewBindGroup>(),
+                light_view.get::<ViewUniformOffset>(),
+            ) else {
+                continue;
+            };
+
+            // bevy clears the shadow map in its own shadow pass, splats are depth tested against meshes
+            let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("gaussian cloud shadow pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(shadow_view.depth_attachment.get_attachment(StoreOp::Store)),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_bind_group(
+                0,
+                &view_bind_group.value,
+                &[view_uniform_offset.offset],
+            );
+
+            // the camera's sort culls to its own frustum, light views draw its whole entry range
+            draw_depth_phase(
+                &mut pass,
+                world,
+                uniform_bind_group,
+                sort_trigger.camera_index,
+                false,
+                &phase.items,
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::{
+    prelude::*,
+    ecs::query::QueryItem,
+    pbr::environment_map::EnvironmentMapLight,
+    render::{
+        extract_component::{
+            ExtractComponent,
+            ExtractComponentPlugin,
+        },
+        render_resource::{
+            TextureDimension,
+            TextureFormat,
+        },
+    },
+    utils::HashSet,
+};
+
+use crate::camera::GaussianCamera;
+
+
+/// number of l2 spherical harmonic coefficients
+pub const ENVIRONMENT_SH_COEFFICIENTS: usize = 9;
+
+/// texels sampled along each cube face edge, larger diffuse maps are strided
+const MAX_PROJECTION_SAMPLES: u32 = 64;
+
+
+#[derive(Default)]
+pub struct EnvironmentLightingPlugin;
+
+impl Plugin for EnvironmentLightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianEnvironmentIrradiance>();
+        app.add_plugins(ExtractComponentPlugin::<GaussianEnvironmentIrradiance>::default());
+
+        app.add_systems(Update, project_environment_maps);
+    }
+}
+
+
+/// diffuse map of a gaussian camera `EnvironmentMapLight` projected onto l2 spherical harmonics
+///
+/// maintained by `EnvironmentLightingPlugin`, evaluated along each gaussian normal when relighting
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    Default,
+    Reflect,
+)]
+#[reflect(Component)]
+pub struct GaussianEnvironmentIrradiance {
+    /// diffuse map the coefficients were projected from
+    pub image: AssetId<Image>,
+    /// linear rgb coefficients, bands 0 to 2
+    pub coefficients: [Vec3; ENVIRONMENT_SH_COEFFICIENTS],
+}
+
+impl GaussianEnvironmentIrradiance {
+    /// diffuse radiance in the environment map direction `direction`
+    pub fn evaluate(&self, direction: Vec3) -> Vec3 {
+        sh_basis(direction.normalize_or_zero())
+            .iter()
+            .zip(self.coefficients.iter())
+            .map(|(basis, coefficient)| *coefficient * *basis)
+            .sum()
+    }
+}
+
+/// render world environment of a view, premultiplied by the environment map intensity
+#[derive(Component, Clone, Debug, Default)]
+pub struct GaussianViewEnvironment {
+    pub coefficients: [Vec4; ENVIRONMENT_SH_COEFFICIENTS],
+    /// rotates world space normals into environment map space
+    pub rotation: Mat3,
+}
+
+impl ExtractComponent for GaussianEnvironmentIrradiance {
+    type QueryData = (
+        &'static Self,
+        &'static EnvironmentMapLight,
+    );
+
+    type QueryFilter = With<GaussianCamera>;
+    type Out = GaussianViewEnvironment;
+
+    fn extract_component((irradiance, environment_map): QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        GaussianViewEnvironment {
+            coefficients: irradiance.coefficients.map(|coefficient| (coefficient * environment_map.intensity).extend(0.0)),
+            rotation: Mat3::from_quat(environment_map.rotation.inverse()),
+        }.into()
+    }
+}
+
+
+fn project_environment_maps(
+    mut commands: Commands,
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    cameras: Query<
+        (
+            Entity,
+            &EnvironmentMapLight,
+            Option<&GaussianEnvironmentIrradiance>,
+        ),
+        With<GaussianCamera>,
+    >,
+    stale: Query<
+        Entity,
+        (
+            With<GaussianEnvironmentIrradiance>,
+            Without<EnvironmentMapLight>,
+        ),
+    >,
+) {
+    let changed = image_events.read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    for (entity, environment_map, irradiance) in cameras.iter() {
+        let id = environment_map.diffuse_map.id();
+
+        let outdated = irradiance.is_none_or(|irradiance| irradiance.image != id) || changed.contains(&id);
+        if !outdated {
+            continue;
+        }
+
+        // wait for the diffuse map to load, the previous projection is kept until then
+        let Some(image) = images.get(id) else {
+            continue;
+        };
+
+        let coefficients = project_irradiance(image).unwrap_or_else(|| {
+            warn!(
+                "environment map {:?} cannot be projected for gaussian relighting, expected an uncompressed cubemap in main world memory, got {:?}",
+                id,
+                image.texture_descriptor.format,
+            );
+
+            default()
+        });
+
+        commands.entity(entity).insert(GaussianEnvironmentIrradiance {
+            image: id,
+            coefficients,
+        });
+    }
+
+    for entity in stale.iter() {
+        commands.entity(entity).remove::<GaussianEnvironmentIrradiance>();
+    }
+}
+
+
+/// project the base mip of a cubemap onto l2 spherical harmonics
+///
+/// directions match bevy environment map sampling, i.e. cube space with z flipped
+pub fn project_irradiance(image: &Image) -> Option<[Vec3; ENVIRONMENT_SH_COEFFICIENTS]> {
+    let descriptor = &image.texture_descriptor;
+    let size = descriptor.size.width;
+
+    if descriptor.dimension != TextureDimension::D2
+        || descriptor.size.depth_or_array_layers != 6
+        || descriptor.size.height != size
+        || size == 0
+    {
+        return None;
+    }
+
+    let format = descriptor.format;
+    if format.block_dimensions() != (1, 1) {
+        return None;
+    }
+    let pixel_size = format.block_copy_size(None)? as usize;
+
+    // layers are stored one after another, each with its full mip chain
+    let layer_size = (0..descriptor.mip_level_count)
+        .map(|mip| ((size >> mip).max(1) as usize).pow(2) * pixel_size)
+        .sum::<usize>();
+
+    if image.data.len() < layer_size * 6 {
+        return None;
+    }
+
+    let step = size.div_ceil(MAX_PROJECTION_SAMPLES);
+    let texel_scale = 2.0 / size as f32;
+
+    let mut coefficients = [Vec3::ZERO; ENVIRONMENT_SH_COEFFICIENTS];
+    let mut total_weight = 0.0;
+
+    for face in 0..6 {
+        for y in (0..size).step_by(step as usize) {
+            for x in (0..size).step_by(step as usize) {
+                let offset = face * layer_size + (y * size + x) as usize * pixel_size;
+                let radiance = decode_texel(format, &image.data[offset..offset + pixel_size])?;
+                if !radiance.is_finite() {
+                    continue;
+                }
+
+                let u = (x as f32 + 0.5) * texel_scale - 1.0;
+                let v = (y as f32 + 0.5) * texel_scale - 1.0;
+
+                let cube_direction = match face {
+                    0 => Vec3::new(1.0, -v, -u),
+                    1 => Vec3::new(-1.0, -v, u),
+                    2 => Vec3::new(u, 1.0, v),
+                    3 => Vec3::new(u, -1.0, -v),
+                    4 => Vec3::new(u, -v, 1.0),
+                    _ => Vec3::new(-u, -v, -1.0),
+                };
+
+                // solid angle subtended by the texel
+                let weight = (1.0 + u * u + v * v).powf(-1.5);
+                total_weight += weight;
+
+                let direction = (cube_direction * Vec3::new(1.0, 1.0, -1.0)).normalize();
+                for (coefficient, basis) in coefficients.iter_mut().zip(sh_basis(direction)) {
+                    *coefficient += radiance * basis * weight;
+                }
+            }
+        }
+    }
+
+    if total_weight <= 0.0 {
+        return None;
+    }
+
+    let normalization = 4.0 * std::f32::consts::PI / total_weight;
+
+    Some(coefficients.map(|coefficient| coefficient * normalization))
+}
+
+
+fn sh_basis(direction: Vec3) -> [f32; ENVIRONMENT_SH_COEFFICIENTS] {
+    let Vec3 { x, y, z } = direction;
+
+    [
+        0.282095,
+        0.488603 * y,
+        0.488603 * z,
+        0.488603 * x,
+        1.092548 * x * y,
+        1.092548 * y * z,
+        0.315392 * (3.0 * z * z - 1.0),
+        1.092548 * x * z,
+        0.546274 * (x * x - y * y),
+    ]
+}
+
+fn decode_texel(format: TextureFormat, bytes: &[u8]) -> Option<Vec3> {
+    let channel = |index: usize| bytes[index] as f32 / 255.0;
+    let half = |index: usize| f16_to_f32(u16::from_le_bytes([bytes[index * 2], bytes[index * 2 + 1]]));
+    let float = |index: usize| f32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap());
+
+    let color = match format {
+        TextureFormat::Rgba8Unorm => Vec3::new(channel(0), channel(1), channel(2)),
+        TextureFormat::Rgba8UnormSrgb => LinearRgba::from(Srgba::rgb(channel(0), channel(1), channel(2))).to_vec3(),
+        TextureFormat::Bgra8UnormSrgb => LinearRgba::from(Srgba::rgb(channel(2), channel(1), channel(0))).to_vec3(),
+        TextureFormat::Rgba16Float => Vec3::new(half(0), half(1), half(2)),
+        TextureFormat::Rgba32Float => Vec3::new(float(0), float(1), float(2)),
+        TextureFormat::Rgb9e5Ufloat => {
+            let packed = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+            let scale = 2f32.powi((packed >> 27) as i32 - 15 - 9);
+
+            Vec3::new(
+                (packed & 0x1ff) as f32,
+                ((packed >> 9) & 0x1ff) as f32,
+                ((packed >> 18) & 0x1ff) as f32,
+            ) * scale
+        },
+        _ => return None,
+    };
+
+    color.into()
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f => if mantissa == 0.0 { f32::INFINITY } else { f32::NAN },
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
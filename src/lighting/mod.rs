@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+#[cfg(feature = "lighting")]
+pub mod direct;
+#[cfg(feature = "lighting")]
+pub mod environmental;
+
+
+/// relights a cloud with bevy `DirectionalLight`, `PointLight` and `SpotLight` entities and the
+/// `EnvironmentMapLight` of the camera, requires `lighting`
+///
+/// splat colors are treated as albedo and shaded along a per-gaussian normal, the shortest scale axis
+/// (the surfel normal for 2dgs). only the instanced backend is relit.
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianCloudLighting {
+    /// blend between the captured color (0.0) and the relit color (1.0)
+    pub relight: f32,
+    /// darken gaussians inside directional light shadow maps
+    pub receive_shadows: bool,
+    /// draw the cloud into the shadow maps of bevy lights
+    pub cast_shadows: bool,
+}
+
+impl Default for GaussianCloudLighting {
+    fn default() -> Self {
+        Self {
+            relight: 1.0,
+            receive_shadows: true,
+            cast_shadows: true,
+        }
+    }
+}
+
+impl GaussianCloudLighting {
+    /// render world state of clouds without a lighting component
+    pub const UNLIT: Self = Self {
+        relight: 0.0,
+        receive_shadows: false,
+        cast_shadows: false,
+    };
+
+    pub fn is_lit(&self) -> bool {
+        cfg!(feature = "lighting") && self.relight > 0.0
+    }
+}
+
+
+#[derive(Default)]
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianCloudLighting>();
+
+        #[cfg(feature = "lighting")]
+        app.add_plugins((
+            direct::DirectLightingPlugin,
+            environmental::EnvironmentLightingPlugin,
+        ));
+    }
+}
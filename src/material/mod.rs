@@ -1,10 +1,9 @@
 use bevy::prelude::*;
 
 pub mod depth;
-pub mod spherical_harmonics;
-
-#[cfg(feature = "material_noise")]
 pub mod noise;
+pub mod pbr;
+pub mod spherical_harmonics;
 
 
 #[derive(Default)]
@@ -16,6 +15,9 @@ impl Plugin for MaterialPlugin {
         #[cfg(feature = "material_noise")]
         app.add_plugins(noise::NoiseMaterialPlugin);
 
+        #[cfg(feature = "lighting")]
+        app.add_plugins(pbr::PbrMaterialPlugin);
+
         app.add_plugins((
             depth::DepthMaterialPlugin,
             spherical_harmonics::SphericalHarmonicCoefficientsPlugin,
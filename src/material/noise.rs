@@ -1,81 +1,141 @@
-use bevy::prelude::*;
-use noise::{
-    NoiseFn,
-    RidgedMulti,
-    Simplex,
+use bevy::{
+    prelude::*,
+    asset::load_internal_asset,
+    render::render_resource::ShaderType,
 };
-
-use crate::{
-    Gaussian,
-    GaussianCloud,
-    GaussianCloudHandle,
+use rand::{
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
 };
 
 
-#[derive(Component, Debug, Reflect)]
-pub struct NoiseMaterial {
-    pub scale: f32,
-}
-impl Default for NoiseMaterial {
-    fn default() -> Self {
-        NoiseMaterial {
-            scale: 1.0,
-        }
-    }
-}
+const NOISE_MATERIAL_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(871239564);
+
+/// simplex noise repeats every 289 units, seeds are scattered within one period
+const NOISE_PERIOD: f32 = 289.0;
+
+/// octaves evaluated by the shader, larger values are clamped
+pub const MAX_NOISE_OCTAVES: u32 = 8;
 
 
 #[derive(Default)]
 pub struct NoiseMaterialPlugin;
 
 impl Plugin for NoiseMaterialPlugin {
-    #[allow(unused)]
     fn build(&self, app: &mut App) {
-        app.register_type::<NoiseMaterial>();
+        load_internal_asset!(
+            app,
+            NOISE_MATERIAL_SHADER_HANDLE,
+            "noise.wgsl",
+            Shader::from_wgsl
+        );
 
-        app.add_systems(Update, apply_noise_cpu);
+        app.register_type::<NoiseMaterial>();
+        app.register_type::<NoiseMaterialMode>();
     }
 }
 
 
-fn apply_noise_cpu(
-    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
-    mut selections: Query<(
-        Entity,
-        &GaussianCloudHandle,
-        &NoiseMaterial,
-        Changed<NoiseMaterial>,
-    )>,
-) {
-    for (
-        _entity,
-        cloud_handle,
-        noise_material,
-        changed,
-    ) in selections.iter_mut() {
-        if !changed {
-            continue;
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum NoiseMaterialMode {
+    Simplex,
+    /// fractal sum of inverted simplex ridges
+    #[default]
+    RidgedMulti,
+}
+
+
+/// perturbs the color and position of each gaussian by animated noise on the gpu, requires `material_noise`
+///
+/// noise is sampled at the cloud space position of each gaussian and scrolled over time. positions
+/// are displaced after sorting, keep `position_strength` small relative to the gaussian spacing.
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct NoiseMaterial {
+    pub mode: NoiseMaterialMode,
+    pub seed: u32,
+    /// spatial frequency of the first octave
+    pub scale: f32,
+    pub octaves: u32,
+    /// frequency multiplier between octaves
+    pub lacunarity: f32,
+    /// amplitude multiplier between octaves
+    pub persistence: f32,
+    /// noise space units scrolled per second
+    pub speed: f32,
+    /// blend between the splat color (0.0) and the noise color (1.0)
+    pub color_strength: f32,
+    /// cloud space displacement at full noise amplitude
+    pub position_strength: f32,
+}
+
+impl Default for NoiseMaterial {
+    fn default() -> Self {
+        NoiseMaterial {
+            mode: NoiseMaterialMode::default(),
+            seed: 0,
+            scale: 1.0,
+            octaves: 4,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            speed: 0.25,
+            color_strength: 1.0,
+            position_strength: 0.0,
         }
+    }
+}
 
-        let mut rigid_multi = RidgedMulti::<Simplex>::default();
-        rigid_multi.frequency = noise_material.scale as f64;
 
-        let cloud = gaussian_clouds_res.get_mut(cloud_handle).unwrap();
+#[derive(Debug, Default, Clone, Copy, ShaderType)]
+pub struct GaussianNoiseUniform {
+    pub seed_offset: Vec3,
+    pub frequency: f32,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub persistence: f32,
+    pub speed: f32,
+    pub color_strength: f32,
+    pub position_strength: f32,
+    /// 0 disabled, 1 simplex, 2 ridged multi-fractal
+    pub mode: u32,
+}
 
-        cloud.gaussians.iter_mut()
-            .for_each(|gaussian| {
-                let point = |gaussian: &Gaussian, idx| {
-                    let x = gaussian.position_visibility[0];
-                    let y = gaussian.position_visibility[1];
-                    let z = gaussian.position_visibility[2];
+impl From<&NoiseMaterial> for GaussianNoiseUniform {
+    fn from(material: &NoiseMaterial) -> Self {
+        let mut rng = StdRng::seed_from_u64(material.seed as u64);
+        let seed_offset = Vec3::from_array(std::array::from_fn(|_| rng.gen_range(0.0..NOISE_PERIOD)));
 
-                    [x as f64, y as f64, z as f64, idx as f64]
-                };
+        let mode = match material.mode {
+            NoiseMaterialMode::Simplex => 1,
+            NoiseMaterialMode::RidgedMulti => 2,
+        };
 
-                for i in 0..gaussian.spherical_harmonic.coefficients.len() {
-                    let noise = rigid_multi.get(point(&gaussian, i));
-                    gaussian.spherical_harmonic.coefficients[i] = noise as f32;
-                }
-            });
+        Self {
+            seed_offset,
+            frequency: material.scale,
+            octaves: material.octaves.clamp(1, MAX_NOISE_OCTAVES),
+            lacunarity: material.lacunarity,
+            persistence: material.persistence,
+            speed: material.speed,
+            color_strength: material.color_strength.clamp(0.0, 1.0),
+            position_strength: material.position_strength,
+            mode,
+        }
     }
 }
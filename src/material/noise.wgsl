@@ -0,0 +1,114 @@
+#define_import_path bevy_gaussian_splatting::noise_material
+
+#import bevy_gaussian_splatting::bindings::{
+    globals,
+    gaussian_uniforms,
+}
+#import bevy_gaussian_splatting::noise::simplex_3d
+
+
+const NOISE_NONE: u32 = 0u;
+const NOISE_SIMPLEX: u32 = 1u;
+const NOISE_RIDGED_MULTI: u32 = 2u;
+
+const MAX_NOISE_OCTAVES: u32 = 8u;
+
+// decorrelates the noise channels
+const CHANNEL_OFFSETS = array<vec3<f32>, 3>(
+    vec3<f32>(0.0, 0.0, 0.0),
+    vec3<f32>(19.1, 33.4, 47.2),
+    vec3<f32>(74.2, 124.5, 99.4),
+);
+
+
+fn ridged_multi_3d(point: vec3<f32>) -> f32 {
+    let noise = gaussian_uniforms.noise;
+
+    var frequency_point = point;
+    var amplitude = 1.0;
+    var weight = 1.0;
+    var total = 0.0;
+    var norm = 0.0;
+
+    for (var octave = 0u; octave < min(noise.octaves, MAX_NOISE_OCTAVES); octave += 1u) {
+        var signal = 1.0 - abs(simplex_3d(frequency_point));
+        signal *= signal * weight;
+
+        // successive ridges are sharpened where the previous octave peaked
+        weight = saturate(signal * 2.0);
+
+        total += signal * amplitude;
+        norm += amplitude;
+
+        amplitude *= noise.persistence;
+        frequency_point *= noise.lacunarity;
+    }
+
+    return total / max(norm, 0.0001) * 2.0 - 1.0;
+}
+
+fn fractal_simplex_3d(point: vec3<f32>) -> f32 {
+    let noise = gaussian_uniforms.noise;
+
+    var frequency_point = point;
+    var amplitude = 1.0;
+    var total = 0.0;
+    var norm = 0.0;
+
+    for (var octave = 0u; octave < min(noise.octaves, MAX_NOISE_OCTAVES); octave += 1u) {
+        total += simplex_3d(frequency_point) * amplitude;
+        norm += amplitude;
+
+        amplitude *= noise.persistence;
+        frequency_point *= noise.lacunarity;
+    }
+
+    return total / max(norm, 0.0001);
+}
+
+// seeded, animated noise in -1.0..1.0 at a cloud space position
+fn noise_material_sample(local_position: vec3<f32>, channel: u32) -> f32 {
+    let noise = gaussian_uniforms.noise;
+
+    let point = local_position * noise.frequency
+        + noise.seed_offset
+        + CHANNEL_OFFSETS[channel]
+        + vec3<f32>(globals.time * noise.speed);
+
+    if (noise.mode == NOISE_RIDGED_MULTI) {
+        return ridged_multi_3d(point);
+    }
+
+    return fractal_simplex_3d(point);
+}
+
+fn noise_material_sample_3d(local_position: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(
+        noise_material_sample(local_position, 0u),
+        noise_material_sample(local_position, 1u),
+        noise_material_sample(local_position, 2u),
+    );
+}
+
+
+fn noise_material_position(local_position: vec3<f32>) -> vec3<f32> {
+    let noise = gaussian_uniforms.noise;
+
+    if (noise.mode == NOISE_NONE || noise.position_strength == 0.0) {
+        return local_position;
+    }
+
+    return local_position + noise.position_strength * noise_material_sample_3d(local_position);
+}
+
+fn noise_material_color(color: vec3<f32>, local_position: vec3<f32>) -> vec3<f32> {
+    let noise = gaussian_uniforms.noise;
+
+    if (noise.mode == NOISE_NONE || noise.color_strength == 0.0) {
+        return color;
+    }
+
+    let noise_color = 0.5 + 0.5 * noise_material_sample_3d(local_position);
+
+    return mix(color, noise_color, noise.color_strength);
+}
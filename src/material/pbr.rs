@@ -0,0 +1,97 @@
+use std::marker::Copy;
+
+use bevy::{
+    prelude::*,
+    asset::load_internal_asset,
+    color::ColorToPacked,
+};
+use bytemuck::{
+    Pod,
+    Zeroable,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+
+const PBR_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(340596187);
+
+
+pub struct PbrMaterialPlugin;
+
+impl Plugin for PbrMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            PBR_SHADER_HANDLE,
+            "pbr.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<PbrMaterial>();
+    }
+}
+
+
+/// per-gaussian base color, roughness and metallic, stored as a plane next to the spherical harmonics
+///
+/// zeroed materials are absent, those gaussians are shaded from their spherical harmonic color.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Copy,
+    PartialEq,
+    Reflect,
+    Pod,
+    Zeroable,
+    Serialize,
+    Deserialize,
+)]
+#[repr(C)]
+pub struct PbrMaterial {
+    /// srgb encoded base color, the alpha byte marks the material as present
+    pub base_color: [u8; 4],
+    /// perceptual roughness, metallic and two unused bytes
+    pub roughness_metallic: [u8; 4],
+}
+
+impl PbrMaterial {
+    /// `base_color` is linear, all attributes are clamped to 0.0..=1.0
+    pub fn new(
+        base_color: [f32; 3],
+        roughness: f32,
+        metallic: f32,
+    ) -> Self {
+        let [r, g, b] = base_color.map(|channel| channel.clamp(0.0, 1.0));
+        let [r, g, b, _] = Srgba::from(LinearRgba::rgb(r, g, b)).to_u8_array();
+
+        let unorm = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        Self {
+            base_color: [r, g, b, u8::MAX],
+            roughness_metallic: [unorm(roughness), unorm(metallic), 0, 0],
+        }
+    }
+
+    pub fn is_present(&self) -> bool {
+        self.base_color[3] != 0
+    }
+
+    /// linear base color
+    pub fn base_color(&self) -> [f32; 3] {
+        let [r, g, b, _] = self.base_color;
+        let [r, g, b, _] = LinearRgba::from(Srgba::rgb_u8(r, g, b)).to_f32_array();
+
+        [r, g, b]
+    }
+
+    pub fn roughness(&self) -> f32 {
+        self.roughness_metallic[0] as f32 / 255.0
+    }
+
+    pub fn metallic(&self) -> f32 {
+        self.roughness_metallic[1] as f32 / 255.0
+    }
+}
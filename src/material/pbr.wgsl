@@ -0,0 +1,337 @@
+#define_import_path bevy_gaussian_splatting::pbr
+
+#import bevy_gaussian_splatting::bindings::{
+    view,
+    gaussian_uniforms,
+}
+#import bevy_gaussian_splatting::helpers::get_rotation_matrix
+
+#ifdef PBR_MATERIAL
+#import bevy_gaussian_splatting::bindings::pbr_materials
+#import bevy_gaussian_splatting::spherical_harmonics::srgb_to_linear
+#endif
+
+
+struct DirectionalLight {
+    cascade_clip_from_world: array<mat4x4<f32>, #{MAX_GAUSSIAN_SHADOW_CASCADES}>,
+    cascade_far_bounds: vec4<f32>,
+    cascade_normal_offsets: vec4<f32>,
+    color: vec3<f32>,
+    shadow_layer: u32,
+    direction_to_light: vec3<f32>,
+    cascade_count: u32,
+    shadow_depth_bias: f32,
+};
+
+struct PointLight {
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    spot_scale: f32,
+    spot_direction: vec3<f32>,
+    spot_offset: f32,
+};
+
+struct Lights {
+    directional_lights: array<DirectionalLight, #{MAX_GAUSSIAN_DIRECTIONAL_LIGHTS}>,
+    point_lights: array<PointLight, #{MAX_GAUSSIAN_POINT_LIGHTS}>,
+    environment: array<vec4<f32>, 9>,
+    environment_rotation: mat3x3<f32>,
+    ambient: vec3<f32>,
+    directional_light_count: u32,
+    point_light_count: u32,
+};
+
+@group(0) @binding(2) var<uniform> lights: Lights;
+#ifdef WEBGL2
+@group(0) @binding(3) var directional_shadow_textures: texture_depth_2d;
+#else
+@group(0) @binding(3) var directional_shadow_textures: texture_depth_2d_array;
+#endif
+@group(0) @binding(4) var directional_shadow_sampler: sampler_comparison;
+
+
+const PI: f32 = 3.141592653589793;
+const NO_SHADOW_LAYER: u32 = 0xFFFFFFFFu;
+
+
+struct GaussianSurface {
+    base_color: vec3<f32>,
+    perceptual_roughness: f32,
+    metallic: f32,
+    // lambertian splat color for gaussians without pbr attributes
+    lambertian: bool,
+};
+
+fn gaussian_surface(splat_index: u32, albedo: vec3<f32>) -> GaussianSurface {
+    var surface = GaussianSurface(albedo, 1.0, 0.0, true);
+
+#ifdef PBR_MATERIAL
+    // clouds without pbr attributes bind a single absent material
+    let packed = pbr_materials[min(splat_index, arrayLength(&pbr_materials) - 1u)];
+    let base_color = unpack4x8unorm(packed.x);
+
+    if (base_color.a > 0.5) {
+        let roughness_metallic = unpack4x8unorm(packed.y);
+
+        surface = GaussianSurface(
+            srgb_to_linear(base_color.rgb),
+            roughness_metallic.x,
+            roughness_metallic.y,
+            false,
+        );
+    }
+#endif
+
+    return surface;
+}
+
+
+// brdf terms of bevy_pbr::lighting, which can not be imported next to the gaussian view bindings
+fn D_GGX(roughness: f32, NdotH: f32) -> f32 {
+    let oneMinusNdotHSquared = 1.0 - NdotH * NdotH;
+    let a = NdotH * roughness;
+    let k = roughness / (oneMinusNdotHSquared + a * a);
+    return k * k * (1.0 / PI);
+}
+
+fn V_SmithGGXCorrelated(roughness: f32, NdotV: f32, NdotL: f32) -> f32 {
+    let a2 = roughness * roughness;
+    let lambdaV = NdotL * sqrt((NdotV - a2 * NdotV) * NdotV + a2);
+    let lambdaL = NdotV * sqrt((NdotL - a2 * NdotL) * NdotL + a2);
+    return 0.5 / (lambdaV + lambdaL);
+}
+
+fn F_Schlick_vec(f0: vec3<f32>, f90: f32, VdotH: f32) -> vec3<f32> {
+    return f0 + (f90 - f0) * pow(1.0 - VdotH, 5.0);
+}
+
+fn F_Schlick(f0: f32, f90: f32, VdotH: f32) -> f32 {
+    return f0 + (f90 - f0) * pow(1.0 - VdotH, 5.0);
+}
+
+fn Fd_Burley(roughness: f32, NdotV: f32, NdotL: f32, LdotH: f32) -> f32 {
+    let f90 = 0.5 + 2.0 * roughness * LdotH * LdotH;
+    let lightScatter = F_Schlick(1.0, f90, NdotL);
+    let viewScatter = F_Schlick(1.0, f90, NdotV);
+    return lightScatter * viewScatter * (1.0 / PI);
+}
+
+fn F_AB(perceptual_roughness: f32, NdotV: f32) -> vec2<f32> {
+    let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
+    let c1 = vec4<f32>(1.0, 0.0425, 1.04, -0.04);
+    let r = perceptual_roughness * c0 + c1;
+    let a004 = min(r.x * r.x, exp2(-9.28 * NdotV)) * r.x + r.y;
+    return vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+}
+
+fn EnvBRDFApprox(F0: vec3<f32>, F_ab: vec2<f32>) -> vec3<f32> {
+    return F0 * F_ab.x + F_ab.y;
+}
+
+fn perceptualRoughnessToRoughness(perceptualRoughness: f32) -> f32 {
+    let clampedPerceptualRoughness = clamp(perceptualRoughness, 0.089, 1.0);
+    return clampedPerceptualRoughness * clampedPerceptualRoughness;
+}
+
+
+// outgoing radiance towards the view per unit of incident irradiance from `to_light`
+fn surface_brdf(
+    surface: GaussianSurface,
+    normal: vec3<f32>,
+    to_view: vec3<f32>,
+    to_light: vec3<f32>,
+) -> vec3<f32> {
+    if (surface.lambertian) {
+        return surface.base_color / PI;
+    }
+
+    let half_vector = normalize(to_light + to_view);
+    let NdotV = max(dot(normal, to_view), 0.0001);
+    let NdotL = saturate(dot(normal, to_light));
+    let NdotH = saturate(dot(normal, half_vector));
+    let LdotH = saturate(dot(to_light, half_vector));
+
+    let roughness = perceptualRoughnessToRoughness(surface.perceptual_roughness);
+    let F0 = mix(vec3<f32>(0.04), surface.base_color, surface.metallic);
+    let diffuse_color = surface.base_color * (1.0 - surface.metallic);
+
+    let specular = D_GGX(roughness, NdotH)
+        * V_SmithGGXCorrelated(roughness, NdotV, NdotL)
+        * F_Schlick_vec(F0, 1.0, LdotH);
+    let diffuse = diffuse_color * Fd_Burley(roughness, NdotV, NdotL, LdotH);
+
+    return diffuse + specular;
+}
+
+// radiance towards the view from the ambient light and environment map
+fn surface_indirect(
+    surface: GaussianSurface,
+    normal: vec3<f32>,
+    to_view: vec3<f32>,
+) -> vec3<f32> {
+    let diffuse_light = lights.ambient + environment_radiance(normal);
+
+    if (surface.lambertian) {
+        return surface.base_color * diffuse_light;
+    }
+
+    let NdotV = max(dot(normal, to_view), 0.0001);
+    let F0 = mix(vec3<f32>(0.04), surface.base_color, surface.metallic);
+    let diffuse_color = surface.base_color * (1.0 - surface.metallic);
+
+    // the diffuse environment stands in for the prefiltered specular map
+    let specular_light = lights.ambient + environment_radiance(reflect(-to_view, normal));
+    let specular = EnvBRDFApprox(F0, F_AB(surface.perceptual_roughness, NdotV));
+
+    return diffuse_color * diffuse_light + specular * specular_light;
+}
+
+
+// unit normal along the shortest scale axis, flipped towards the camera
+fn gaussian_normal(
+    rotation: vec4<f32>,
+    scale: vec3<f32>,
+    ray_direction: vec3<f32>,
+) -> vec3<f32> {
+    // rows of the rotation matrix are the principal axes of the covariance
+    let axes = transpose(get_rotation_matrix(rotation));
+
+    var local_normal = axes[2];
+    if (scale.x <= scale.y && scale.x <= scale.z) {
+        local_normal = axes[0];
+    } else if (scale.y <= scale.z) {
+        local_normal = axes[1];
+    }
+
+    let normal = normalize((gaussian_uniforms.transform * vec4<f32>(local_normal, 0.0)).xyz);
+
+    return select(normal, -normal, dot(normal, ray_direction) > 0.0);
+}
+
+
+// 1.0 when lit, 0.0 when fully occluded in the cascade covering the position
+fn directional_shadow(
+    light_index: u32,
+    world_position: vec3<f32>,
+    normal: vec3<f32>,
+) -> f32 {
+    let shadow_layer = lights.directional_lights[light_index].shadow_layer;
+    if (shadow_layer == NO_SHADOW_LAYER) {
+        return 1.0;
+    }
+
+    let view_z = (view.view_from_world * vec4<f32>(world_position, 1.0)).z;
+
+    for (var cascade = 0u; cascade < lights.directional_lights[light_index].cascade_count; cascade += 1u) {
+        if (-view_z >= lights.directional_lights[light_index].cascade_far_bounds[cascade]) {
+            continue;
+        }
+
+        let offset_position = world_position
+            + normal * lights.directional_lights[light_index].cascade_normal_offsets[cascade]
+            + lights.directional_lights[light_index].direction_to_light * lights.directional_lights[light_index].shadow_depth_bias;
+
+        let clip = lights.directional_lights[light_index].cascade_clip_from_world[cascade] * vec4<f32>(offset_position, 1.0);
+        let ndc = clip.xyz / clip.w;
+
+        if (any(abs(ndc.xy) > vec2<f32>(1.0)) || ndc.z < 0.0 || ndc.z > 1.0) {
+            return 1.0;
+        }
+
+        let uv = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5);
+
+#ifdef WEBGL2
+        return textureSampleCompareLevel(
+            directional_shadow_textures,
+            directional_shadow_sampler,
+            uv,
+            ndc.z,
+        );
+#else
+        return textureSampleCompareLevel(
+            directional_shadow_textures,
+            directional_shadow_sampler,
+            uv,
+            i32(shadow_layer + cascade),
+            ndc.z,
+        );
+#endif
+    }
+
+    return 1.0;
+}
+
+
+fn point_light_irradiance(
+    light_index: u32,
+    world_position: vec3<f32>,
+    normal: vec3<f32>,
+) -> vec3<f32> {
+    let light = lights.point_lights[light_index];
+
+    let to_light = light.position - world_position;
+    let distance_squared = max(dot(to_light, to_light), 0.0001);
+    let direction = to_light * inverseSqrt(distance_squared);
+
+    // inverse square falloff windowed to zero at the light range
+    let factor = distance_squared / (light.range * light.range);
+    let window = saturate(1.0 - factor * factor);
+    let attenuation = window * window / distance_squared;
+
+    let cone = saturate(dot(-direction, light.spot_direction) * light.spot_scale + light.spot_offset);
+
+    return light.color * attenuation * cone * cone * max(dot(normal, direction), 0.0);
+}
+
+
+// diffuse environment map radiance along the normal, from its l2 spherical harmonics
+fn environment_radiance(normal: vec3<f32>) -> vec3<f32> {
+    let n = lights.environment_rotation * normal;
+    let sh = lights.environment;
+
+    let radiance = 0.282095 * sh[0].rgb
+        + 0.488603 * (n.y * sh[1].rgb + n.z * sh[2].rgb + n.x * sh[3].rgb)
+        + 1.092548 * (n.x * n.y * sh[4].rgb + n.y * n.z * sh[5].rgb + n.x * n.z * sh[7].rgb)
+        + 0.315392 * (3.0 * n.z * n.z - 1.0) * sh[6].rgb
+        + 0.546274 * (n.x * n.x - n.y * n.y) * sh[8].rgb;
+
+    return max(radiance, vec3<f32>(0.0));
+}
+
+
+// shades the splat by the scene lights and environment map, blended by the cloud relight factor
+//
+// gaussians with pbr attributes use their base color, roughness and metallic, others treat the
+// splat color as a lambertian albedo
+fn gaussian_relight(
+    albedo: vec3<f32>,
+    splat_index: u32,
+    world_position: vec3<f32>,
+    normal: vec3<f32>,
+    ray_direction: vec3<f32>,
+) -> vec3<f32> {
+    let surface = gaussian_surface(splat_index, albedo);
+    let to_view = -ray_direction;
+
+    var radiance = surface_indirect(surface, normal, to_view);
+
+    for (var i = 0u; i < lights.directional_light_count; i += 1u) {
+        let direction = lights.directional_lights[i].direction_to_light;
+
+        var visibility = 1.0;
+#ifdef RECEIVE_SHADOWS
+        visibility = directional_shadow(i, world_position, normal);
+#endif
+
+        let irradiance = lights.directional_lights[i].color * max(dot(normal, direction), 0.0) * visibility;
+        radiance += irradiance * surface_brdf(surface, normal, to_view, direction);
+    }
+
+    for (var i = 0u; i < lights.point_light_count; i += 1u) {
+        let direction = normalize(lights.point_lights[i].position - world_position);
+        radiance += point_light_irradiance(i, world_position, normal) * surface_brdf(surface, normal, to_view, direction);
+    }
+
+    return mix(albedo, radiance * view.exposure, gaussian_uniforms.relight);
+}
@@ -47,19 +47,22 @@ const fn num_sh_coefficients(degree: usize) -> usize {
 
 
 #[cfg(feature = "sh0")]
-const SH_DEGREE: usize = 0;
+pub const SH_DEGREE: usize = 0;
 
 #[cfg(feature = "sh1")]
-const SH_DEGREE: usize = 1;
+pub const SH_DEGREE: usize = 1;
 
 #[cfg(feature = "sh2")]
-const SH_DEGREE: usize = 2;
+pub const SH_DEGREE: usize = 2;
 
 #[cfg(feature = "sh3")]
-const SH_DEGREE: usize = 3;
+pub const SH_DEGREE: usize = 3;
 
 #[cfg(feature = "sh4")]
-const SH_DEGREE: usize = 4;
+pub const SH_DEGREE: usize = 4;
+
+/// degree zero spherical harmonic basis constant
+pub const SH_C0: f32 = 0.282_094_8;
 
 pub const SH_CHANNELS: usize = 3;
 pub const SH_COEFF_COUNT_PER_CHANNEL: usize = num_sh_coefficients(SH_DEGREE);
@@ -149,6 +152,30 @@ impl SphericalHarmonicCoefficients {
     pub fn set(&mut self, index: usize, value: f32) {
         self.coefficients[index] = value;
     }
+
+    #[cfg(feature = "f16")]
+    pub fn get(&self, index: usize) -> f32 {
+        let packed = self.coefficients[index / 2] >> (16 * (index % 2));
+        f16::from_bits(packed as u16).to_f32()
+    }
+
+    #[cfg(feature = "f32")]
+    pub fn get(&self, index: usize) -> f32 {
+        self.coefficients[index]
+    }
+
+    /// view independent color of the degree zero band, as evaluated by `spherical_harmonics_lookup`
+    pub fn base_color(&self) -> Vec3 {
+        Vec3::new(self.get(0), self.get(1), self.get(2)) * SH_C0 + Vec3::splat(0.5)
+    }
+
+    pub fn set_base_color(&mut self, color: Vec3) {
+        let dc = (color - Vec3::splat(0.5)) / SH_C0;
+
+        self.set(0, dc.x);
+        self.set(1, dc.y);
+        self.set(2, dc.z);
+    }
 }
 
 
@@ -0,0 +1,598 @@
+use std::hash::{
+    BuildHasher,
+    Hasher,
+};
+
+use bevy::{
+    prelude::*,
+    math::{
+        Affine3A,
+        Vec3A,
+    },
+    render::primitives::Frustum,
+    utils::{
+        FixedState,
+        HashMap,
+        HashSet,
+    },
+};
+
+use crate::GaussianCloud;
+
+
+/// gaussians bounded by this many standard deviations along each axis
+pub const BVH_SIGMA_EXTENT: f32 = 3.0;
+
+const BVH_LEAF_SIZE: usize = 4;
+
+
+#[derive(Default)]
+pub struct GaussianBvhPlugin;
+
+impl Plugin for GaussianBvhPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GaussianBvhs>();
+
+        app.add_systems(PreUpdate, update_gaussian_bvhs);
+    }
+}
+
+
+/// bvh node bounding gaussian centers, with the largest unit scale half extent of its gaussians kept
+/// separately so bounds can be grown by a per-entity `global_scale`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GaussianBvhNode {
+    pub center_min: Vec3A,
+    pub center_max: Vec3A,
+    pub extent: Vec3A,
+    /// first child for interior nodes (the second child is `child + 1`), zero for leaves
+    pub child: u32,
+    /// first entry of `GaussianBvh::indices` covered by the node
+    pub start: u32,
+    /// number of gaussians covered by the node
+    pub count: u32,
+}
+
+impl GaussianBvhNode {
+    pub fn is_leaf(&self) -> bool {
+        self.child == 0
+    }
+
+    /// bounds of the node with gaussian extents multiplied by `scale`
+    pub fn bounds(&self, scale: f32) -> (Vec3A, Vec3A) {
+        let extent = self.extent * scale;
+        (self.center_min - extent, self.center_max + extent)
+    }
+
+    /// entry and exit distance of a ray through the scaled node bounds
+    pub fn ray_interval(
+        &self,
+        origin: Vec3A,
+        inverse_direction: Vec3A,
+        scale: f32,
+    ) -> Option<(f32, f32)> {
+        let (min, max) = self.bounds(scale);
+
+        let t0 = (min - origin) * inverse_direction;
+        let t1 = (max - origin) * inverse_direction;
+
+        let near = t0.min(t1).max_element().max(0.0);
+        let far = t0.max(t1).min_element();
+
+        (near <= far).then_some((near, far))
+    }
+
+    /// overlap of the scaled node bounds with a convex region of `normal_d` half spaces
+    pub fn classify_half_spaces(
+        &self,
+        half_spaces: &[Vec4],
+        scale: f32,
+    ) -> BvhOverlap {
+        let (min, max) = self.bounds(scale);
+        let center = (min + max) * 0.5;
+        let half_size = (max - min) * 0.5;
+
+        let mut overlap = BvhOverlap::Inside;
+        for half_space in half_spaces {
+            let normal = Vec3A::from_vec4(*half_space);
+            let distance = normal.dot(center) + half_space.w;
+            let radius = normal.abs().dot(half_size);
+
+            if distance < -radius {
+                return BvhOverlap::Outside;
+            }
+
+            if distance < radius {
+                overlap = BvhOverlap::Intersects;
+            }
+        }
+
+        overlap
+    }
+
+    /// squared distance from a point to the bounds of the gaussian centers
+    pub fn center_distance_squared(&self, point: Vec3A) -> f32 {
+        (self.center_min - point)
+            .max(point - self.center_max)
+            .max(Vec3A::ZERO)
+            .length_squared()
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BvhOverlap {
+    Outside,
+    Intersects,
+    Inside,
+}
+
+
+/// bounding volume hierarchy over the `BVH_SIGMA_EXTENT` ellipsoids of a gaussian cloud
+///
+/// point queries test gaussian centers in the local space of the cloud, nodes are ordered so that
+/// children always follow their parent.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GaussianBvh {
+    pub nodes: Vec<GaussianBvhNode>,
+    /// gaussian indices, every node references a contiguous range
+    pub indices: Vec<u32>,
+}
+
+impl GaussianBvh {
+    pub fn new(cloud: &GaussianCloud) -> Self {
+        let centers = gaussian_centers(cloud);
+        let extents = gaussian_extents(cloud);
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * cloud.len() / BVH_LEAF_SIZE + 1),
+            indices: (0..cloud.len() as u32).collect(),
+        };
+
+        if cloud.is_empty() {
+            return bvh;
+        }
+
+        bvh.nodes.push(GaussianBvhNode::default());
+
+        let mut stack = vec![(0, 0, cloud.len())];
+        while let Some((node_index, start, end)) = stack.pop() {
+            let mut node = GaussianBvhNode {
+                start: start as u32,
+                count: (end - start) as u32,
+                ..default()
+            };
+            node.fit(&bvh.indices[start..end], &centers, &extents);
+
+            if end - start > BVH_LEAF_SIZE {
+                let size = node.center_max - node.center_min;
+                let axis = if size.x >= size.y && size.x >= size.z {
+                    0
+                } else if size.y >= size.z {
+                    1
+                } else {
+                    2
+                };
+
+                let mid = (start + end) / 2;
+                bvh.indices[start..end].select_nth_unstable_by(mid - start, |a, b| {
+                    centers[*a as usize][axis].total_cmp(&centers[*b as usize][axis])
+                });
+
+                let child = bvh.nodes.len();
+                bvh.nodes.push(GaussianBvhNode::default());
+                bvh.nodes.push(GaussianBvhNode::default());
+
+                node.child = child as u32;
+
+                stack.push((child, start, mid));
+                stack.push((child + 1, mid, end));
+            }
+
+            bvh.nodes[node_index] = node;
+        }
+
+        bvh
+    }
+
+    /// recomputes node bounds after gaussians moved, keeping the tree topology
+    ///
+    /// the cloud must have the same gaussian count the bvh was built with, tree quality degrades with
+    /// large edits so rebuild with `GaussianBvh::new` after those.
+    pub fn refit(&mut self, cloud: &GaussianCloud) {
+        debug_assert_eq!(self.indices.len(), cloud.len());
+
+        let centers = gaussian_centers(cloud);
+        let extents = gaussian_extents(cloud);
+
+        for node_index in (0..self.nodes.len()).rev() {
+            let mut node = self.nodes[node_index];
+
+            if node.is_leaf() {
+                node.fit(self.node_indices(&node), &centers, &extents);
+            } else {
+                let first = self.nodes[node.child as usize];
+                let second = self.nodes[node.child as usize + 1];
+
+                node.center_min = first.center_min.min(second.center_min);
+                node.center_max = first.center_max.max(second.center_max);
+                node.extent = first.extent.max(second.extent);
+            }
+
+            self.nodes[node_index] = node;
+        }
+    }
+
+    pub fn root(&self) -> Option<&GaussianBvhNode> {
+        self.nodes.first()
+    }
+
+    /// gaussian indices covered by a node
+    pub fn node_indices(&self, node: &GaussianBvhNode) -> &[u32] {
+        &self.indices[node.start as usize..(node.start + node.count) as usize]
+    }
+
+    /// depth first traversal, `classify` prunes or accepts whole subtrees and `accept` tests single
+    /// gaussians of leaves that only intersect
+    pub fn traverse(
+        &self,
+        mut classify: impl FnMut(&GaussianBvhNode) -> BvhOverlap,
+        mut accept: impl FnMut(u32) -> bool,
+        mut visit: impl FnMut(u32),
+    ) {
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            match classify(node) {
+                BvhOverlap::Outside => {}
+                BvhOverlap::Inside => self.node_indices(node).iter().copied().for_each(&mut visit),
+                BvhOverlap::Intersects if node.is_leaf() => {
+                    self.node_indices(node)
+                        .iter()
+                        .copied()
+                        .filter(|index| accept(*index))
+                        .for_each(&mut visit);
+                }
+                BvhOverlap::Intersects => {
+                    stack.push(node.child as usize + 1);
+                    stack.push(node.child as usize);
+                }
+            }
+        }
+    }
+
+    /// gaussians with centers within `radius` of `point`
+    pub fn within_radius(
+        &self,
+        cloud: &GaussianCloud,
+        point: Vec3A,
+        radius: f32,
+    ) -> Vec<usize> {
+        let mut indices = Vec::new();
+        self.visit_within_radius(cloud, point, radius, |index| indices.push(index as usize));
+        indices
+    }
+
+    /// number of gaussians with centers within `radius` of `point`
+    pub fn count_within_radius(
+        &self,
+        cloud: &GaussianCloud,
+        point: Vec3A,
+        radius: f32,
+    ) -> usize {
+        let mut count = 0;
+        self.visit_within_radius(cloud, point, radius, |_| count += 1);
+        count
+    }
+
+    fn visit_within_radius(
+        &self,
+        cloud: &GaussianCloud,
+        point: Vec3A,
+        radius: f32,
+        visit: impl FnMut(u32),
+    ) {
+        let radius_squared = radius * radius;
+
+        self.traverse(
+            |node| {
+                if node.center_distance_squared(point) > radius_squared {
+                    return BvhOverlap::Outside;
+                }
+
+                let farthest = (node.center_min - point).abs().max((node.center_max - point).abs());
+                if farthest.length_squared() <= radius_squared {
+                    BvhOverlap::Inside
+                } else {
+                    BvhOverlap::Intersects
+                }
+            },
+            |index| Vec3A::from(*cloud.position(index as usize)).distance_squared(point) <= radius_squared,
+            visit,
+        );
+    }
+
+    /// the `k` gaussians with centers closest to `point`, as (index, distance) sorted by distance
+    pub fn nearest(
+        &self,
+        cloud: &GaussianCloud,
+        point: Vec3A,
+        k: usize,
+    ) -> Vec<(usize, f32)> {
+        let mut nearest: Vec<(f32, u32)> = Vec::with_capacity(k + 1);
+        if k == 0 || self.nodes.is_empty() {
+            return Vec::new();
+        }
+
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            if nearest.len() == k && node.center_distance_squared(point) > nearest[k - 1].0 {
+                continue;
+            }
+
+            if node.is_leaf() {
+                for &index in self.node_indices(node) {
+                    let distance_squared = Vec3A::from(*cloud.position(index as usize)).distance_squared(point);
+                    if nearest.len() == k && distance_squared >= nearest[k - 1].0 {
+                        continue;
+                    }
+
+                    let position = nearest.partition_point(|(other, _)| *other <= distance_squared);
+                    nearest.insert(position, (distance_squared, index));
+                    nearest.truncate(k);
+                }
+
+                continue;
+            }
+
+            let first = node.child as usize;
+            let second = first + 1;
+
+            if self.nodes[first].center_distance_squared(point) <= self.nodes[second].center_distance_squared(point) {
+                stack.push(second);
+                stack.push(first);
+            } else {
+                stack.push(first);
+                stack.push(second);
+            }
+        }
+
+        nearest.into_iter()
+            .map(|(distance_squared, index)| (index as usize, distance_squared.sqrt()))
+            .collect()
+    }
+
+    /// gaussians with centers inside an axis aligned box
+    pub fn within_aabb(
+        &self,
+        cloud: &GaussianCloud,
+        min: Vec3A,
+        max: Vec3A,
+    ) -> Vec<usize> {
+        let mut indices = Vec::new();
+
+        self.traverse(
+            |node| {
+                if node.center_max.cmplt(min).any() || node.center_min.cmpgt(max).any() {
+                    BvhOverlap::Outside
+                } else if node.center_min.cmpge(min).all() && node.center_max.cmple(max).all() {
+                    BvhOverlap::Inside
+                } else {
+                    BvhOverlap::Intersects
+                }
+            },
+            |index| {
+                let position = Vec3A::from(*cloud.position(index as usize));
+                position.cmpge(min).all() && position.cmple(max).all()
+            },
+            |index| indices.push(index as usize),
+        );
+
+        indices
+    }
+
+    /// gaussians with centers inside an oriented box of `half_size` placed by `isometry`
+    pub fn within_obb(
+        &self,
+        cloud: &GaussianCloud,
+        isometry: Isometry3d,
+        half_size: Vec3A,
+    ) -> Vec<usize> {
+        let half_spaces = [
+            (Vec3A::X, half_size.x),
+            (Vec3A::Y, half_size.y),
+            (Vec3A::Z, half_size.z),
+        ]
+        .into_iter()
+        .flat_map(|(axis, half_extent)| {
+            let normal = isometry.rotation * axis;
+            let center = isometry.translation.dot(normal);
+
+            [
+                (-normal).extend(center + half_extent),
+                normal.extend(half_extent - center),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+        self.within_half_spaces(cloud, &half_spaces, 0.0)
+    }
+
+    /// gaussians inside a convex region of `normal_d` half spaces (inside where `normal · p + d >= 0`),
+    /// nodes are tested with their gaussian extents multiplied by `scale` and leaves test centers
+    pub fn within_half_spaces(
+        &self,
+        cloud: &GaussianCloud,
+        half_spaces: &[Vec4],
+        scale: f32,
+    ) -> Vec<usize> {
+        let mut indices = Vec::new();
+
+        self.traverse(
+            |node| node.classify_half_spaces(half_spaces, scale),
+            |index| {
+                let position = Vec3A::from(*cloud.position(index as usize));
+                half_spaces.iter()
+                    .all(|half_space| Vec3A::from_vec4(*half_space).dot(position) + half_space.w >= 0.0)
+            },
+            |index| indices.push(index as usize),
+        );
+
+        indices
+    }
+
+    /// gaussians whose scaled bounds intersect a world-space frustum, with the cloud placed by `world_from_local`
+    pub fn within_frustum(
+        &self,
+        frustum: &Frustum,
+        world_from_local: &Affine3A,
+        scale: f32,
+    ) -> Vec<usize> {
+        let half_spaces = frustum.half_spaces.iter()
+            .map(|half_space| local_half_space(half_space.normal_d(), world_from_local))
+            .filter(|half_space| half_space.is_finite())
+            .collect::<Vec<_>>();
+
+        let mut indices = Vec::new();
+
+        self.traverse(
+            |node| node.classify_half_spaces(&half_spaces, scale),
+            // leaves are small, keep every gaussian of an intersecting leaf rather than storing per gaussian extents
+            |_| true,
+            |index| indices.push(index as usize),
+        );
+
+        indices
+    }
+}
+
+
+/// world-space `normal_d` half space expressed in the local space of `world_from_local`
+pub fn local_half_space(normal_d: Vec4, world_from_local: &Affine3A) -> Vec4 {
+    let normal = Vec3A::from_vec4(normal_d);
+    let local_normal = world_from_local.matrix3.transpose() * normal;
+
+    local_normal.extend(normal.dot(world_from_local.translation) + normal_d.w)
+}
+
+/// unit scale half extent of the `BVH_SIGMA_EXTENT` ellipsoid of a 3d covariance
+pub fn gaussian_extent(cov3d: &[f32; 6]) -> Vec3A {
+    Vec3A::new(cov3d[0], cov3d[3], cov3d[5]).max(Vec3A::ZERO).powf(0.5) * BVH_SIGMA_EXTENT
+}
+
+fn gaussian_centers(cloud: &GaussianCloud) -> Vec<Vec3A> {
+    cloud.position_iter()
+        .map(|position| Vec3A::from(*position))
+        .collect()
+}
+
+fn geometry_hash(cloud: &GaussianCloud) -> u64 {
+    let mut hasher = FixedState.build_hasher();
+    hasher.write_usize(cloud.len());
+
+    for (index, position) in cloud.position_iter().enumerate() {
+        #[cfg(feature = "precompute_covariance_3d")]
+        let shape = cloud.covariance_3d(index);
+
+        #[cfg(not(feature = "precompute_covariance_3d"))]
+        let shape = {
+            let (rotation, scale, _) = cloud.rotation_scale_opacity(index);
+            [rotation.x, rotation.y, rotation.z, rotation.w, scale.x, scale.y, scale.z]
+        };
+
+        position.iter()
+            .chain(shape.iter())
+            .for_each(|value| hasher.write_u32(value.to_bits()));
+    }
+
+    hasher.finish()
+}
+
+fn gaussian_extents(cloud: &GaussianCloud) -> Vec<Vec3A> {
+    (0..cloud.len())
+        .map(|index| gaussian_extent(&cloud.covariance_3d(index)))
+        .collect()
+}
+
+impl GaussianBvhNode {
+    fn fit(
+        &mut self,
+        indices: &[u32],
+        centers: &[Vec3A],
+        extents: &[Vec3A],
+    ) {
+        self.center_min = Vec3A::splat(f32::INFINITY);
+        self.center_max = Vec3A::splat(f32::NEG_INFINITY);
+        self.extent = Vec3A::ZERO;
+
+        for &index in indices {
+            self.center_min = self.center_min.min(centers[index as usize]);
+            self.center_max = self.center_max.max(centers[index as usize]);
+            self.extent = self.extent.max(extents[index as usize]);
+        }
+    }
+}
+
+
+/// bvh per loaded `GaussianCloud` asset, kept up to date with asset edits
+#[derive(Resource, Default)]
+pub struct GaussianBvhs {
+    pub bvhs: HashMap<AssetId<GaussianCloud>, GaussianBvh>,
+    /// hash of the positions, rotations and scales each bvh was fit to
+    geometry: HashMap<AssetId<GaussianCloud>, u64>,
+}
+
+impl GaussianBvhs {
+    pub fn get(&self, id: impl Into<AssetId<GaussianCloud>>) -> Option<&GaussianBvh> {
+        self.bvhs.get(&id.into())
+    }
+}
+
+fn update_gaussian_bvhs(
+    mut events: EventReader<AssetEvent<GaussianCloud>>,
+    gaussian_clouds: Res<Assets<GaussianCloud>>,
+    mut bvhs: ResMut<GaussianBvhs>,
+) {
+    let mut changed = HashSet::new();
+
+    for event in events.read() {
+        match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                changed.insert(*id);
+            }
+            AssetEvent::Removed { id } | AssetEvent::Unused { id } => {
+                changed.remove(id);
+                bvhs.bvhs.remove(id);
+                bvhs.geometry.remove(id);
+            }
+            AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+
+    for id in changed {
+        let Some(cloud) = gaussian_clouds.get(id) else {
+            continue;
+        };
+
+        // visibility and color edits (e.g. selections) leave the bounds untouched
+        let geometry = geometry_hash(cloud);
+        if bvhs.geometry.insert(id, geometry) == Some(geometry) && bvhs.bvhs.contains_key(&id) {
+            continue;
+        }
+
+        // edits that keep the gaussian count (e.g. transforms) only move bounds, anything else rebuilds
+        match bvhs.bvhs.get_mut(&id) {
+            Some(bvh) if bvh.indices.len() == cloud.len() => bvh.refit(cloud),
+            _ => {
+                bvhs.bvhs.insert(id, GaussianBvh::new(cloud));
+            }
+        }
+    }
+}
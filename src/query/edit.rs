@@ -0,0 +1,306 @@
+use bevy::{
+    prelude::*,
+    math::Affine3A,
+    utils::HashSet,
+};
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudAppearance,
+    GaussianCloudDebugSettings,
+    GaussianCloudHandle,
+    GaussianCloudRasterSettings,
+    GaussianCloudSortSettings,
+    query::{
+        history::{
+            EditCommand,
+            EditHistory,
+        },
+        select::Select,
+    },
+    sort::SortedEntriesHandle,
+};
+
+
+#[derive(Default)]
+pub struct EditPlugin;
+
+impl Plugin for EditPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DeleteSelectionEvent>();
+        app.add_event::<DuplicateSelectionEvent>();
+        app.add_event::<TransformSelectionEvent>();
+        app.add_event::<RecolorSelectionEvent>();
+
+        app.add_systems(Update, (
+            delete_selection,
+            duplicate_selection,
+            transform_selection,
+            recolor_selection,
+        ));
+    }
+}
+
+
+/// copy of the cloud without the gaussians at `indicies`, every remaining gaussian visible
+pub fn delete_gaussians(cloud: &GaussianCloud, indicies: &[usize]) -> GaussianCloud {
+    let mut deleted = vec![false; cloud.len()];
+    for &index in indicies {
+        deleted[index] = true;
+    }
+
+    let kept = (0..cloud.len())
+        .filter(|&index| !deleted[index])
+        .collect::<Vec<_>>();
+
+    visible_subset(cloud, &kept)
+}
+
+/// copy of the gaussians at `indicies` as a new cloud, every gaussian visible
+pub fn duplicate_gaussians(cloud: &GaussianCloud, indicies: &[usize]) -> GaussianCloud {
+    visible_subset(cloud, indicies)
+}
+
+fn visible_subset(cloud: &GaussianCloud, indicies: &[usize]) -> GaussianCloud {
+    let mut subset = cloud.subset(indicies);
+
+    for index in 0..subset.len() {
+        *subset.visibility_mut(index) = 1.0;
+    }
+    subset.resize_to_square();
+
+    subset
+}
+
+/// transforms the gaussians at `indicies` in cloud space, rotating and scaling about their centroid
+pub fn transform_gaussians(cloud: &mut GaussianCloud, indicies: &[usize], transform: &Transform) {
+    if indicies.is_empty() {
+        return;
+    }
+
+    let centroid = indicies.iter()
+        .map(|&index| Vec3::from(*cloud.position(index)))
+        .sum::<Vec3>() / indicies.len() as f32;
+
+    let about_centroid = Affine3A::from_translation(centroid)
+        * transform.compute_affine()
+        * Affine3A::from_translation(-centroid);
+
+    for &index in indicies {
+        cloud.transform_gaussian(index, &about_centroid);
+    }
+}
+
+/// blends the view independent color of the gaussians at `indicies` towards `color` (srgb)
+pub fn recolor_gaussians(cloud: &mut GaussianCloud, indicies: &[usize], color: Vec3, strength: f32) {
+    for &index in indicies {
+        let spherical_harmonic = cloud.spherical_harmonic_mut(index);
+
+        let base_color = spherical_harmonic.base_color();
+        spherical_harmonic.set_base_color(base_color.lerp(color, strength));
+    }
+}
+
+
+/// removes the selected gaussians from their cloud asset and clears the selection
+#[derive(Event, Debug, Reflect)]
+pub struct DeleteSelectionEvent;
+
+fn delete_selection(
+    mut commands: Commands,
+    mut events: EventReader<DeleteSelectionEvent>,
+    mut history: Option<ResMut<EditHistory>>,
+    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    gaussian_clouds: Query<(
+        Entity,
+        &GaussianCloudHandle,
+        Option<&Select>,
+    )>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    events.clear();
+
+    let mut deleted = HashSet::new();
+
+    for (
+        _entity,
+        cloud_handle,
+        select,
+    ) in gaussian_clouds.iter() {
+        let Some(select) = select else {
+            continue;
+        };
+
+        // a second selection on the same asset indexes the gaussians from before the delete
+        if select.indicies.is_empty() || deleted.contains(&cloud_handle.0.id()) {
+            continue;
+        }
+
+        let Some(cloud) = gaussian_clouds_res.get_mut(cloud_handle) else {
+            continue;
+        };
+
+        let command = EditCommand::delete(cloud, &select.indicies);
+        if let Some(history) = history.as_mut() {
+            history.record(cloud_handle, command);
+        }
+
+        deleted.insert(cloud_handle.0.id());
+    }
+
+    // selections and sorted entries of every entity drawing an edited asset are sized to the previous gaussian count
+    for (entity, cloud_handle, _) in gaussian_clouds.iter() {
+        if deleted.contains(&cloud_handle.0.id()) {
+            commands.entity(entity)
+                .remove::<Select>()
+                .remove::<SortedEntriesHandle>();
+        }
+    }
+}
+
+
+/// spawns a new cloud entity holding a copy of the selected gaussians, with the source transform and settings components
+#[derive(Event, Debug, Reflect)]
+pub struct DuplicateSelectionEvent;
+
+fn duplicate_selection(
+    mut commands: Commands,
+    mut events: EventReader<DuplicateSelectionEvent>,
+    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    selections: Query<(
+        &GaussianCloudHandle,
+        &GaussianCloudSortSettings,
+        &GaussianCloudAppearance,
+        &GaussianCloudRasterSettings,
+        &GaussianCloudDebugSettings,
+        &Transform,
+        &Select,
+    )>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    events.clear();
+
+    for (
+        cloud_handle,
+        sort_settings,
+        appearance,
+        raster_settings,
+        debug_settings,
+        transform,
+        select,
+    ) in selections.iter() {
+        if select.indicies.is_empty() {
+            continue;
+        }
+
+        let Some(cloud) = gaussian_clouds_res.get(cloud_handle) else {
+            continue;
+        };
+
+        let duplicate = duplicate_gaussians(cloud, &select.indicies);
+
+        commands.spawn((
+            GaussianCloudHandle(gaussian_clouds_res.add(duplicate)),
+            sort_settings.clone(),
+            appearance.clone(),
+            raster_settings.clone(),
+            debug_settings.clone(),
+            *transform,
+            Name::new("gaussian_cloud_duplicate"),
+        ));
+    }
+}
+
+
+/// transforms the selected gaussians in the local space of their cloud, pivoting on the selection centroid
+#[derive(Event, Debug, Reflect)]
+pub struct TransformSelectionEvent {
+    pub transform: Transform,
+}
+
+fn transform_selection(
+    mut events: EventReader<TransformSelectionEvent>,
+    mut history: Option<ResMut<EditHistory>>,
+    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    selections: Query<(
+        &GaussianCloudHandle,
+        &Select,
+    )>,
+) {
+    for event in events.read() {
+        for (
+            cloud_handle,
+            select,
+        ) in selections.iter() {
+            if select.indicies.is_empty() {
+                continue;
+            }
+
+            let Some(cloud) = gaussian_clouds_res.get_mut(cloud_handle) else {
+                continue;
+            };
+
+            let command = EditCommand::replace(cloud, &select.indicies, |cloud| {
+                transform_gaussians(cloud, &select.indicies, &event.transform);
+            });
+            if let Some(history) = history.as_mut() {
+                history.record(cloud_handle, command);
+            }
+        }
+    }
+}
+
+
+/// blends the spherical harmonic dc term of the selected gaussians towards `color` by `strength`
+#[derive(Event, Debug, Reflect)]
+pub struct RecolorSelectionEvent {
+    pub color: Color,
+    pub strength: f32,
+}
+
+impl Default for RecolorSelectionEvent {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            strength: 1.0,
+        }
+    }
+}
+
+fn recolor_selection(
+    mut events: EventReader<RecolorSelectionEvent>,
+    mut history: Option<ResMut<EditHistory>>,
+    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    selections: Query<(
+        &GaussianCloudHandle,
+        &Select,
+    )>,
+) {
+    for event in events.read() {
+        let color = Vec3::from_array(event.color.to_srgba().to_f32_array_no_alpha());
+
+        for (
+            cloud_handle,
+            select,
+        ) in selections.iter() {
+            if select.indicies.is_empty() {
+                continue;
+            }
+
+            let Some(cloud) = gaussian_clouds_res.get_mut(cloud_handle) else {
+                continue;
+            };
+
+            let command = EditCommand::replace(cloud, &select.indicies, |cloud| {
+                recolor_gaussians(cloud, &select.indicies, color, event.strength);
+            });
+            if let Some(history) = history.as_mut() {
+                history.record(cloud_handle, command);
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudHandle,
+    query::{
+        edit::delete_gaussians,
+        select::Select,
+    },
+    sort::SortedEntriesHandle,
+};
+
+
+#[derive(Default)]
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditHistory>();
+
+        app.add_event::<UndoEditEvent>();
+        app.add_event::<RedoEditEvent>();
+
+        app.add_systems(Update, apply_history_events);
+    }
+}
+
+
+/// reversible change to a single gaussian cloud asset
+#[derive(Clone, Debug)]
+pub enum EditCommand {
+    Visibility {
+        indicies: Vec<usize>,
+        before: Vec<f32>,
+        after: Vec<f32>,
+    },
+    /// gaussians removed from the cloud, `indicies` ascending in the cloud before the delete
+    Delete {
+        indicies: Vec<usize>,
+        removed: GaussianCloud,
+        visibility: Vec<f32>,
+        /// gaussians left by the delete, the edited cloud is padded past this by `resize_to_square`
+        kept: usize,
+    },
+    /// gaussians overwritten in place, e.g. by transforms or recoloring
+    Replace {
+        indicies: Vec<usize>,
+        before: GaussianCloud,
+        after: GaussianCloud,
+    },
+}
+
+impl EditCommand {
+    /// visibility diff from the current cloud to `visibility`, `None` when nothing changes
+    pub fn visibility(cloud: &GaussianCloud, visibility: &[f32]) -> Option<Self> {
+        let indicies = (0..cloud.len())
+            .filter(|&index| cloud.visibility(index) != visibility[index])
+            .collect::<Vec<_>>();
+
+        if indicies.is_empty() {
+            return None;
+        }
+
+        Some(Self::Visibility {
+            before: indicies.iter().map(|&index| cloud.visibility(index)).collect(),
+            after: indicies.iter().map(|&index| visibility[index]).collect(),
+            indicies,
+        })
+    }
+
+    /// records the gaussians at `indicies` before and after `edit` is applied to the cloud
+    pub fn replace(
+        cloud: &mut GaussianCloud,
+        indicies: &[usize],
+        edit: impl FnOnce(&mut GaussianCloud),
+    ) -> Self {
+        let before = cloud.subset(indicies);
+        edit(cloud);
+        let after = cloud.subset(indicies);
+
+        Self::Replace {
+            indicies: indicies.to_vec(),
+            before,
+            after,
+        }
+    }
+
+    /// deletes the gaussians at `indicies` from the cloud, recording them
+    pub fn delete(cloud: &mut GaussianCloud, indicies: &[usize]) -> Self {
+        let mut indicies = indicies.to_vec();
+        indicies.sort_unstable();
+        indicies.dedup();
+
+        let command = Self::Delete {
+            removed: cloud.subset(&indicies),
+            visibility: (0..cloud.len()).map(|index| cloud.visibility(index)).collect(),
+            kept: cloud.len() - indicies.len(),
+            indicies,
+        };
+        command.redo(cloud);
+
+        command
+    }
+
+    /// whether undoing or redoing the command adds or removes gaussians
+    pub fn resizes(&self) -> bool {
+        matches!(self, Self::Delete { .. })
+    }
+
+    pub fn undo(&self, cloud: &mut GaussianCloud) {
+        match self {
+            Self::Visibility { indicies, before, .. } => set_visibility(cloud, indicies, before),
+            Self::Delete { indicies, removed, visibility, kept } => {
+                let padded = cloud.len();
+
+                // the restored cloud is the kept gaussians followed by the removed ones, reordered to the original layout
+                let mut removed_iter = indicies.iter().enumerate().peekable();
+                let mut kept_index = 0;
+                let order = (0..kept + indicies.len())
+                    .map(|index| match removed_iter.next_if(|(_, &removed)| removed == index) {
+                        Some((removed_index, _)) => padded + removed_index,
+                        None => {
+                            kept_index += 1;
+                            kept_index - 1
+                        },
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut merged = cloud.clone();
+                merged.append(removed);
+
+                *cloud = merged.subset(&order);
+                for (index, &visibility) in visibility.iter().enumerate() {
+                    *cloud.visibility_mut(index) = visibility;
+                }
+            },
+            Self::Replace { indicies, before, .. } => cloud.set_subset(indicies, before),
+        }
+    }
+
+    pub fn redo(&self, cloud: &mut GaussianCloud) {
+        match self {
+            Self::Visibility { indicies, after, .. } => set_visibility(cloud, indicies, after),
+            Self::Delete { indicies, .. } => *cloud = delete_gaussians(cloud, indicies),
+            Self::Replace { indicies, after, .. } => cloud.set_subset(indicies, after),
+        }
+    }
+}
+
+fn set_visibility(cloud: &mut GaussianCloud, indicies: &[usize], visibility: &[f32]) {
+    for (&index, &visibility) in indicies.iter().zip(visibility) {
+        *cloud.visibility_mut(index) = visibility;
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct EditEntry {
+    pub cloud: AssetId<GaussianCloud>,
+    pub command: EditCommand,
+}
+
+/// undo and redo stacks of edits made to gaussian cloud assets by `query::select` and `query::edit`
+#[derive(Resource, Debug)]
+pub struct EditHistory {
+    pub undo: Vec<EditEntry>,
+    pub redo: Vec<EditEntry>,
+    /// oldest entries are dropped beyond this many undo steps
+    pub max_entries: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            max_entries: 64,
+        }
+    }
+}
+
+impl EditHistory {
+    /// records an already applied command, discarding the redo stack
+    pub fn record(
+        &mut self,
+        cloud: impl Into<AssetId<GaussianCloud>>,
+        command: EditCommand,
+    ) {
+        self.redo.clear();
+        self.undo.push(EditEntry {
+            cloud: cloud.into(),
+            command,
+        });
+
+        if self.undo.len() > self.max_entries {
+            let excess = self.undo.len() - self.max_entries;
+            self.undo.drain(..excess);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// reverts the latest edit, entries of removed clouds are dropped
+    pub fn undo(&mut self, clouds: &mut Assets<GaussianCloud>) -> Option<&EditEntry> {
+        let entry = self.undo.pop()?;
+
+        let cloud = clouds.get_mut(entry.cloud)?;
+        entry.command.undo(cloud);
+
+        self.redo.push(entry);
+        self.redo.last()
+    }
+
+    /// re-applies the latest undone edit, entries of removed clouds are dropped
+    pub fn redo(&mut self, clouds: &mut Assets<GaussianCloud>) -> Option<&EditEntry> {
+        let entry = self.redo.pop()?;
+
+        let cloud = clouds.get_mut(entry.cloud)?;
+        entry.command.redo(cloud);
+
+        self.undo.push(entry);
+        self.undo.last()
+    }
+}
+
+
+#[derive(Event, Debug, Reflect)]
+pub struct UndoEditEvent;
+
+#[derive(Event, Debug, Reflect)]
+pub struct RedoEditEvent;
+
+#[allow(clippy::type_complexity)]
+fn apply_history_events(
+    mut commands: Commands,
+    mut undo_events: EventReader<UndoEditEvent>,
+    mut redo_events: EventReader<RedoEditEvent>,
+    mut history: ResMut<EditHistory>,
+    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    mut gaussian_clouds: Query<(
+        Entity,
+        &GaussianCloudHandle,
+        Option<&mut Select>,
+    )>,
+) {
+    let mut changed = Vec::new();
+
+    for _ in undo_events.read() {
+        if let Some(entry) = history.undo(&mut gaussian_clouds_res) {
+            changed.push((entry.cloud, entry.command.resizes()));
+        }
+    }
+
+    for _ in redo_events.read() {
+        if let Some(entry) = history.redo(&mut gaussian_clouds_res) {
+            changed.push((entry.cloud, entry.command.resizes()));
+        }
+    }
+
+    for (id, resized) in changed {
+        let Some(cloud) = gaussian_clouds_res.get(id) else {
+            continue;
+        };
+
+        for (entity, cloud_handle, select) in gaussian_clouds.iter_mut() {
+            if cloud_handle.0.id() != id {
+                continue;
+            }
+
+            // selections and sorted entries can't be trusted once gaussians are added or removed
+            if resized {
+                commands.entity(entity)
+                    .remove::<Select>()
+                    .remove::<SortedEntriesHandle>();
+                continue;
+            }
+
+            // visibility is the applied selection, keep the component in sync with it
+            if let Some(mut select) = select {
+                select.indicies = (0..cloud.len())
+                    .filter(|&index| cloud.visibility(index) > 0.5)
+                    .collect();
+                select.completed = true;
+            }
+        }
+    }
+}
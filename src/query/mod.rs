@@ -1,11 +1,26 @@
 use bevy::prelude::*;
 
+#[cfg(feature = "query_bvh")]
+pub mod bvh;
+
+#[cfg(feature = "query_edit")]
+pub mod edit;
+
+#[cfg(feature = "query_edit")]
+pub mod history;
+
+#[cfg(feature = "query_pick")]
+pub mod pick;
+
 #[cfg(feature = "query_raycast")]
 pub mod raycast;
 
 #[cfg(feature = "query_select")]
 pub mod select;
 
+#[cfg(feature = "query_select")]
+pub mod select_tools;
+
 #[cfg(feature = "query_sparse")]
 pub mod sparse;
 
@@ -16,16 +31,28 @@ pub struct QueryPlugin;
 impl Plugin for QueryPlugin {
     #[allow(unused)]
     fn build(&self, app: &mut App) {
+        #[cfg(feature = "query_bvh")]
+        app.add_plugins(bvh::GaussianBvhPlugin);
+
+        #[cfg(feature = "query_edit")]
+        app.add_plugins((
+            edit::EditPlugin,
+            history::HistoryPlugin,
+        ));
+
+        #[cfg(feature = "query_pick")]
+        app.add_plugins(pick::PickPlugin);
+
         #[cfg(feature = "query_raycast")]
-        app.add_plugins(raycast::RaycastSelectionPlugin);
+        app.add_plugins(raycast::RaycastPlugin);
 
         #[cfg(feature = "query_select")]
         app.add_plugins(select::SelectPlugin);
 
-        #[cfg(all(
-            feature = "query_sparse",
-            not(feature = "precompute_covariance_3d"),
-        ))]
+        #[cfg(feature = "query_select")]
+        app.add_plugins(select_tools::SelectToolsPlugin);
+
+        #[cfg(feature = "query_sparse")]
         app.add_plugins(sparse::SparsePlugin);
     }
 }
@@ -0,0 +1,628 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use bevy::{
+    prelude::*,
+    core_pipeline::core_3d::graph::{
+        Core3d,
+        Node3d,
+    },
+    ecs::{
+        entity::Entities,
+        query::QueryItem,
+    },
+    render::{
+        extract_component::{
+            DynamicUniformIndex,
+            ExtractComponent,
+            ExtractComponentPlugin,
+        },
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+            ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::*,
+        renderer::{
+            RenderContext,
+            RenderDevice,
+        },
+        sync_world::MainEntity,
+        texture::{
+            CachedTexture,
+            TextureCache,
+        },
+        view::{
+            ExtractedView,
+            RenderVisibleEntities,
+            ViewUniformOffset,
+        },
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+};
+
+#[cfg(feature = "query_select")]
+use crate::{
+    query::select::Select,
+    GaussianCloud,
+};
+use crate::{
+    camera::GaussianCamera,
+    gaussian::{
+        cloud::GaussianCloudHandle,
+        settings::{
+            GaussianCloudAppearance,
+            GaussianCloudDebugSettings,
+            GaussianCloudRasterSettings,
+        },
+    },
+    render::{
+        draw_gaussian_cloud,
+        GaussianCloudBindGroup,
+        GaussianCloudPass,
+        GaussianCloudPipeline,
+        GaussianCloudPipelineKey,
+        GaussianCloudUniform,
+        GaussianUniformBindGroups,
+        GaussianViewBindGroup,
+        GpuGaussianCloud,
+    },
+    sort::{
+        GlobalSortMember,
+        GpuSortedEntry,
+        SortTrigger,
+        SortedEntriesHandle,
+    },
+};
+
+
+const PICK_FORMAT: TextureFormat = TextureFormat::R32Uint;
+const NO_PICK: u32 = u32::MAX;
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GaussianPickLabel;
+
+
+#[derive(Default)]
+pub struct PickPlugin;
+
+impl Plugin for PickPlugin {
+    fn build(&self, app: &mut App) {
+        let results = GaussianPickReadbacks::default();
+
+        app.register_type::<GaussianPicking>();
+        app.add_plugins(ExtractComponentPlugin::<GaussianPicking>::default());
+
+        app.add_event::<PickGaussianEvent>();
+        app.add_event::<GaussianPickedEvent>();
+
+        app.insert_resource(results.clone());
+
+        app.add_systems(First, clear_pick_requests);
+        app.add_systems(Update, (
+            queue_pick_requests,
+            receive_pick_results,
+        ));
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(results)
+                .add_render_graph_node::<ViewNodeRunner<GaussianPickNode>>(
+                    Core3d,
+                    GaussianPickLabel,
+                )
+                .add_render_graph_edges(
+                    Core3d,
+                    (
+                        Node3d::MainTransparentPass,
+                        GaussianPickLabel,
+                        Node3d::EndMainPass,
+                    ),
+                )
+                .add_systems(
+                    Render,
+                    (
+                        queue_pick_phase.in_set(RenderSet::Queue),
+                        prepare_pick_targets.in_set(RenderSet::PrepareResources),
+                        map_pick_readbacks.in_set(RenderSet::Cleanup),
+                    ),
+                );
+        }
+    }
+}
+
+
+/// requests the gaussian under a physical pixel of a camera's viewport, answered by `GaussianPickedEvent`
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct PickGaussianEvent {
+    pub camera: Entity,
+    pub position: UVec2,
+    /// add the picked gaussian to the cloud's `Select`, merged with the gaussians already selected
+    pub select: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct GaussianPick {
+    pub entity: Entity,
+    pub index: usize,
+}
+
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct GaussianPickedEvent {
+    pub camera: Entity,
+    pub position: UVec2,
+    /// nearest gaussian reaching the cloud's `depth_alpha_threshold`, scene meshes are not considered
+    pub hit: Option<GaussianPick>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct PickRequest {
+    pub position: UVec2,
+    pub select: bool,
+}
+
+/// pending pick requests of a camera, the id pass only runs while requests are pending
+#[derive(
+    Component,
+    ExtractComponent,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianPicking {
+    pub requests: Vec<PickRequest>,
+}
+
+
+struct PickReadback {
+    camera: Entity,
+    request: PickRequest,
+    gaussian_index: u32,
+    entity_index: u32,
+}
+
+/// completed readbacks, written by buffer map callbacks in the render world
+#[derive(Resource, Clone, Default)]
+struct GaussianPickReadbacks(Arc<Mutex<Vec<PickReadback>>>);
+
+
+fn queue_pick_requests(
+    mut commands: Commands,
+    mut events: EventReader<PickGaussianEvent>,
+    mut cameras: Query<Option<&mut GaussianPicking>, With<GaussianCamera>>,
+) {
+    for event in events.read() {
+        let request = PickRequest {
+            position: event.position,
+            select: event.select,
+        };
+
+        match cameras.get_mut(event.camera) {
+            Ok(Some(mut picking)) => picking.requests.push(request),
+            Ok(None) => {
+                commands.entity(event.camera).insert(GaussianPicking {
+                    requests: vec![request],
+                });
+            },
+            Err(_) => warn!("pick requested for {:?} which is not a gaussian camera", event.camera),
+        }
+    }
+}
+
+// runs after the previous frame's requests were extracted
+fn clear_pick_requests(
+    mut cameras: Query<&mut GaussianPicking>,
+) {
+    for mut picking in cameras.iter_mut() {
+        if !picking.requests.is_empty() {
+            picking.requests.clear();
+        }
+    }
+}
+
+fn receive_pick_results(
+    entities: &Entities,
+    readbacks: Res<GaussianPickReadbacks>,
+    mut picked: EventWriter<GaussianPickedEvent>,
+    #[cfg(feature = "query_select")]
+    mut commands: Commands,
+    #[cfg(feature = "query_select")]
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    #[cfg(feature = "query_select")]
+    mut selections: Query<(&GaussianCloudHandle, Option<&mut Select>)>,
+) {
+    let completed = std::mem::take(&mut *readbacks.0.lock().unwrap());
+
+    for readback in completed {
+        let hit = (readback.gaussian_index != NO_PICK)
+            .then(|| entities.resolve_from_id(readback.entity_index))
+            .flatten()
+            .map(|entity| GaussianPick {
+                entity,
+                index: readback.gaussian_index as usize,
+            });
+
+        #[cfg(feature = "query_select")]
+        if let Some(hit) = hit.filter(|_| readback.request.select) {
+            match selections.get_mut(hit.entity) {
+                Ok((_, Some(mut select))) => {
+                    if !select.indicies.contains(&hit.index) {
+                        select.indicies.push(hit.index);
+                    }
+                    select.completed = false;
+                },
+                // the current selection lives in the cloud visibility, merge into it instead of replacing it
+                Ok((handle, None)) => {
+                    if let Some(cloud) = gaussian_clouds_res.get(handle) {
+                        let select = (0..cloud.len())
+                            .filter(|&index| index == hit.index || cloud.visibility(index) > 0.5)
+                            .collect::<Select>();
+
+                        commands.entity(hit.entity).insert(select);
+                    }
+                },
+                Err(_) => {},
+            }
+        }
+
+        picked.send(GaussianPickedEvent {
+            camera: readback.camera,
+            position: readback.request.position,
+            hit,
+        });
+    }
+}
+
+
+/// clouds of a picking view drawn into the id targets, with their specialized pipelines
+#[derive(Component, Default)]
+pub struct GaussianPickPhase {
+    pub items: Vec<(Entity, CachedRenderPipelineId)>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_pick_phase(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    custom_pipeline: Res<GaussianCloudPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GaussianCloudPipeline>>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &RenderVisibleEntities,
+            &GaussianPicking,
+        ),
+        With<GaussianCamera>,
+    >,
+    gaussian_clouds: Query<
+        (
+            &GaussianCloudAppearance,
+            &GaussianCloudRasterSettings,
+            &GaussianCloudDebugSettings,
+        ),
+        Without<GlobalSortMember>,
+    >,
+) {
+    for (
+        view_entity,
+        view,
+        visible_entities,
+        picking,
+    ) in views.iter() {
+        if picking.requests.is_empty() {
+            commands.entity(view_entity).remove::<GaussianPickPhase>();
+            continue;
+        }
+
+        let items = visible_entities.iter::<With<GaussianCloudHandle>>()
+            .filter_map(|(render_entity, _)| {
+                let (
+                    appearance,
+                    raster_settings,
+                    debug_settings,
+                ) = gaussian_clouds.get(*render_entity).ok()?;
+
+                let key = GaussianCloudPipelineKey {
+                    sample_count: 1,
+                    hdr: view.hdr,
+                    pass: GaussianCloudPass::Pick,
+                    ..GaussianCloudPipelineKey::from_settings(appearance, raster_settings, debug_settings)
+                };
+
+                let pipeline = pipelines.specialize(&pipeline_cache, &custom_pipeline, key);
+
+                Some((*render_entity, pipeline))
+            })
+            .collect();
+
+        commands.entity(view_entity).insert(GaussianPickPhase { items });
+    }
+}
+
+
+/// id textures of a picking view and the readback buffers of its pending requests
+#[derive(Component)]
+pub struct GaussianPickTargets {
+    pub gaussian_index: CachedTexture,
+    pub entity_index: CachedTexture,
+    pub readbacks: Vec<(PickRequest, Buffer)>,
+}
+
+fn prepare_pick_targets(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    mut texture_cache: ResMut<TextureCache>,
+    views: Query<(
+        Entity,
+        &ExtractedView,
+        &GaussianPicking,
+    )>,
+) {
+    for (
+        entity,
+        view,
+        picking,
+    ) in views.iter() {
+        if picking.requests.is_empty() {
+            commands.entity(entity).remove::<GaussianPickTargets>();
+            continue;
+        }
+
+        let size = view.viewport.zw().max(UVec2::ONE);
+
+        let mut texture = |label: &'static str| texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some(label),
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: PICK_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+                view_formats: &[],
+            },
+        );
+
+        let readbacks = picking.requests.iter()
+            .filter(|request| request.position.cmplt(size).all())
+            .map(|request| {
+                let buffer = render_device.create_buffer(&BufferDescriptor {
+                    label: Some("gaussian pick readback buffer"),
+                    size: 2 * std::mem::size_of::<u32>() as u64,
+                    usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                });
+
+                (*request, buffer)
+            })
+            .collect();
+
+        commands.entity(entity).insert(GaussianPickTargets {
+            gaussian_index: texture("gaussian_pick_index_texture"),
+            entity_index: texture("gaussian_pick_entity_texture"),
+            readbacks,
+        });
+    }
+}
+
+fn map_pick_readbacks(
+    render_device: Res<RenderDevice>,
+    readbacks: Res<GaussianPickReadbacks>,
+    mut views: Query<(
+        &MainEntity,
+        &mut GaussianPickTargets,
+    )>,
+) {
+    for (
+        camera,
+        mut targets,
+    ) in views.iter_mut() {
+        for (request, buffer) in targets.readbacks.drain(..) {
+            let camera = camera.id();
+            let readbacks = readbacks.clone();
+            let mapped_buffer = buffer.clone();
+
+            buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                if result.is_err() {
+                    return;
+                }
+
+                let (gaussian_index, entity_index) = {
+                    let data = mapped_buffer.slice(..).get_mapped_range();
+                    let ids: &[u32] = bytemuck::cast_slice(&data);
+                    (ids[0], ids[1])
+                };
+                mapped_buffer.unmap();
+
+                readbacks.0.lock().unwrap().push(PickReadback {
+                    camera,
+                    request,
+                    gaussian_index,
+                    entity_index,
+                });
+            });
+        }
+    }
+
+    render_device.poll(wgpu::Maintain::Poll);
+}
+
+
+#[derive(Default)]
+pub struct GaussianPickNode;
+
+impl ViewNode for GaussianPickNode {
+    type ViewQuery = (
+        &'static GaussianCamera,
+        &'static GaussianViewBindGroup,
+        &'static ViewUniformOffset,
+        &'static SortTrigger,
+        &'static GaussianPickPhase,
+        &'static GaussianPickTargets,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (
+            gaussian_camera,
+            view_bind_group,
+            view_uniform_offset,
+            sort_trigger,
+            phase,
+            targets,
+        ): QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        if gaussian_camera.warmup || targets.readbacks.is_empty() {
+            return Ok(());
+        }
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let gaussian_clouds = world.resource::<RenderAssets<GpuGaussianCloud>>();
+        let sorted_entries = world.resource::<RenderAssets<GpuSortedEntry>>();
+
+        let Some(uniform_bind_group) = world.resource::<GaussianUniformBindGroups>().base_bind_group.as_ref() else {
+            return Ok(());
+        };
+
+        let clear = Operations {
+            load: LoadOp::Clear(wgpu::Color {
+                r: NO_PICK as f64,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            }),
+            store: StoreOp::Store,
+        };
+
+        {
+            let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("gaussian cloud pick pass"),
+                color_attachments: &[
+                    Some(RenderPassColorAttachment {
+                        view: &targets.gaussian_index.default_view,
+                        resolve_target: None,
+                        ops: clear,
+                    }),
+                    Some(RenderPassColorAttachment {
+                        view: &targets.entity_index.default_view,
+                        resolve_target: None,
+                        ops: clear,
+                    }),
+                ],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_bind_group(
+                0,
+                &view_bind_group.value,
+                &[view_uniform_offset.offset],
+            );
+
+            for (entity, pipeline_id) in phase.items.iter() {
+                let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else {
+                    continue;
+                };
+
+                let Ok(entity) = world.get_entity(*entity) else {
+                    continue;
+                };
+
+                let (
+                    Some(handle),
+                    Some(sorted_entries_handle),
+                    Some(bind_groups),
+                    Some(uniform_index),
+                ) = (
+                    entity.get::<GaussianCloudHandle>(),
+                    entity.get::<SortedEntriesHandle>(),
+                    entity.get::<GaussianCloudBindGroup>(),
+                    entity.get::<DynamicUniformIndex<GaussianCloudUniform>>(),
+                ) else {
+                    continue;
+                };
+
+                let Some(gpu_gaussian_cloud) = gaussian_clouds.get(handle) else {
+                    continue;
+                };
+
+                pass.set_render_pipeline(pipeline);
+                pass.set_bind_group(
+                    1,
+                    uniform_bind_group,
+                    &[uniform_index.index()],
+                );
+
+                draw_gaussian_cloud(
+                    &mut pass,
+                    sort_trigger.camera_index,
+                    gpu_gaussian_cloud,
+                    sorted_entries.get(sorted_entries_handle),
+                    bind_groups,
+                );
+            }
+        }
+
+        let command_encoder = render_context.command_encoder();
+
+        for (request, buffer) in targets.readbacks.iter() {
+            let textures = [
+                &targets.gaussian_index.texture,
+                &targets.entity_index.texture,
+            ];
+
+            for (slot, texture) in textures.into_iter().enumerate() {
+                command_encoder.copy_texture_to_buffer(
+                    ImageCopyTexture {
+                        texture,
+                        mip_level: 0,
+                        origin: Origin3d {
+                            x: request.position.x,
+                            y: request.position.y,
+                            z: 0,
+                        },
+                        aspect: TextureAspect::All,
+                    },
+                    ImageCopyBuffer {
+                        buffer,
+                        layout: ImageDataLayout {
+                            offset: (slot * std::mem::size_of::<u32>()) as u64,
+                            bytes_per_row: None,
+                            rows_per_image: None,
+                        },
+                    },
+                    Extent3d {
+                        width: 1,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,104 +1,318 @@
 use bevy::{
     prelude::*,
-    render::mesh::PrimitiveTopology,
+    ecs::system::SystemParam,
+    math::{
+        Mat3A,
+        Vec3A,
+    },
+};
+
+use crate::{
+    gaussian::settings::GaussianCloudAppearance,
+    query::bvh::{
+        GaussianBvh,
+        GaussianBvhs,
+        BVH_SIGMA_EXTENT,
+    },
+    GaussianCloud,
+    GaussianCloudHandle,
 };
-use std::collections::BTreeMap;
 
 
-struct Triangle {
-    vertices: [Vec3; 3],
+#[derive(Default)]
+pub struct RaycastPlugin;
+
+impl Plugin for RaycastPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianRaycastSettings>();
+        app.register_type::<GaussianRayHit>();
+    }
 }
 
-// TODO: update Handle<Mesh>
-fn point_in_mesh_system(
-    mesh_query: Query<(&Handle<Mesh>, &Transform)>,
-    point_query: Query<(&Point, Entity)>,
-    meshes: Res<Assets<Mesh>>,
-    mut commands: Commands,
-) {
-    for (mesh_handle, transform) in mesh_query.iter() {
-        if let Some(mesh) = meshes.get(mesh_handle) {
-            for (point, entity) in point_query.iter() {
-                let local_point = transform.compute_matrix().inverse().transform_point3(point.position);
-
-                if is_point_in_mesh(&local_point, mesh) {
-                    commands.entity(entity).insert(InsideMesh);
-                }
-            }
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+pub struct GaussianRaycastSettings {
+    /// opacity weighted density at which a ray hits a gaussian, the hit surface is the ellipsoid of that density
+    pub density_threshold: f32,
+    pub max_distance: f32,
+}
+
+impl Default for GaussianRaycastSettings {
+    fn default() -> Self {
+        Self {
+            density_threshold: 0.1,
+            max_distance: f32::INFINITY,
         }
     }
 }
 
-fn is_point_in_mesh(point: &Vec3, mesh: &Mesh) -> bool {
-    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
-        panic!("Mesh must be a triangle list");
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct GaussianRayHit {
+    pub entity: Entity,
+    pub index: usize,
+    /// world-space distance along the ray
+    pub distance: f32,
+    pub position: Vec3,
+    /// world-space normal of the density ellipsoid, facing the ray origin when it starts inside the gaussian
+    pub normal: Vec3,
+}
+
+
+/// hit of a ray in the local space of a gaussian cloud, `distance` is in units of the ray direction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GaussianCloudRayHit {
+    pub index: usize,
+    pub distance: f32,
+    pub normal: Vec3A,
+}
+
+
+/// ray casting against every visible gaussian cloud entity, usable from gameplay systems
+#[derive(SystemParam)]
+pub struct GaussianRaycast<'w, 's> {
+    bvhs: Res<'w, GaussianBvhs>,
+    gaussian_clouds: Res<'w, Assets<GaussianCloud>>,
+    entities: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static GaussianCloudHandle,
+            &'static GaussianCloudAppearance,
+            &'static GlobalTransform,
+            Option<&'static InheritedVisibility>,
+        ),
+    >,
+}
+
+impl GaussianRaycast<'_, '_> {
+    /// closest gaussian hit by the ray across all visible clouds
+    pub fn cast_ray(
+        &self,
+        ray: Ray3d,
+        settings: &GaussianRaycastSettings,
+    ) -> Option<GaussianRayHit> {
+        let mut closest: Option<GaussianRayHit> = None;
+
+        for (entity, ..) in self.entities.iter() {
+            let max_distance = closest.map_or(settings.max_distance, |hit| hit.distance);
+            let settings = GaussianRaycastSettings {
+                max_distance,
+                ..*settings
+            };
+
+            if let Some(hit) = self.cast_ray_entity(entity, ray, &settings) {
+                closest = Some(hit);
+            }
+        }
+
+        closest
     }
 
-    let vertices = if let Some(vertex_attribute) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
-        vertex_attribute
-            .as_float3()
-            .expect("Expected vertex positions as Vec3")
-    } else {
-        panic!("Mesh does not contain vertex positions");
-    };
-
-    let indices = if let Some(Indices::U32(indices)) = &mesh.indices() {
-        indices
-    } else {
-        panic!("Mesh indices must be of type U32");
-    };
-
-    let mut intersections = 0;
-    for chunk in indices.chunks_exact(3) {
-        let triangle = Triangle {
-            vertices: [
-                vertices[chunk[0] as usize],
-                vertices[chunk[1] as usize],
-                vertices[chunk[2] as usize],
-            ],
+    /// closest gaussian of a single cloud entity hit by the ray
+    pub fn cast_ray_entity(
+        &self,
+        entity: Entity,
+        ray: Ray3d,
+        settings: &GaussianRaycastSettings,
+    ) -> Option<GaussianRayHit> {
+        let (
+            entity,
+            cloud_handle,
+            appearance,
+            transform,
+            visibility,
+        ) = self.entities.get(entity).ok()?;
+
+        if visibility.is_some_and(|visibility| !visibility.get()) {
+            return None;
+        }
+
+        let cloud = self.gaussian_clouds.get(cloud_handle)?;
+        let bvh = self.bvhs.get(cloud_handle)?;
+
+        let world_from_local = transform.affine();
+        let local_from_world = world_from_local.inverse();
+
+        // the local direction keeps its length so local distances match world distances
+        let origin = local_from_world.transform_point3a(ray.origin.into());
+        let direction = local_from_world.transform_vector3a(ray.direction.as_vec3().into());
+
+        let hit = raycast_cloud(
+            cloud,
+            bvh,
+            origin,
+            direction,
+            appearance.global_scale,
+            appearance.global_opacity,
+            settings,
+        )?;
+
+        let normal = local_from_world.matrix3.transpose() * hit.normal;
+
+        Some(GaussianRayHit {
+            entity,
+            index: hit.index,
+            distance: hit.distance,
+            position: ray.get_point(hit.distance),
+            normal: normal.normalize_or_zero().into(),
+        })
+    }
+
+    /// whether no gaussian blocks the segment between two world-space points
+    pub fn line_of_sight(
+        &self,
+        from: Vec3,
+        to: Vec3,
+        settings: &GaussianRaycastSettings,
+    ) -> bool {
+        let Ok(direction) = Dir3::new(to - from) else {
+            return true;
+        };
+
+        let settings = GaussianRaycastSettings {
+            max_distance: from.distance(to).min(settings.max_distance),
+            ..*settings
+        };
+
+        self.cast_ray(Ray3d::new(from, direction), &settings).is_none()
+    }
+}
+
+
+/// closest gaussian of a cloud hit by a local-space ray, traversing its bvh front to back
+pub fn raycast_cloud(
+    cloud: &GaussianCloud,
+    bvh: &GaussianBvh,
+    origin: Vec3A,
+    direction: Vec3A,
+    global_scale: f32,
+    global_opacity: f32,
+    settings: &GaussianRaycastSettings,
+) -> Option<GaussianCloudRayHit> {
+    let inverse_direction = direction.recip();
+    let mut closest: Option<GaussianCloudRayHit> = None;
+    let mut max_distance = settings.max_distance;
+
+    let mut stack = vec![0];
+    while let Some(node_index) = stack.pop() {
+        let Some(node) = bvh.nodes.get(node_index) else {
+            continue;
         };
 
-        let ray_direction = Vec3::new(1.0, 0.0, 0.0);
-        if ray_intersects_triangle(point, &ray_direction, &triangle) {
-            intersections += 1;
+        match node.ray_interval(origin, inverse_direction, global_scale) {
+            Some((near, _)) if near <= max_distance => {}
+            _ => continue,
+        }
+
+        if !node.is_leaf() {
+            let first = node.child as usize;
+            let near_first = bvh.nodes[first].ray_interval(origin, inverse_direction, global_scale)
+                .map_or(f32::INFINITY, |(near, _)| near);
+            let near_second = bvh.nodes[first + 1].ray_interval(origin, inverse_direction, global_scale)
+                .map_or(f32::INFINITY, |(near, _)| near);
+
+            if near_first <= near_second {
+                stack.push(first + 1);
+                stack.push(first);
+            } else {
+                stack.push(first);
+                stack.push(first + 1);
+            }
+
+            continue;
+        }
+
+        for &index in bvh.node_indices(node) {
+            let Some((distance, normal)) = raycast_gaussian(
+                cloud,
+                index as usize,
+                origin,
+                direction,
+                global_scale,
+                global_opacity,
+                settings.density_threshold,
+            ) else {
+                continue;
+            };
+
+            if distance <= max_distance {
+                max_distance = distance;
+                closest = Some(GaussianCloudRayHit {
+                    index: index as usize,
+                    distance,
+                    normal,
+                });
+            }
         }
     }
 
-    intersections % 2 != 0
+    closest
 }
 
+/// distance and local-space normal where a ray enters the density threshold ellipsoid of a gaussian
+pub fn raycast_gaussian(
+    cloud: &GaussianCloud,
+    index: usize,
+    origin: Vec3A,
+    direction: Vec3A,
+    global_scale: f32,
+    global_opacity: f32,
+    density_threshold: f32,
+) -> Option<(f32, Vec3A)> {
+    if cloud.visibility(index) < 0.5 {
+        return None;
+    }
+
+    let (_, opacity) = cloud.max_scale_opacity(index);
+    let opacity = opacity * global_opacity;
+    if opacity <= density_threshold || density_threshold <= 0.0 {
+        return None;
+    }
 
-fn ray_intersects_triangle(ray_origin: &Vec3, ray_direction: &Vec3, triangle: &Triangle) -> bool {
-    let epsilon = 0.000_001;
-    let vertex0 = triangle.vertices[0];
-    let vertex1 = triangle.vertices[1];
-    let vertex2 = triangle.vertices[2];
+    // squared mahalanobis radius where opacity * exp(-r^2 / 2) reaches the threshold, clamped to the bvh bounds
+    let radius_sq = (2.0 * (opacity / density_threshold).ln()).min(BVH_SIGMA_EXTENT * BVH_SIGMA_EXTENT);
 
-    let edge1 = vertex1 - vertex0;
-    let edge2 = vertex2 - vertex0;
-    let h = ray_direction.cross(edge2);
-    let a = edge1.dot(h);
+    let cov3d = cloud.covariance_3d(index);
+    let covariance = Mat3A::from_cols(
+        Vec3A::new(cov3d[0], cov3d[1], cov3d[2]),
+        Vec3A::new(cov3d[1], cov3d[3], cov3d[4]),
+        Vec3A::new(cov3d[2], cov3d[4], cov3d[5]),
+    ) * (global_scale * global_scale);
 
-    if a > -epsilon && a < epsilon {
-        return false;
+    // regularize flat gaussians so the covariance stays invertible
+    let epsilon = (covariance.x_axis.x + covariance.y_axis.y + covariance.z_axis.z).max(f32::MIN_POSITIVE) * 1e-6;
+    let inverse_covariance = (covariance + Mat3A::from_diagonal(Vec3::splat(epsilon))).inverse();
+    if !inverse_covariance.is_finite() {
+        return None;
     }
 
-    let f = 1.0 / a;
-    let s = *ray_origin - vertex0;
-    let u = f * s.dot(h);
+    let offset = origin - Vec3A::from(*cloud.position(index));
+    let weighted_direction = inverse_covariance * direction;
+
+    let a = direction.dot(weighted_direction);
+    let b = offset.dot(weighted_direction);
+    let c = offset.dot(inverse_covariance * offset) - radius_sq;
 
-    if u < 0.0 || u > 1.0 {
-        return false;
+    let discriminant = b * b - a * c;
+    if a <= 0.0 || discriminant < 0.0 {
+        return None;
     }
 
-    let q = s.cross(edge1);
-    let v = f * ray_direction.dot(q);
+    let root = discriminant.sqrt();
+    let exit = (-b + root) / a;
+    if exit < 0.0 {
+        return None;
+    }
 
-    if v < 0.0 || u + v > 1.0 {
-        return false;
+    let entry = (-b - root) / a;
+    if entry < 0.0 {
+        return Some((0.0, -direction.normalize_or_zero()));
     }
 
-    let t = f * edge2.dot(q);
+    let normal = inverse_covariance * (offset + direction * entry);
 
-    t > epsilon
+    Some((entry, normal.normalize_or_zero()))
 }
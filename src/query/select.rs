@@ -1,9 +1,29 @@
-use bevy::prelude::*;
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    tasks::{
+        block_on,
+        futures_lite::future,
+        IoTaskPool,
+        Task,
+    },
+    utils::HashSet,
+};
 
 use crate::{
     GaussianCloud,
     GaussianCloudHandle,
-    io::writer::write_gaussian_cloud_to_file,
+    io::writer::{
+        write_gaussian_cloud_to_file,
+        GaussianCloudFormat,
+    },
+};
+
+#[cfg(feature = "query_edit")]
+use crate::query::history::{
+    EditCommand,
+    EditHistory,
 };
 
 
@@ -20,7 +40,53 @@ impl FromIterator<usize> for Select {
     }
 }
 
+/// how the gaussians found by a selection tool combine with an existing `Select`
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum SelectMode {
+    #[default]
+    Replace,
+    Add,
+    Subtract,
+    Intersect,
+}
+
 impl Select {
+    /// combines `indicies` into the selection and marks it for re-application
+    pub fn apply(&mut self, indicies: Vec<usize>, mode: SelectMode) {
+        let current = self.indicies.iter().copied().collect::<HashSet<_>>();
+
+        let mut combined = match mode {
+            SelectMode::Replace => indicies,
+            SelectMode::Add => current.into_iter()
+                .chain(indicies)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect(),
+            SelectMode::Subtract => {
+                let removed = indicies.into_iter().collect::<HashSet<_>>();
+                current.difference(&removed).copied().collect()
+            },
+            SelectMode::Intersect => indicies.into_iter()
+                .filter(|index| current.contains(index))
+                .collect(),
+        };
+
+        combined.sort_unstable();
+        combined.dedup();
+
+        self.indicies = combined;
+        self.completed = false;
+    }
+
     pub fn invert(&mut self, cloud_size: usize) -> Select {
         let inverted = (0..cloud_size)
             .filter(|index| !self.indicies.contains(index))
@@ -40,14 +106,20 @@ pub struct SelectPlugin;
 impl Plugin for SelectPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Select>();
+        app.register_type::<SelectMode>();
 
         app.add_event::<InvertSelectionEvent>();
         app.add_event::<SaveSelectionEvent>();
+        app.add_event::<SelectionSavedEvent>();
+        app.add_event::<SaveSelectionFailedEvent>();
+
+        app.init_resource::<SaveSelectionTasks>();
 
         app.add_systems(Update, (
             apply_selection,
             invert_selection,
             save_selection,
+            poll_save_selection_tasks,
         ));
     }
 }
@@ -55,6 +127,8 @@ impl Plugin for SelectPlugin {
 
 fn apply_selection(
     asset_server: Res<AssetServer>,
+    #[cfg(feature = "query_edit")]
+    mut history: Option<ResMut<EditHistory>>,
     mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
     mut selections: Query<(
         Entity,
@@ -79,14 +153,23 @@ fn apply_selection(
 
         let cloud = gaussian_clouds_res.get_mut(cloud_handle).unwrap();
 
-        (0..cloud.len())
+        let mut visibility = vec![0.0; cloud.len()];
+        select.indicies.iter()
             .for_each(|index| {
-                *cloud.visibility_mut(index) = 0.0;
+                visibility[*index] = 1.0;
             });
 
-        select.indicies.iter()
-            .for_each(|index| {
-                *cloud.visibility_mut(*index) = 1.0;
+        #[cfg(feature = "query_edit")]
+        if let Some(history) = history.as_mut() {
+            if let Some(command) = EditCommand::visibility(cloud, &visibility) {
+                history.record(cloud_handle, command);
+            }
+        }
+
+        visibility.into_iter()
+            .enumerate()
+            .for_each(|(index, visibility)| {
+                *cloud.visibility_mut(index) = visibility;
             });
 
         select.completed = true;
@@ -100,7 +183,7 @@ pub struct InvertSelectionEvent;
 
 fn invert_selection(
     mut events: EventReader<InvertSelectionEvent>,
-    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
     mut selections: Query<(
         Entity,
         &GaussianCloudHandle,
@@ -121,55 +204,173 @@ fn invert_selection(
             continue;
         }
 
-        let cloud = gaussian_clouds_res.get_mut(cloud_handle).unwrap();
+        let cloud = gaussian_clouds_res.get(cloud_handle).unwrap();
 
-        let mut new_indicies = Vec::with_capacity(cloud.len() - select.indicies.len());
+        let new_indicies = (0..cloud.len())
+            .filter(|&index| cloud.visibility(index) == 0.0)
+            .collect();
 
-        (0..cloud.len())
-            .for_each(|index| {
-                if cloud.visibility(index) == 0.0 {
-                    new_indicies.push(index);
-                }
+        // visibility is updated (and recorded) by `apply_selection`
+        select.indicies = new_indicies;
+        select.completed = false;
+    }
+}
 
-                *cloud.visibility_mut(index) = 1.0;
-            });
 
-        select.indicies.iter()
-            .for_each(|index| {
-                *cloud.visibility_mut(*index) = 0.0;
-            });
+/// writes the selected gaussians to `path`, on the io task pool
+///
+/// without an `entity` every selection is saved, each to `path` with the entity appended to the file
+/// stem when more than one selection exists.
+#[derive(Event, Clone, Debug, Reflect)]
+pub struct SaveSelectionEvent {
+    pub path: PathBuf,
+    pub format: GaussianCloudFormat,
+    pub entity: Option<Entity>,
+}
 
-        select.indicies = new_indicies;
+impl Default for SaveSelectionEvent {
+    fn default() -> Self {
+        Self {
+            path: "live_output.gcloud".into(),
+            format: GaussianCloudFormat::Gcloud,
+            entity: None,
+        }
     }
 }
 
+impl SaveSelectionEvent {
+    /// format inferred from the path extension, falling back to gcloud
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let format = GaussianCloudFormat::from_path(&path).unwrap_or_default();
 
-#[derive(Event, Debug, Reflect)]
-pub struct SaveSelectionEvent;
+        Self {
+            path,
+            format,
+            entity: None,
+        }
+    }
+
+    /// saves only the selection of `entity`
+    pub fn with_entity(mut self, entity: Entity) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+    /// `path` with `entity` appended to the file stem, e.g. `live_output_12v1.gcloud`
+    pub fn entity_path(&self, entity: Entity) -> PathBuf {
+        let mut file_name = self.path.file_stem().unwrap_or_default().to_os_string();
+        file_name.push(format!("_{}", entity));
 
-pub fn save_selection(
+        if let Some(extension) = self.path.extension() {
+            file_name.push(".");
+            file_name.push(extension);
+        }
+
+        self.path.with_file_name(file_name)
+    }
+}
+
+/// a `SaveSelectionEvent` write for `entity` finished
+#[derive(Event, Clone, Debug, Reflect)]
+pub struct SelectionSavedEvent {
+    pub entity: Entity,
+    pub path: PathBuf,
+}
+
+/// a `SaveSelectionEvent` write for `entity` failed
+#[derive(Event, Clone, Debug, Reflect)]
+pub struct SaveSelectionFailedEvent {
+    pub entity: Entity,
+    pub path: PathBuf,
+    pub error: String,
+}
+
+struct SaveSelectionTask {
+    entity: Entity,
+    path: PathBuf,
+    task: Task<std::io::Result<()>>,
+}
+
+#[derive(Resource, Default)]
+struct SaveSelectionTasks(Vec<SaveSelectionTask>);
+
+fn save_selection(
     mut events: EventReader<SaveSelectionEvent>,
-    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
-    mut selections: Query<(
+    mut tasks: ResMut<SaveSelectionTasks>,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    selections: Query<(
         Entity,
         &GaussianCloudHandle,
         &Select,
     )>,
 ) {
-    if events.is_empty() {
-        return;
-    }
-    events.clear();
+    for event in events.read() {
+        let targets = selections.iter()
+            .filter(|(entity, ..)| event.entity.is_none_or(|target| target == *entity))
+            .collect::<Vec<_>>();
+
+        for (
+            entity,
+            cloud_handle,
+            select,
+        ) in targets.iter().copied() {
+            let Some(cloud) = gaussian_clouds_res.get(cloud_handle) else {
+                continue;
+            };
 
-    for (
-        _entity,
-        cloud_handle,
-        select,
-    ) in selections.iter_mut() {
-        let cloud = gaussian_clouds_res.get_mut(cloud_handle).unwrap();
+            let selected = cloud.subset(select.indicies.as_slice());
+
+            // concurrent writes to one path would clobber each other
+            let path = if targets.len() > 1 {
+                event.entity_path(entity)
+            } else {
+                event.path.clone()
+            };
 
-        let selected = cloud.subset(select.indicies.as_slice());
+            let task_path = path.clone();
+            let format = event.format;
+            let task = IoTaskPool::get().spawn(async move {
+                write_gaussian_cloud_to_file(&selected, task_path, format)
+            });
 
-        write_gaussian_cloud_to_file(&selected, "live_output.gcloud");
+            tasks.0.push(SaveSelectionTask {
+                entity,
+                path,
+                task,
+            });
+        }
     }
 }
+
+fn poll_save_selection_tasks(
+    mut tasks: ResMut<SaveSelectionTasks>,
+    mut saved_events: EventWriter<SelectionSavedEvent>,
+    mut failed_events: EventWriter<SaveSelectionFailedEvent>,
+) {
+    tasks.0.retain_mut(|save| {
+        let Some(result) = block_on(future::poll_once(&mut save.task)) else {
+            return true;
+        };
+
+        match result {
+            Ok(()) => {
+                saved_events.send(SelectionSavedEvent {
+                    entity: save.entity,
+                    path: save.path.clone(),
+                });
+            },
+            Err(error) => {
+                warn!("failed to save selection to {}: {}", save.path.display(), error);
+
+                failed_events.send(SaveSelectionFailedEvent {
+                    entity: save.entity,
+                    path: save.path.clone(),
+                    error: error.to_string(),
+                });
+            },
+        }
+
+        false
+    });
+}
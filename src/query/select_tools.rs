@@ -0,0 +1,520 @@
+use bevy::{
+    prelude::*,
+    math::Vec3A,
+};
+
+use crate::{
+    GaussianCloud,
+    GaussianCloudHandle,
+    query::{
+        bvh::{
+            GaussianBvh,
+            GaussianBvhs,
+        },
+        select::{
+            Select,
+            SelectMode,
+        },
+    },
+};
+
+
+#[derive(Default)]
+pub struct SelectToolsPlugin;
+
+impl Plugin for SelectToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<RectSelect>();
+        app.register_type::<LassoSelect>();
+        app.register_type::<BrushSelect>();
+        app.register_type::<OpacitySelect>();
+        app.register_type::<ColorSelect>();
+
+        app.add_systems(Update, (
+            select_tool_handler::<RectSelect>,
+            select_tool_handler::<LassoSelect>,
+            select_tool_handler::<BrushSelect>,
+            select_tool_handler::<OpacitySelect>,
+            select_tool_handler::<ColorSelect>,
+        ));
+    }
+}
+
+
+/// projection of cloud-local positions into the logical viewport of a camera
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewportProjection {
+    pub clip_from_local: Mat4,
+    pub viewport: Rect,
+}
+
+impl ViewportProjection {
+    pub fn new(
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        cloud_transform: &GlobalTransform,
+    ) -> Option<Self> {
+        let clip_from_world = camera.clip_from_view() * camera_transform.compute_matrix().inverse();
+
+        Some(Self {
+            clip_from_local: clip_from_world * cloud_transform.compute_matrix(),
+            viewport: camera.logical_viewport_rect()?,
+        })
+    }
+
+    /// viewport position with y pointing down, `None` when the point lies outside the depth range of the camera
+    pub fn project(&self, position: Vec3) -> Option<Vec2> {
+        let clip = self.clip_from_local * position.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let ndc = clip.truncate() / clip.w;
+        if !(0.0..=1.0).contains(&ndc.z) {
+            return None;
+        }
+
+        let uv = Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) / 2.0;
+
+        Some(self.viewport.min + uv * self.viewport.size())
+    }
+
+    /// local-space `normal_d` half spaces bounding the points that `project` maps into `rect`
+    pub fn half_spaces(&self, rect: Rect) -> [Vec4; 6] {
+        // inverse of the viewport mapping in `project`, flipping y back up
+        let to_ndc = |point: Vec2| {
+            let uv = (point - self.viewport.min) / self.viewport.size();
+            Vec2::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0)
+        };
+        let ndc_min = to_ndc(rect.min).min(to_ndc(rect.max));
+        let ndc_max = to_ndc(rect.min).max(to_ndc(rect.max));
+
+        let rows = [
+            self.clip_from_local.row(0),
+            self.clip_from_local.row(1),
+            self.clip_from_local.row(2),
+            self.clip_from_local.row(3),
+        ];
+
+        [
+            rows[0] - rows[3] * ndc_min.x,
+            rows[3] * ndc_max.x - rows[0],
+            rows[1] - rows[3] * ndc_min.y,
+            rows[3] * ndc_max.y - rows[1],
+            rows[2],
+            rows[3] - rows[2],
+        ]
+    }
+}
+
+
+/// a selection tool placed on a gaussian cloud entity, evaluated once until `completed` is reset
+pub trait SelectTool: Component {
+    fn mode(&self) -> SelectMode;
+
+    fn completed(&self) -> bool;
+
+    fn set_completed(&mut self, completed: bool);
+
+    /// camera whose viewport the tool works in, screen-space tools wait until it is available
+    fn camera(&self) -> Option<Entity> {
+        None
+    }
+
+    /// `bvh` narrows spatial tools to candidate gaussians, tools scan the whole cloud without it
+    fn select(
+        &self,
+        cloud: &GaussianCloud,
+        bvh: Option<&GaussianBvh>,
+        cloud_transform: &GlobalTransform,
+        projection: Option<&ViewportProjection>,
+    ) -> Vec<usize>;
+}
+
+
+/// selects gaussians whose centers project into a screen-space rectangle
+#[derive(Component, Clone, Debug, Reflect)]
+pub struct RectSelect {
+    pub camera: Entity,
+    /// logical viewport coordinates, as reported by `Window::cursor_position`
+    pub rect: Rect,
+    pub mode: SelectMode,
+    pub completed: bool,
+}
+
+impl SelectTool for RectSelect {
+    fn mode(&self) -> SelectMode {
+        self.mode
+    }
+
+    fn completed(&self) -> bool {
+        self.completed
+    }
+
+    fn set_completed(&mut self, completed: bool) {
+        self.completed = completed;
+    }
+
+    fn camera(&self) -> Option<Entity> {
+        Some(self.camera)
+    }
+
+    fn select(
+        &self,
+        cloud: &GaussianCloud,
+        bvh: Option<&GaussianBvh>,
+        _cloud_transform: &GlobalTransform,
+        projection: Option<&ViewportProjection>,
+    ) -> Vec<usize> {
+        let Some(projection) = projection else {
+            return vec![];
+        };
+
+        select_projected(cloud, bvh, projection, self.rect, |point| self.rect.contains(point))
+    }
+}
+
+
+/// selects gaussians whose centers project into a screen-space polygon
+#[derive(Component, Clone, Debug, Reflect)]
+pub struct LassoSelect {
+    pub camera: Entity,
+    /// closed polygon in logical viewport coordinates
+    pub points: Vec<Vec2>,
+    pub mode: SelectMode,
+    pub completed: bool,
+}
+
+impl SelectTool for LassoSelect {
+    fn mode(&self) -> SelectMode {
+        self.mode
+    }
+
+    fn completed(&self) -> bool {
+        self.completed
+    }
+
+    fn set_completed(&mut self, completed: bool) {
+        self.completed = completed;
+    }
+
+    fn camera(&self) -> Option<Entity> {
+        Some(self.camera)
+    }
+
+    fn select(
+        &self,
+        cloud: &GaussianCloud,
+        bvh: Option<&GaussianBvh>,
+        _cloud_transform: &GlobalTransform,
+        projection: Option<&ViewportProjection>,
+    ) -> Vec<usize> {
+        let Some(projection) = projection else {
+            return vec![];
+        };
+
+        if self.points.len() < 3 {
+            return vec![];
+        }
+
+        let bounds = self.points.iter()
+            .fold(Rect::EMPTY, |bounds, point| bounds.union_point(*point));
+
+        select_projected(cloud, bvh, projection, bounds, |point| {
+            bounds.contains(point) && polygon_contains(&self.points, point)
+        })
+    }
+}
+
+/// even-odd test of a point against a closed polygon
+pub fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+
+    let mut previous = polygon[polygon.len() - 1];
+    for &current in polygon {
+        if (current.y > point.y) != (previous.y > point.y) {
+            let x = current.x + (point.y - current.y) / (previous.y - current.y) * (previous.x - current.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+
+        previous = current;
+    }
+
+    inside
+}
+
+fn select_projected(
+    cloud: &GaussianCloud,
+    bvh: Option<&GaussianBvh>,
+    projection: &ViewportProjection,
+    bounds: Rect,
+    filter: impl Fn(Vec2) -> bool,
+) -> Vec<usize> {
+    let projects_inside = |index: &usize| {
+        projection.project(Vec3::from(*cloud.position(*index))).is_some_and(&filter)
+    };
+
+    match bvh {
+        Some(bvh) => {
+            // a pixel of slack keeps gaussians on the bounds that the half space test rounds away
+            let half_spaces = projection.half_spaces(bounds.inflate(1.0));
+
+            bvh.within_half_spaces(cloud, &half_spaces, 0.0)
+                .into_iter()
+                .filter(projects_inside)
+                .collect()
+        },
+        None => (0..cloud.len())
+            .filter(projects_inside)
+            .collect(),
+    }
+}
+
+
+/// selects gaussians whose centers lie in a world-space sphere
+#[derive(Component, Clone, Debug, Reflect)]
+pub struct BrushSelect {
+    pub center: Vec3,
+    pub radius: f32,
+    pub mode: SelectMode,
+    pub completed: bool,
+}
+
+impl Default for BrushSelect {
+    fn default() -> Self {
+        Self {
+            center: Vec3::ZERO,
+            radius: 0.1,
+            mode: SelectMode::Add,
+            completed: false,
+        }
+    }
+}
+
+impl SelectTool for BrushSelect {
+    fn mode(&self) -> SelectMode {
+        self.mode
+    }
+
+    fn completed(&self) -> bool {
+        self.completed
+    }
+
+    fn set_completed(&mut self, completed: bool) {
+        self.completed = completed;
+    }
+
+    fn select(
+        &self,
+        cloud: &GaussianCloud,
+        bvh: Option<&GaussianBvh>,
+        cloud_transform: &GlobalTransform,
+        _projection: Option<&ViewportProjection>,
+    ) -> Vec<usize> {
+        let world_from_local = cloud_transform.affine();
+        let radius_squared = self.radius * self.radius;
+
+        let in_brush = |index: &usize| {
+            let position = world_from_local.transform_point3(Vec3::from(*cloud.position(*index)));
+            position.distance_squared(self.center) <= radius_squared
+        };
+
+        match bvh {
+            Some(bvh) => {
+                // the world sphere is an ellipsoid in local space, query the sphere bounding it
+                let local_center = world_from_local.inverse().transform_point3a(Vec3A::from(self.center));
+                let min_scale = cloud_transform.scale().abs().min_element();
+                if min_scale <= 0.0 {
+                    return vec![];
+                }
+
+                bvh.within_radius(cloud, local_center, self.radius / min_scale)
+                    .into_iter()
+                    .filter(in_brush)
+                    .collect()
+            },
+            None => (0..cloud.len())
+                .filter(in_brush)
+                .collect(),
+        }
+    }
+}
+
+
+/// selects gaussians with an opacity in `min..=max`
+#[derive(Component, Clone, Debug, Reflect)]
+pub struct OpacitySelect {
+    pub min: f32,
+    pub max: f32,
+    pub mode: SelectMode,
+    pub completed: bool,
+}
+
+impl Default for OpacitySelect {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 0.1,
+            mode: SelectMode::Replace,
+            completed: false,
+        }
+    }
+}
+
+impl SelectTool for OpacitySelect {
+    fn mode(&self) -> SelectMode {
+        self.mode
+    }
+
+    fn completed(&self) -> bool {
+        self.completed
+    }
+
+    fn set_completed(&mut self, completed: bool) {
+        self.completed = completed;
+    }
+
+    fn select(
+        &self,
+        cloud: &GaussianCloud,
+        _bvh: Option<&GaussianBvh>,
+        _cloud_transform: &GlobalTransform,
+        _projection: Option<&ViewportProjection>,
+    ) -> Vec<usize> {
+        (0..cloud.len())
+            .filter(|&index| {
+                let (_, opacity) = cloud.max_scale_opacity(index);
+                (self.min..=self.max).contains(&opacity)
+            })
+            .collect()
+    }
+}
+
+
+/// selects gaussians whose view independent color is within `tolerance` (euclidean srgb distance) of `color`
+#[derive(Component, Clone, Debug, Reflect)]
+pub struct ColorSelect {
+    pub color: Color,
+    pub tolerance: f32,
+    pub mode: SelectMode,
+    pub completed: bool,
+}
+
+impl Default for ColorSelect {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            tolerance: 0.1,
+            mode: SelectMode::Replace,
+            completed: false,
+        }
+    }
+}
+
+impl SelectTool for ColorSelect {
+    fn mode(&self) -> SelectMode {
+        self.mode
+    }
+
+    fn completed(&self) -> bool {
+        self.completed
+    }
+
+    fn set_completed(&mut self, completed: bool) {
+        self.completed = completed;
+    }
+
+    fn select(
+        &self,
+        cloud: &GaussianCloud,
+        _bvh: Option<&GaussianBvh>,
+        _cloud_transform: &GlobalTransform,
+        _projection: Option<&ViewportProjection>,
+    ) -> Vec<usize> {
+        let target = Vec3::from_array(self.color.to_srgba().to_f32_array_no_alpha());
+        let tolerance_squared = self.tolerance * self.tolerance;
+
+        (0..cloud.len())
+            .filter(|&index| {
+                let color = cloud.spherical_harmonic(index).base_color().clamp(Vec3::ZERO, Vec3::ONE);
+                color.distance_squared(target) <= tolerance_squared
+            })
+            .collect()
+    }
+}
+
+
+#[allow(clippy::type_complexity)]
+fn select_tool_handler<T: SelectTool>(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    bvhs: Option<Res<GaussianBvhs>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut tools: Query<(
+        Entity,
+        &GaussianCloudHandle,
+        &GlobalTransform,
+        &mut T,
+        Option<&mut Select>,
+    )>,
+) {
+    for (
+        entity,
+        cloud_handle,
+        cloud_transform,
+        mut tool,
+        select,
+    ) in tools.iter_mut() {
+        if tool.completed() {
+            continue;
+        }
+
+        if let Some(load_state) = asset_server.get_load_state(&cloud_handle.0) {
+            if load_state.is_loading() {
+                continue;
+            }
+        }
+
+        let Some(cloud) = gaussian_clouds_res.get(cloud_handle) else {
+            continue;
+        };
+
+        let projection = match tool.camera() {
+            Some(camera) => {
+                let Some(projection) = cameras.get(camera)
+                    .ok()
+                    .and_then(|(camera, camera_transform)| {
+                        ViewportProjection::new(camera, camera_transform, cloud_transform)
+                    })
+                else {
+                    continue;
+                };
+
+                Some(projection)
+            },
+            None => None,
+        };
+
+        tool.set_completed(true);
+
+        // bvhs catch up with asset edits in `PreUpdate`, skip one that was built for another gaussian count
+        let bvh = bvhs.as_ref()
+            .and_then(|bvhs| bvhs.get(cloud_handle))
+            .filter(|bvh| bvh.indices.len() == cloud.len());
+
+        let indicies = tool.select(cloud, bvh, cloud_transform, projection.as_ref());
+
+        match select {
+            Some(mut select) => select.apply(indicies, tool.mode()),
+            None => {
+                let mut select = Select::default();
+                select.apply(indicies, tool.mode());
+
+                commands.entity(entity).insert(select);
+            },
+        }
+    }
+}
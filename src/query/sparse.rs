@@ -1,29 +1,20 @@
 use bevy::{
     prelude::*,
-    asset::LoadState,
+    math::Vec3A,
 };
-use kd_tree::{
-    KdPoint,
-    KdTree,
-};
-use static_assertions::assert_cfg;
-use typenum::consts::U3;
 
 use crate::{
-    Gaussian,
     GaussianCloud,
     GaussianCloudHandle,
-    query::select::Select,
+    query::{
+        bvh::{
+            GaussianBvh,
+            GaussianBvhs,
+        },
+        select::Select,
+    },
 };
 
-assert_cfg!(
-    all(
-        feature = "query_sparse",
-        not(feature = "precompute_covariance_3d"),
-    ),
-    "sparse queries and precomputed covariance are not implemented",
-);
-
 
 #[derive(Component, Debug, Reflect)]
 pub struct SparseSelect {
@@ -46,17 +37,16 @@ impl SparseSelect {
     pub fn select(
         &self,
         cloud: &GaussianCloud,
+        bvh: &GaussianBvh,
     ) -> Select {
-        let tree = KdTree::build_by_ordered_float(cloud.gaussian_iter().collect());
-
-        cloud.gaussian_iter()
+        cloud.position_iter()
             .enumerate()
-            .filter(|(_idx, gaussian)| {
-                let neighbors = tree.within_radius(gaussian, self.radius);
+            .filter(|(_idx, position)| {
+                let neighbors = bvh.count_within_radius(cloud, Vec3A::from(**position), self.radius);
 
-                neighbors.len() < self.neighbor_threshold
+                neighbors < self.neighbor_threshold
             })
-            .map(|(idx, _gaussian)| idx)
+            .map(|(idx, _position)| idx)
             .collect::<Select>()
     }
 }
@@ -74,20 +64,11 @@ impl Plugin for SparsePlugin {
 }
 
 
-impl KdPoint for Gaussian {
-    type Scalar = f32;
-    type Dim = U3;
-
-    fn at(&self, i: usize) -> Self::Scalar {
-        self.position_visibility.position[i]
-    }
-}
-
-
 fn select_sparse_handler(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     gaussian_clouds_res: Res<Assets<GaussianCloud>>,
+    bvhs: Res<GaussianBvhs>,
     mut selections: Query<(
         Entity,
         &GaussianCloudHandle,
@@ -99,31 +80,26 @@ fn select_sparse_handler(
         cloud_handle,
         mut select,
     ) in selections.iter_mut() {
-        if Some(LoadState::Loading) == asset_server.get_load_state(cloud_handle) {
-            continue;
-        }
-
-        if Some(LoadState::Loading) == asset_server.get_load_state(cloud_handle) {
-            continue;
+        if let Some(load_state) = asset_server.get_load_state(&cloud_handle.0) {
+            if load_state.is_loading() {
+                continue;
+            }
         }
 
         if select.completed {
             continue;
         }
-        select.completed = true;
 
-        let cloud = gaussian_clouds_res.get(cloud_handle).unwrap();
-        let tree = KdTree::build_by_ordered_float(cloud.gaussian_iter().collect());
+        let (Some(cloud), Some(bvh)) = (
+            gaussian_clouds_res.get(cloud_handle),
+            bvhs.get(cloud_handle),
+        ) else {
+            continue;
+        };
 
-        let new_selection = cloud.gaussian_iter()
-            .enumerate()
-            .filter(|(_idx, gaussian)| {
-                let neighbors = tree.within_radius(gaussian, select.radius);
+        select.completed = true;
 
-                neighbors.len() < select.neighbor_threshold
-            })
-            .map(|(idx, _gaussian)| idx)
-            .collect::<Select>();
+        let new_selection = select.select(cloud, bvh);
 
         commands.entity(entity)
             .remove::<Select>()
@@ -0,0 +1,131 @@
+use bevy::{
+    prelude::*,
+    log::warn_once,
+    render::{
+        extract_component::{
+            ExtractComponent,
+            ExtractComponentPlugin,
+        },
+        render_asset::{
+            RenderAssets,
+            RenderAssetUsages,
+        },
+        render_resource::*,
+        texture::GpuImage,
+    },
+};
+
+use crate::render::tile::{
+    TileRasterizeTextures,
+    TILE_ALPHA_FORMAT,
+    TILE_COLOR_FORMAT,
+    TILE_DEPTH_FORMAT,
+    TILE_GAUSSIAN_ID_FORMAT,
+    TILE_NORMAL_FORMAT,
+};
+
+
+#[derive(Default)]
+pub struct GaussianAovPlugin;
+
+impl Plugin for GaussianAovPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GaussianAovTargets>();
+        app.add_plugins(ExtractComponentPlugin::<GaussianAovTargets>::default());
+    }
+}
+
+
+/// per-pixel outputs of `GaussianCloudBackend::Tile` clouds, copied into user images every frame
+///
+/// images must be viewport sized, use the matching `TILE_*_FORMAT` and include `TextureUsages::COPY_DST`,
+/// see `aov_image`. outputs are not accumulated across clouds, with several tile clouds in view they
+/// hold the nearest cloud only (with a warning), merge clouds with `GlobalSort` to get a single set of outputs.
+#[derive(
+    Component,
+    ExtractComponent,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianAovTargets {
+    /// premultiplied color with accumulated alpha, `TILE_COLOR_FORMAT`
+    pub color: Option<Handle<Image>>,
+    /// expected view-space depth, `TILE_DEPTH_FORMAT`
+    pub depth: Option<Handle<Image>>,
+    /// expected world-space normal, `TILE_NORMAL_FORMAT`
+    pub normal: Option<Handle<Image>>,
+    /// accumulated alpha (1 - transmittance), `TILE_ALPHA_FORMAT`
+    pub alpha: Option<Handle<Image>>,
+    /// index of the top contributing gaussian in `x` and the main world entity index of its cloud in `y`,
+    /// `u32::MAX` where none contributes, `TILE_GAUSSIAN_ID_FORMAT`
+    pub gaussian_id: Option<Handle<Image>>,
+}
+
+/// creates an image usable as a `GaussianAovTargets` output and as a copy source for readback
+pub fn aov_image(size: UVec2, format: TextureFormat) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &vec![0; format.block_copy_size(None).unwrap_or(4) as usize],
+        format,
+        RenderAssetUsages::default(),
+    );
+
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC;
+
+    image
+}
+
+pub fn copy_aov_targets(
+    command_encoder: &mut CommandEncoder,
+    images: &RenderAssets<GpuImage>,
+    textures: &TileRasterizeTextures,
+    targets: &GaussianAovTargets,
+) {
+    let mut outputs = vec![
+        (&targets.color, &textures.color.texture, TILE_COLOR_FORMAT),
+        (&targets.depth, &textures.depth.texture, TILE_DEPTH_FORMAT),
+    ];
+
+    if let Some(aov) = textures.aov.as_ref() {
+        outputs.extend([
+            (&targets.normal, &aov.normal.texture, TILE_NORMAL_FORMAT),
+            (&targets.alpha, &aov.alpha.texture, TILE_ALPHA_FORMAT),
+            (&targets.gaussian_id, &aov.gaussian_id.texture, TILE_GAUSSIAN_ID_FORMAT),
+        ]);
+    }
+
+    for (target, source, format) in outputs {
+        let Some(image) = target.as_ref().and_then(|handle| images.get(handle)) else {
+            continue;
+        };
+
+        if image.texture_format != format {
+            warn_once!("gaussian aov target format {:?} does not match {:?}", image.texture_format, format);
+            continue;
+        }
+
+        let source_size = source.size();
+        let size = Extent3d {
+            width: source_size.width.min(image.size.x),
+            height: source_size.height.min(image.size.y),
+            depth_or_array_layers: 1,
+        };
+
+        command_encoder.copy_texture_to_texture(
+            source.as_image_copy(),
+            image.texture.as_image_copy(),
+            size,
+        );
+    }
+}
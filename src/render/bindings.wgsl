@@ -7,15 +7,63 @@
 @group(0) @binding(0) var<uniform> view: View;
 @group(0) @binding(1) var<uniform> globals: Globals;
 
+struct VolumeMask {
+    mask_from_world: mat4x4<f32>,
+    params: vec4<f32>,
+    voxel_max: vec4<f32>,
+    flags: vec4<u32>,
+};
+
+struct Reveal {
+    origin: vec3<f32>,
+    front: f32,
+    direction: vec3<f32>,
+    feather: f32,
+    mode: u32,
+};
+
+struct Noise {
+    seed_offset: vec3<f32>,
+    frequency: f32,
+    octaves: u32,
+    lacunarity: f32,
+    persistence: f32,
+    speed: f32,
+    color_strength: f32,
+    position_strength: f32,
+    mode: u32,
+};
+
+struct ColorGrading {
+    balance: mat3x3<f32>,
+    exposure: f32,
+    contrast: f32,
+    saturation: f32,
+    enabled: u32,
+};
+
 struct GaussianUniforms {
     transform: mat4x4<f32>,
     global_opacity: f32,
     global_scale: f32,
     count: u32,
     count_root_ceil: u32,
+    depth_alpha_threshold: f32,
+    entity_index: u32,
+    volume_mask_count: u32,
+    volume_masks: array<VolumeMask, #{MAX_VOLUME_MASKS}>,
+    reveal: Reveal,
+    relight: f32,
+    noise: Noise,
+    color_grading: ColorGrading,
+    max_sh_band: u32,
 };
 @group(1) @binding(0) var<uniform> gaussian_uniforms: GaussianUniforms;
 
+#ifdef BUFFER_STORAGE
+@group(1) @binding(1) var<storage, read> volume_mask_voxels: array<u32>;
+#endif
+
 
 #ifdef PACKED_F32
 struct Gaussian {
@@ -67,6 +115,15 @@ struct Gaussian {
 #endif
 
 
+#ifdef PBR_MATERIAL
+@group(2) @binding(4) var<storage, read> pbr_materials: array<vec2<u32>>;
+#endif
+
+#ifdef MIP_FILTER_3D
+@group(2) @binding(5) var<storage, read> filter_3d: array<f32>;
+#endif
+
+
 #ifdef PLANAR_TEXTURE_F16
 @group(2) @binding(0) var position_visibility: texture_2d<f32>;
 
@@ -0,0 +1,87 @@
+use bevy::{
+    prelude::*,
+    asset::load_internal_asset,
+    math::{
+        mat3,
+        vec2,
+        vec3,
+    },
+    render::render_resource::ShaderType,
+};
+
+use crate::gaussian::settings::GaussianColorGrading;
+
+
+const COLOR_GRADING_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(296120458);
+
+/// converts linear rgb to the LMS cone response space, matching `bevy_render::view`
+const RGB_TO_LMS: Mat3 = mat3(
+    vec3(0.311692, 0.0905138, 0.00764433),
+    vec3(0.652085, 0.901341, 0.0486554),
+    vec3(0.0362225, 0.00814478, 0.943700),
+);
+
+const LMS_TO_RGB: Mat3 = mat3(
+    vec3(4.06305, -0.40791, -0.0118812),
+    vec3(-2.93241, 1.40437, -0.0486532),
+    vec3(-0.130646, 0.00353630, 1.0605344),
+);
+
+/// CIE 1931 xy chromaticity of the D65 white point
+const D65_XY: Vec2 = vec2(0.31272, 0.32903);
+const D65_LMS: Vec3 = vec3(0.975538, 1.01648, 1.08475);
+
+
+#[derive(Default)]
+pub struct ColorGradingPlugin;
+
+impl Plugin for ColorGradingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            COLOR_GRADING_SHADER_HANDLE,
+            "color_grading.wgsl",
+            Shader::from_wgsl
+        );
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, ShaderType)]
+pub struct GaussianColorGradingUniform {
+    /// white balance in linear rgb
+    pub balance: Mat3,
+    /// linear multiplier, `2^exposure`
+    pub exposure: f32,
+    pub contrast: f32,
+    pub saturation: f32,
+    /// 0 skips grading entirely
+    pub enabled: u32,
+}
+
+impl Default for GaussianColorGradingUniform {
+    fn default() -> Self {
+        (&GaussianColorGrading::default()).into()
+    }
+}
+
+impl From<&GaussianColorGrading> for GaussianColorGradingUniform {
+    fn from(grading: &GaussianColorGrading) -> Self {
+        // shift the white point in xy, then scale the cone responses towards D65 (von kries)
+        let white_point_xy = D65_XY + vec2(-grading.temperature, grading.tint);
+        let white_point_lms = vec3(0.701634, 1.15856, -0.904175)
+            + (vec3(-0.051461, 0.045854, 0.953127)
+                + vec3(0.452749, -0.296122, -0.955206) * white_point_xy.x)
+                / white_point_xy.y;
+
+        let balance = LMS_TO_RGB * Mat3::from_diagonal(D65_LMS / white_point_lms) * RGB_TO_LMS;
+
+        Self {
+            balance,
+            exposure: grading.exposure.exp2(),
+            contrast: grading.contrast.max(0.0),
+            saturation: grading.saturation.max(0.0),
+            enabled: (!grading.is_identity()) as u32,
+        }
+    }
+}
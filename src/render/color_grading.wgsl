@@ -0,0 +1,23 @@
+#define_import_path bevy_gaussian_splatting::color_grading
+
+#import bevy_gaussian_splatting::bindings::gaussian_uniforms
+
+
+const MIDDLE_GREY: f32 = 0.18;
+const LUMINANCE_WEIGHTS: vec3<f32> = vec3<f32>(0.2126, 0.7152, 0.0722);
+
+
+// grades a linear color by the cloud exposure, white balance, contrast, and saturation
+fn color_grade(color: vec3<f32>) -> vec3<f32> {
+    let grading = gaussian_uniforms.color_grading;
+
+    if (grading.enabled == 0u) {
+        return color;
+    }
+
+    var graded = grading.balance * (color * grading.exposure);
+    graded = MIDDLE_GREY * pow(max(graded, vec3<f32>(0.0)) / MIDDLE_GREY, vec3<f32>(grading.contrast));
+
+    let luminance = dot(graded, LUMINANCE_WEIGHTS);
+    return max(mix(vec3<f32>(luminance), graded, grading.saturation), vec3<f32>(0.0));
+}
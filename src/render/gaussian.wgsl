@@ -8,18 +8,46 @@
     output_entries,
     Entry,
 }
+#import bevy_gaussian_splatting::color_grading::color_grade
 #import bevy_gaussian_splatting::depth::{
     depth_to_rgb,
 }
+#import bevy_gaussian_splatting::gaussian_3d::compute_cov2d_3dgs
 #import bevy_gaussian_splatting::helpers::{
     get_rotation_matrix,
     get_scale_matrix,
 }
+#import bevy_gaussian_splatting::reveal::reveal_opacity
+#import bevy_gaussian_splatting::volume_mask::{
+    volume_mask_hidden,
+    volume_mask_selected,
+}
 #import bevy_gaussian_splatting::transform::{
     world_to_clip,
     in_frustum,
 }
 
+#ifdef GAUSSIAN_MATERIAL
+#import bevy_gaussian_splatting::gaussian_material::{
+    GaussianMaterialFragment,
+    GaussianMaterialVertex,
+}
+#endif
+
+#ifdef NOISE_MATERIAL
+#import bevy_gaussian_splatting::noise_material::{
+    noise_material_color,
+    noise_material_position,
+}
+#endif
+
+#ifdef LIGHTING
+#import bevy_gaussian_splatting::pbr::{
+    gaussian_normal,
+    gaussian_relight,
+}
+#endif
+
 #ifdef GAUSSIAN_SURFEL
 #import bevy_gaussian_splatting::surfel::{
     compute_cov2d_surfel,
@@ -137,6 +165,9 @@ struct GaussianVertexOutput {
     @location(5) mean_2d: vec2<f32>,
     @location(6) radius: vec2<f32>,
 #endif
+#ifdef GAUSSIAN_PICK
+    @location(7) @interpolate(flat) gaussian_index: u32,
+#endif
 };
 #else
 struct GaussianVertexOutput {
@@ -153,86 +184,12 @@ struct GaussianVertexOutput {
     @location(5) @interpolate(flat) mean_2d: vec2<f32>,
     @location(6) @interpolate(flat) radius: vec2<f32>,
 #endif
-};
+#ifdef GAUSSIAN_PICK
+    @location(7) @interpolate(flat) gaussian_index: u32,
 #endif
-
-
-// https://github.com/cvlab-epfl/gaussian-splatting-web/blob/905b3c0fb8961e42c79ef97e64609e82383ca1c2/src/shaders.ts#L185
-// TODO: precompute
-fn compute_cov3d(scale: vec3<f32>, rotation: vec4<f32>) -> array<f32, 6> {
-    let S = get_scale_matrix(scale);
-
-    let T = mat3x3<f32>(
-        gaussian_uniforms.transform[0].xyz,
-        gaussian_uniforms.transform[1].xyz,
-        gaussian_uniforms.transform[2].xyz,
-    );
-
-    let R = get_rotation_matrix(rotation);
-
-    let M = S * R;
-    let Sigma = transpose(M) * M;
-    let TS = T * Sigma * transpose(T);
-
-    return array<f32, 6>(
-        TS[0][0],
-        TS[0][1],
-        TS[0][2],
-        TS[1][1],
-        TS[1][2],
-        TS[2][2],
-    );
-}
-
-fn compute_cov2d_3dgs(
-    position: vec3<f32>,
-    index: u32,
-) -> vec3<f32> {
-#ifdef PRECOMPUTE_COVARIANCE_3D
-    let cov3d = get_cov3d(index);
-#else
-    let rotation = get_rotation(index);
-    let scale = get_scale(index);
-
-    let cov3d = compute_cov3d(scale, rotation);
+};
 #endif
 
-    let Vrk = mat3x3(
-        cov3d[0], cov3d[1], cov3d[2],
-        cov3d[1], cov3d[3], cov3d[4],
-        cov3d[2], cov3d[4], cov3d[5],
-    );
-
-    var t = view.view_from_world * vec4<f32>(position, 1.0);
-
-    let focal = vec2<f32>(
-        view.clip_from_view.x.x * view.viewport.z,
-        view.clip_from_view.y.y * view.viewport.w,
-    );
-
-    let s = 1.0 / (t.z * t.z);
-    let J = mat3x3(
-        focal.x / t.z, 0.0, -(focal.x * t.x) * s,
-        0.0, -focal.y / t.z, (focal.y * t.y) * s,
-        0.0, 0.0, 0.0,
-    );
-
-    let W = transpose(
-        mat3x3<f32>(
-            view.view_from_world.x.xyz,
-            view.view_from_world.y.xyz,
-            view.view_from_world.z.xyz,
-        )
-    );
-
-    let T = W * J;
-
-    var cov = transpose(T) * transpose(Vrk) * T;
-    cov[0][0] += 0.3f;
-    cov[1][1] += 0.3f;
-
-    return vec3<f32>(cov[0][0], cov[0][1], cov[1][1]);
-}
 
 fn get_bounding_box(
     cov2d: vec3<f32>,
@@ -343,15 +300,31 @@ fn vs_points(
 
     discard_quad |= entry.key == 0xFFFFFFFFu; // || splat_index == 0u;
 
-    let position = vec4<f32>(get_position(splat_index), 1.0);
+#ifdef NOISE_MATERIAL
+    var local_position = noise_material_position(get_position(splat_index));
+#else
+    var local_position = get_position(splat_index);
+#endif
+
+    // `material_vertex` is imported by the entry shader generated for each `GaussianMaterial`
+#ifdef GAUSSIAN_MATERIAL_VERTEX
+    local_position = material_vertex(GaussianMaterialVertex(splat_index, local_position)).position;
+#endif
+
+    let position = vec4<f32>(local_position, 1.0);
 
     let transformed_position = (gaussian_uniforms.transform * position).xyz;
     let projected_position = world_to_clip(transformed_position);
 
     discard_quad |= !in_frustum(projected_position.xyz);
 
+    discard_quad |= volume_mask_hidden(transformed_position);
+
+    let reveal = reveal_opacity(position.xyz);
+    discard_quad |= reveal <= 0.0;
+
 #ifdef DRAW_SELECTED
-    discard_quad |= get_visibility(splat_index) < 0.5;
+    discard_quad |= !volume_mask_selected(transformed_position, get_visibility(splat_index) > 0.5);
 #endif
 
     if (discard_quad) {
@@ -415,6 +388,27 @@ fn vs_points(
     );
 #else
     rgb = get_color(splat_index, ray_direction);
+
+#ifdef NOISE_MATERIAL
+    rgb = noise_material_color(rgb, position.xyz);
+#endif
+
+#ifdef LIGHTING
+#ifdef PRECOMPUTE_COVARIANCE_3D
+    // rotation and scale are folded into the covariance, shade the splat as if it faced the camera
+    let normal = -ray_direction;
+#else
+    let normal = gaussian_normal(
+        get_rotation(splat_index),
+        get_scale(splat_index),
+        ray_direction,
+    );
+#endif
+
+    rgb = gaussian_relight(rgb, splat_index, transformed_position, normal, ray_direction);
+#endif
+
+    rgb = color_grade(rgb);
 #endif
 
     let opacity = get_opacity(splat_index);
@@ -428,20 +422,23 @@ fn vs_points(
     // TODO: verify color benefit for ray_direction computed at quad verticies instead of gaussian center (same as current complexity)
     output.color = vec4<f32>(
         rgb,
-        opacity * gaussian_uniforms.global_opacity,
+        opacity * gaussian_uniforms.global_opacity * reveal,
     );
 
 #ifdef HIGHLIGHT_SELECTED
-    if (get_visibility(splat_index) > 0.5) {
+    if (volume_mask_selected(transformed_position, get_visibility(splat_index) > 0.5)) {
         output.color = vec4<f32>(0.3, 1.0, 0.1, 1.0);
     }
 #endif
 
 #ifdef GAUSSIAN_3D
-    let cov2d = compute_cov2d_3dgs(
+    let filtered_cov2d = compute_cov2d_3dgs(
         transformed_position,
         splat_index,
     );
+    let cov2d = filtered_cov2d.xyz;
+    output.color.a *= filtered_cov2d.w;
+
     let bb = get_bounding_box(
         cov2d,
         quad_offset,
@@ -481,6 +478,10 @@ fn vs_points(
     output.radius = bb.zw;
 #endif
 
+#ifdef GAUSSIAN_PICK
+    output.gaussian_index = splat_index;
+#endif
+
     output.uv = quad_offset;
     output.position = vec4<f32>(
         projected_position.xy + bb.xy,
@@ -490,8 +491,7 @@ fn vs_points(
     return output;
 }
 
-@fragment
-fn fs_main(input: GaussianVertexOutput) -> @location(0) vec4<f32> {
+fn gaussian_fragment(input: GaussianVertexOutput) -> vec4<f32> {
 #ifdef USE_AABB
 #ifdef GAUSSIAN_SURFEL
     let radius = input.radius;
@@ -546,12 +546,61 @@ fn fs_main(input: GaussianVertexOutput) -> @location(0) vec4<f32> {
     }
 #endif
 
+#ifdef GAUSSIAN_MATERIAL_FRAGMENT
+    let color = material_fragment(GaussianMaterialFragment(
+        input.position,
+        vec4<f32>(input.color.rgb, exp(power) * input.color.a),
+        input.uv,
+    ));
+
+    let alpha = min(color.a, 0.999);
+    let rgb = color.rgb;
+#else
     let alpha = min(exp(power) * input.color.a, 0.999);
+    let rgb = input.color.rgb;
+#endif
 
     // TODO: round alpha to terminate depth test?
 
     return vec4<f32>(
-        input.color.rgb * alpha,
+        rgb * alpha,
         alpha,
     );
 }
+
+@fragment
+fn fs_main(input: GaussianVertexOutput) -> @location(0) vec4<f32> {
+    return gaussian_fragment(input);
+}
+
+// depth only entry, the quad is placed at the gaussian center depth
+@fragment
+fn fs_depth(input: GaussianVertexOutput) {
+    let color = gaussian_fragment(input);
+
+    if (color.a < gaussian_uniforms.depth_alpha_threshold) {
+        discard;
+    }
+}
+
+#ifdef GAUSSIAN_PICK
+struct PickOutput {
+    @location(0) gaussian_index: u32,
+    @location(1) entity_index: u32,
+};
+
+// draws are sorted back to front, the nearest sufficiently opaque fragment is kept
+@fragment
+fn fs_pick(input: GaussianVertexOutput) -> PickOutput {
+    let color = gaussian_fragment(input);
+
+    if (color.a < gaussian_uniforms.depth_alpha_threshold) {
+        discard;
+    }
+
+    var output: PickOutput;
+    output.gaussian_index = input.gaussian_index;
+    output.entity_index = gaussian_uniforms.entity_index;
+    return output;
+}
+#endif
@@ -0,0 +1,174 @@
+#define_import_path bevy_gaussian_splatting::gaussian_3d
+
+#import bevy_gaussian_splatting::bindings::{
+    view,
+    gaussian_uniforms,
+}
+#import bevy_gaussian_splatting::helpers::{
+    get_rotation_matrix,
+    get_scale_matrix,
+}
+
+#ifdef PACKED
+#ifdef PRECOMPUTE_COVARIANCE_3D
+#import bevy_gaussian_splatting::packed::get_cov3d
+#else
+#import bevy_gaussian_splatting::packed::{
+    get_rotation,
+    get_scale,
+}
+#endif
+#else
+
+#ifdef BUFFER_STORAGE
+#ifdef PRECOMPUTE_COVARIANCE_3D
+#import bevy_gaussian_splatting::planar::get_cov3d
+#else
+#import bevy_gaussian_splatting::planar::{
+    get_rotation,
+    get_scale,
+}
+#endif
+
+#ifdef MIP_FILTER_3D
+#import bevy_gaussian_splatting::planar::get_filter_3d
+#endif
+#endif
+
+#endif
+
+
+#ifdef BUFFER_TEXTURE
+#ifdef PRECOMPUTE_COVARIANCE_3D
+#import bevy_gaussian_splatting::texture::get_cov3d
+#else
+#import bevy_gaussian_splatting::texture::{
+    get_rotation,
+    get_scale,
+}
+#endif
+#endif
+
+
+// mip-splatting screen space low-pass, in squared pixels
+const MIP_FILTER_2D_VARIANCE: f32 = 0.1;
+
+// dilation of the projected covariance when the mip filter is disabled
+const DILATION_VARIANCE: f32 = 0.3;
+
+
+// https://github.com/cvlab-epfl/gaussian-splatting-web/blob/905b3c0fb8961e42c79ef97e64609e82383ca1c2/src/shaders.ts#L185
+// TODO: precompute
+fn compute_cov3d(scale: vec3<f32>, rotation: vec4<f32>) -> array<f32, 6> {
+    let S = get_scale_matrix(scale);
+
+    let T = mat3x3<f32>(
+        gaussian_uniforms.transform[0].xyz,
+        gaussian_uniforms.transform[1].xyz,
+        gaussian_uniforms.transform[2].xyz,
+    );
+
+    let R = get_rotation_matrix(rotation);
+
+    let M = S * R;
+    let Sigma = transpose(M) * M;
+    let TS = T * Sigma * transpose(T);
+
+    return array<f32, 6>(
+        TS[0][0],
+        TS[0][1],
+        TS[0][2],
+        TS[1][1],
+        TS[1][2],
+        TS[2][2],
+    );
+}
+
+fn cov3d_determinant(cov3d: array<f32, 6>) -> f32 {
+    return cov3d[0] * (cov3d[3] * cov3d[5] - cov3d[4] * cov3d[4])
+        - cov3d[1] * (cov3d[1] * cov3d[5] - cov3d[4] * cov3d[2])
+        + cov3d[2] * (cov3d[1] * cov3d[4] - cov3d[3] * cov3d[2]);
+}
+
+// projected covariance in `xyz`, `w` is the opacity multiplier compensating the mip filters
+fn compute_cov2d_3dgs(
+    position: vec3<f32>,
+    index: u32,
+) -> vec4<f32> {
+    var opacity_scale = 1.0;
+
+#ifdef PRECOMPUTE_COVARIANCE_3D
+    var cov3d = get_cov3d(index);
+#else
+    var cov3d = compute_cov3d(get_scale(index), get_rotation(index));
+#endif
+
+#ifdef MIP_FILTER_3D
+    // the filter is a world space standard deviation, added after the cloud transform and global scale
+    let filter_variance = get_filter_3d(index) * get_filter_3d(index);
+    let unfiltered_determinant_3d = cov3d_determinant(cov3d);
+
+    cov3d[0] += filter_variance;
+    cov3d[3] += filter_variance;
+    cov3d[5] += filter_variance;
+
+    opacity_scale = sqrt(max(unfiltered_determinant_3d / cov3d_determinant(cov3d), 0.0));
+#endif
+
+    let Vrk = mat3x3(
+        cov3d[0], cov3d[1], cov3d[2],
+        cov3d[1], cov3d[3], cov3d[4],
+        cov3d[2], cov3d[4], cov3d[5],
+    );
+
+    var t = view.view_from_world * vec4<f32>(position, 1.0);
+
+    let focal = vec2<f32>(
+        view.clip_from_view.x.x * view.viewport.z,
+        view.clip_from_view.y.y * view.viewport.w,
+    );
+
+    let s = 1.0 / (t.z * t.z);
+    var J = mat3x3(
+        focal.x / t.z, 0.0, -(focal.x * t.x) * s,
+        0.0, -focal.y / t.z, (focal.y * t.y) * s,
+        0.0, 0.0, 0.0,
+    );
+
+    // orthographic views (e.g. directional light shadow cascades) have no perspective divide
+    if (view.clip_from_view[3][3] == 1.0) {
+        J = mat3x3(
+            -focal.x, 0.0, 0.0,
+            0.0, focal.y, 0.0,
+            0.0, 0.0, 0.0,
+        );
+    }
+
+    let W = transpose(
+        mat3x3<f32>(
+            view.view_from_world.x.xyz,
+            view.view_from_world.y.xyz,
+            view.view_from_world.z.xyz,
+        )
+    );
+
+    let T = W * J;
+
+    var cov = transpose(T) * transpose(Vrk) * T;
+
+    // focal is measured over the full ndc range, the covariance is in squared half pixels
+#ifdef MIP_FILTER
+    let unfiltered_determinant = cov[0][0] * cov[1][1] - cov[0][1] * cov[0][1];
+
+    cov[0][0] += 4.0 * MIP_FILTER_2D_VARIANCE;
+    cov[1][1] += 4.0 * MIP_FILTER_2D_VARIANCE;
+
+    let filtered_determinant = cov[0][0] * cov[1][1] - cov[0][1] * cov[0][1];
+    opacity_scale *= sqrt(max(unfiltered_determinant / filtered_determinant, 0.0));
+#else
+    cov[0][0] += DILATION_VARIANCE;
+    cov[1][1] += DILATION_VARIANCE;
+#endif
+
+    return vec4<f32>(cov[0][0], cov[0][1], cov[1][1], opacity_scale);
+}
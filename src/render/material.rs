@@ -0,0 +1,503 @@
+use std::{
+    fmt::Write,
+    marker::PhantomData,
+};
+
+use bevy::{
+    prelude::*,
+    asset::AssetPath,
+    core_pipeline::core_3d::Transparent3d,
+    ecs::{
+        query::{
+            QueryItem,
+            ROQueryItem,
+        },
+        system::{
+            lifetimeless::*,
+            SystemParamItem,
+        },
+    },
+    render::{
+        extract_component::{
+            ExtractComponent,
+            ExtractComponentPlugin,
+        },
+        render_asset::{
+            PrepareAssetError,
+            RenderAsset,
+            RenderAssetPlugin,
+            RenderAssets,
+        },
+        render_phase::{
+            AddRenderCommand,
+            DrawFunctions,
+            PhaseItem,
+            PhaseItemExtraIndex,
+            RenderCommand,
+            RenderCommandResult,
+            SetItemPipeline,
+            TrackedRenderPass,
+            ViewSortedRenderPhases,
+        },
+        render_resource::*,
+        renderer::RenderDevice,
+        view::{
+            ExtractedView,
+            RenderVisibleEntities,
+        },
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+};
+
+use crate::{
+    camera::GaussianCamera,
+    gaussian::{
+        cloud::GaussianCloudHandle,
+        settings::{
+            GaussianCloudAppearance,
+            GaussianCloudBackend,
+            GaussianCloudDebugSettings,
+            GaussianCloudRasterSettings,
+        },
+    },
+    lighting::GaussianCloudLighting,
+    render::{
+        DrawGaussianInstanced,
+        GaussianCloudCentroid,
+        GaussianCloudPass,
+        GaussianCloudPipeline,
+        GaussianCloudPipelineKey,
+        GpuGaussianCloud,
+        SetGaussianUniformBindGroup,
+        SetGaussianViewBindGroup,
+    },
+    sort::{
+        GlobalSortMember,
+        GpuSortedEntry,
+        SortedEntriesHandle,
+    },
+};
+
+
+/// bind group of `GaussianMaterial` bindings, following the view, uniform, cloud and sorted entry groups
+pub const GAUSSIAN_MATERIAL_BIND_GROUP: usize = 4;
+
+const GAUSSIAN_SHADER_SOURCE: &str = include_str!("gaussian.wgsl");
+
+
+/// user shading hooks for gaussian clouds, analogous to bevy's `Material`
+///
+/// hook modules are imported by asset path, `vertex_shader` must define
+/// `fn material_vertex(vertex: GaussianMaterialVertex) -> GaussianMaterialVertex` and `fragment_shader`
+/// `fn material_fragment(fragment: GaussianMaterialFragment) -> vec4<f32>`, both types are imported from
+/// `bevy_gaussian_splatting::gaussian_material`. material bindings live in `@group(4)`
+/// (`GAUSSIAN_MATERIAL_BIND_GROUP`), which needs a device supporting five bind groups. on devices
+/// limited to four, such as webgpu, the plugin logs an error and clouds using `M` are not drawn.
+///
+/// hooks apply to the color pass of the quad rasterizer, prepass, shadow and pick passes draw the unmodified cloud.
+pub trait GaussianMaterial: Asset + AsBindGroup + Clone + Sized {
+    fn vertex_shader() -> ShaderRef {
+        ShaderRef::Default
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        ShaderRef::Default
+    }
+}
+
+
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    Deref,
+    DerefMut,
+    Reflect,
+)]
+#[reflect(Component)]
+pub struct GaussianMaterialHandle<M: GaussianMaterial>(pub Handle<M>);
+
+impl<M: GaussianMaterial> Default for GaussianMaterialHandle<M> {
+    fn default() -> Self {
+        Self(Handle::default())
+    }
+}
+
+impl<M: GaussianMaterial> From<Handle<M>> for GaussianMaterialHandle<M> {
+    fn from(handle: Handle<M>) -> Self {
+        Self(handle)
+    }
+}
+
+impl<M: GaussianMaterial> ExtractComponent for GaussianMaterialHandle<M> {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = (Self, GaussianMaterialMarker);
+
+    fn extract_component(handle: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        (handle.clone(), GaussianMaterialMarker).into()
+    }
+}
+
+/// render world clouds drawn through a `GaussianMaterialPipeline` instead of the default pipeline
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct GaussianMaterialMarker;
+
+
+pub struct GaussianMaterialPlugin<M: GaussianMaterial>(PhantomData<M>);
+
+impl<M: GaussianMaterial> Default for GaussianMaterialPlugin<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M: GaussianMaterial> Plugin for GaussianMaterialPlugin<M> {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<M>();
+        app.register_type::<GaussianMaterialHandle<M>>();
+
+        app.add_plugins((
+            ExtractComponentPlugin::<GaussianMaterialHandle<M>>::default(),
+            RenderAssetPlugin::<PreparedGaussianMaterial<M>>::default(),
+        ));
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .add_render_command::<Transparent3d, DrawGaussianMaterial<M>>()
+                .add_systems(
+                    Render,
+                    queue_gaussian_materials::<M>
+                        .run_if(resource_exists::<GaussianMaterialPipeline<M>>)
+                        .in_set(RenderSet::Queue),
+                );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app(RenderApp) {
+            let max_bind_groups = render_app.world().resource::<RenderDevice>().limits().max_bind_groups;
+
+            if max_bind_groups <= GAUSSIAN_MATERIAL_BIND_GROUP as u32 {
+                error!(
+                    "{} needs bind group {}, the device supports {} bind groups, the material is disabled",
+                    M::short_type_path(),
+                    GAUSSIAN_MATERIAL_BIND_GROUP,
+                    max_bind_groups,
+                );
+                return;
+            }
+        }
+
+        let shaders = GaussianMaterialShaders::<M>::new(app.world_mut());
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            let pipeline = GaussianMaterialPipeline::<M>::new(render_app.world(), &shaders);
+
+            render_app
+                .insert_resource(pipeline)
+                .init_resource::<SpecializedRenderPipelines<GaussianMaterialPipeline<M>>>();
+        }
+
+        app.insert_resource(shaders);
+    }
+}
+
+
+/// entry shader generated for `M` and the hook modules it imports, kept alive in the main world
+#[derive(Resource)]
+pub struct GaussianMaterialShaders<M: GaussianMaterial> {
+    pub entry: Handle<Shader>,
+    pub vertex: Option<Handle<Shader>>,
+    pub fragment: Option<Handle<Shader>>,
+    marker: PhantomData<M>,
+}
+
+impl<M: GaussianMaterial> GaussianMaterialShaders<M> {
+    fn new(world: &mut World) -> Self {
+        let vertex = hook_path::<M>(M::vertex_shader());
+        let fragment = hook_path::<M>(M::fragment_shader());
+
+        // hook functions are imported by name ahead of the gaussian shader source
+        let mut source = String::new();
+        for (path, hook) in [(&vertex, "material_vertex"), (&fragment, "material_fragment")] {
+            if let Some(path) = path {
+                writeln!(source, "#import \"{}\"::{}", path, hook).unwrap();
+            }
+        }
+        source.push_str(GAUSSIAN_SHADER_SOURCE);
+
+        let asset_server = world.resource::<AssetServer>();
+        let vertex = vertex.map(|path| asset_server.load(path));
+        let fragment = fragment.map(|path| asset_server.load(path));
+
+        let entry = world.resource_mut::<Assets<Shader>>().add(Shader::from_wgsl(
+            source,
+            format!("bevy_gaussian_splatting/gaussian_material/{}.wgsl", M::short_type_path()),
+        ));
+
+        Self {
+            entry,
+            vertex,
+            fragment,
+            marker: PhantomData,
+        }
+    }
+}
+
+fn hook_path<M: GaussianMaterial>(shader: ShaderRef) -> Option<AssetPath<'static>> {
+    match shader {
+        ShaderRef::Default => None,
+        ShaderRef::Path(path) => Some(path),
+        ShaderRef::Handle(handle) => {
+            warn!(
+                "{} hook {:?} is ignored, gaussian material hooks are imported by asset path",
+                M::short_type_path(),
+                handle,
+            );
+
+            None
+        },
+    }
+}
+
+
+#[derive(Resource)]
+pub struct GaussianMaterialPipeline<M: GaussianMaterial> {
+    pub gaussian_cloud_pipeline: GaussianCloudPipeline,
+    pub material_layout: BindGroupLayout,
+    pub shader: Handle<Shader>,
+    pub vertex_hook: bool,
+    pub fragment_hook: bool,
+    marker: PhantomData<M>,
+}
+
+impl<M: GaussianMaterial> GaussianMaterialPipeline<M> {
+    fn new(render_world: &World, shaders: &GaussianMaterialShaders<M>) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+        let material_layout = M::bind_group_layout(render_device);
+
+        Self {
+            gaussian_cloud_pipeline: render_world.resource::<GaussianCloudPipeline>().clone(),
+            material_layout,
+            shader: shaders.entry.clone(),
+            vertex_hook: shaders.vertex.is_some(),
+            fragment_hook: shaders.fragment.is_some(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<M: GaussianMaterial> SpecializedRenderPipeline for GaussianMaterialPipeline<M> {
+    type Key = GaussianCloudPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut descriptor = self.gaussian_cloud_pipeline.specialize(key);
+
+        let mut shader_defs: Vec<ShaderDefVal> = vec!["GAUSSIAN_MATERIAL".into()];
+        if self.vertex_hook {
+            shader_defs.push("GAUSSIAN_MATERIAL_VERTEX".into());
+        }
+        if self.fragment_hook {
+            shader_defs.push("GAUSSIAN_MATERIAL_FRAGMENT".into());
+        }
+
+        descriptor.label = Some("gaussian material render pipeline".into());
+        descriptor.layout.truncate(GAUSSIAN_MATERIAL_BIND_GROUP);
+        descriptor.layout.push(self.material_layout.clone());
+
+        descriptor.vertex.shader = self.shader.clone();
+        descriptor.vertex.shader_defs.extend(shader_defs.iter().cloned());
+
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader = self.shader.clone();
+            fragment.shader_defs.extend(shader_defs);
+        }
+
+        descriptor
+    }
+}
+
+
+/// material bind group, rebuilt only when the material asset changes
+pub struct PreparedGaussianMaterial<M: GaussianMaterial> {
+    pub bindings: Vec<(u32, OwnedBindingResource)>,
+    pub bind_group: BindGroup,
+    pub data: M::Data,
+}
+
+impl<M: GaussianMaterial> RenderAsset for PreparedGaussianMaterial<M> {
+    type SourceAsset = M;
+    type Param = (
+        SRes<RenderDevice>,
+        Option<SRes<GaussianMaterialPipeline<M>>>,
+        M::Param,
+    );
+
+    fn prepare_asset(
+        material: Self::SourceAsset,
+        (render_device, pipeline, ref mut material_param): &mut SystemParamItem<Self::Param>,
+    ) -> Result<Self, PrepareAssetError<Self::SourceAsset>> {
+        // the pipeline is missing when the device lacks the material bind group
+        let Some(pipeline) = pipeline else {
+            return Err(PrepareAssetError::RetryNextUpdate(material));
+        };
+
+        match material.as_bind_group(&pipeline.material_layout, render_device, material_param) {
+            Ok(prepared) => Ok(PreparedGaussianMaterial {
+                bindings: prepared.bindings,
+                bind_group: prepared.bind_group,
+                data: prepared.data,
+            }),
+            Err(AsBindGroupError::RetryNextUpdate) => Err(PrepareAssetError::RetryNextUpdate(material)),
+            Err(other) => Err(PrepareAssetError::AsBindGroupError(other)),
+        }
+    }
+}
+
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn queue_gaussian_materials<M: GaussianMaterial>(
+    transparent_3d_draw_functions: Res<DrawFunctions<Transparent3d>>,
+    material_pipeline: Res<GaussianMaterialPipeline<M>>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GaussianMaterialPipeline<M>>>,
+    pipeline_cache: Res<PipelineCache>,
+    gaussian_clouds: Res<RenderAssets<GpuGaussianCloud>>,
+    sorted_entries: Res<RenderAssets<GpuSortedEntry>>,
+    materials: Res<RenderAssets<PreparedGaussianMaterial<M>>>,
+    mut transparent_render_phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &GaussianCamera,
+            &RenderVisibleEntities,
+            Option<&Msaa>,
+        ),
+    >,
+    gaussian_materials: Query<
+        (
+            &GaussianMaterialHandle<M>,
+            &GaussianCloudHandle,
+            &SortedEntriesHandle,
+            &GaussianCloudCentroid,
+            &GaussianCloudAppearance,
+            &GaussianCloudRasterSettings,
+            &GaussianCloudDebugSettings,
+            &GaussianCloudLighting,
+        ),
+        Without<GlobalSortMember>,
+    >,
+) {
+    let warmup = views.iter().any(|(_, _, camera, _, _)| camera.warmup);
+    if warmup {
+        return;
+    }
+
+    let draw_material = transparent_3d_draw_functions.read().id::<DrawGaussianMaterial<M>>();
+
+    for (
+        view_entity,
+        view,
+        _,
+        visible_entities,
+        msaa,
+    ) in &views {
+        let Some(transparent_phase) = transparent_render_phases.get_mut(&view_entity) else {
+            continue;
+        };
+
+        let msaa = msaa.cloned().unwrap_or_default();
+        let rangefinder = view.rangefinder3d();
+
+        for (render_entity, visible_entity) in visible_entities.iter::<With<GaussianCloudHandle>>() {
+            let Ok((
+                material_handle,
+                cloud_handle,
+                sorted_entries_handle,
+                centroid,
+                appearance,
+                raster_settings,
+                debug_settings,
+                lighting,
+            )) = gaussian_materials.get(*render_entity) else {
+                continue;
+            };
+
+            if raster_settings.backend == GaussianCloudBackend::Tile {
+                continue;
+            }
+
+            if materials.get(&material_handle.0).is_none() {
+                continue;
+            }
+
+            if gaussian_clouds.get(cloud_handle).is_none() {
+                continue;
+            }
+
+            if sorted_entries.get(sorted_entries_handle).is_none() {
+                continue;
+            }
+
+            let key = GaussianCloudPipelineKey {
+                sample_count: msaa.samples(),
+                hdr: view.hdr,
+                pass: GaussianCloudPass::Color,
+                lighting: lighting.is_lit(),
+                receive_shadows: lighting.is_lit() && lighting.receive_shadows,
+                ..GaussianCloudPipelineKey::from_settings(appearance, raster_settings, debug_settings)
+            };
+
+            let pipeline = pipelines.specialize(&pipeline_cache, &material_pipeline, key);
+
+            transparent_phase.add(Transparent3d {
+                entity: (*render_entity, *visible_entity),
+                draw_function: draw_material,
+                distance: rangefinder.distance_translation(&centroid.0),
+                pipeline,
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::NONE,
+            });
+        }
+    }
+}
+
+
+type DrawGaussianMaterial<M> = (
+    SetItemPipeline,
+    SetGaussianViewBindGroup<0>,
+    SetGaussianUniformBindGroup<1>,
+    SetGaussianMaterialBindGroup<M, GAUSSIAN_MATERIAL_BIND_GROUP>,
+    DrawGaussianInstanced,
+);
+
+pub struct SetGaussianMaterialBindGroup<M: GaussianMaterial, const I: usize>(PhantomData<M>);
+impl<P: PhaseItem, M: GaussianMaterial, const I: usize> RenderCommand<P> for SetGaussianMaterialBindGroup<M, I> {
+    type Param = SRes<RenderAssets<PreparedGaussianMaterial<M>>>;
+    type ViewQuery = ();
+    type ItemQuery = Read<GaussianMaterialHandle<M>>;
+
+    #[inline]
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        material_handle: Option<ROQueryItem<'w, Self::ItemQuery>>,
+        materials: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(material_handle) = material_handle else {
+            return RenderCommandResult::Skip;
+        };
+
+        let Some(material) = materials.into_inner().get(&material_handle.0) else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_bind_group(I, &material.bind_group, &[]);
+
+        RenderCommandResult::Success
+    }
+}
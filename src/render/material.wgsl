@@ -0,0 +1,16 @@
+#define_import_path bevy_gaussian_splatting::gaussian_material
+
+
+// cloud space gaussian center, returned by `material_vertex` before projection
+struct GaussianMaterialVertex {
+    splat_index: u32,
+    position: vec3<f32>,
+};
+
+// straight alpha color of a splat fragment, alpha includes the gaussian falloff
+struct GaussianMaterialFragment {
+    frag_coord: vec4<f32>,
+    color: vec4<f32>,
+    // quad offset in -1.0..1.0
+    uv: vec2<f32>,
+};
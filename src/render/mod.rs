@@ -22,6 +22,7 @@ use bevy::{
             GlobalsBuffer,
             GlobalsUniform,
         },
+        primitives::Aabb,
         render_asset::{
             PrepareAssetError,
             RenderAsset,
@@ -54,6 +55,7 @@ use bevy::{
         RenderSet,
         sync_world::RenderEntity,
     },
+    utils::HashMap,
 };
 
 use crate::{
@@ -64,9 +66,13 @@ use crate::{
             GaussianCloudHandle,
         },
         settings::{
+            GaussianCloudBackend,
             GaussianCloudDrawMode,
+            GaussianCloudAppearance,
+            GaussianCloudDebugSettings,
+            GaussianCloudRasterSettings,
             GaussianCloudRasterize,
-            GaussianCloudSettings,
+            GaussianCloudSortSettings,
             GaussianMode,
         },
     },
@@ -75,8 +81,11 @@ use crate::{
         SH_COEFF_COUNT,
         SH_VEC4_PLANES,
     },
+    lighting::GaussianCloudLighting,
+    material::noise::NoiseMaterial,
     morph::MorphPlugin,
     sort::{
+        GlobalSortMember,
         GpuSortedEntry,
         SortPlugin,
         SortEntry,
@@ -94,9 +103,19 @@ mod planar;
 #[cfg(feature = "buffer_texture")]
 mod texture;
 
+pub mod aov;
+pub mod color_grading;
+pub mod material;
+pub mod prepass;
+pub mod reveal;
+pub mod tile;
+pub mod volume_mask;
 
+
+const GAUSSIAN_MATERIAL_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(908231745);
 const BINDINGS_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(675257236);
 const GAUSSIAN_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(68294581);
+const GAUSSIAN_3D_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(513471236);
 const GAUSSIAN_SURFEL_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(123166726);
 const HELPERS_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(134646367);
 const PACKED_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(123623514);
@@ -124,6 +143,20 @@ impl Plugin for RenderPipelinePlugin {
             Shader::from_wgsl
         );
 
+        load_internal_asset!(
+            app,
+            GAUSSIAN_MATERIAL_SHADER_HANDLE,
+            "material.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            GAUSSIAN_3D_SHADER_HANDLE,
+            "gaussian_3d.wgsl",
+            Shader::from_wgsl
+        );
+
         load_internal_asset!(
             app,
             GAUSSIAN_SURFEL_SHADER_HANDLE,
@@ -172,6 +205,12 @@ impl Plugin for RenderPipelinePlugin {
         app.add_plugins((
             MorphPlugin,
             SortPlugin,
+            aov::GaussianAovPlugin,
+            color_grading::ColorGradingPlugin,
+            prepass::GaussianDepthPrepassPlugin,
+            reveal::RevealPlugin,
+            tile::TileRasterizePlugin,
+            volume_mask::VolumeMaskPlugin,
         ));
 
         #[cfg(feature = "buffer_texture")]
@@ -205,12 +244,21 @@ impl Plugin for RenderPipelinePlugin {
 
 #[derive(Bundle)]
 pub struct GpuGaussianSplattingBundle {
-    pub settings: GaussianCloudSettings,
+    pub sort_settings: GaussianCloudSortSettings,
+    pub appearance: GaussianCloudAppearance,
+    pub raster_settings: GaussianCloudRasterSettings,
+    pub debug_settings: GaussianCloudDebugSettings,
     pub settings_uniform: GaussianCloudUniform,
     pub sorted_entries: SortedEntriesHandle,
     pub cloud_handle: GaussianCloudHandle,
+    pub centroid: GaussianCloudCentroid,
+    pub lighting: GaussianCloudLighting,
 }
 
+/// world space center of the cloud bounds, orders clouds against other transparent phase items
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct GaussianCloudCentroid(pub Vec3);
+
 #[derive(Debug, Clone)]
 pub struct GpuGaussianCloud {
     #[cfg(feature = "packed")]
@@ -272,7 +320,7 @@ type GpuGaussianBundleQuery = (
     Entity,
     &'static GaussianCloudHandle,
     &'static SortedEntriesHandle,
-    &'static GaussianCloudSettings,
+    &'static GaussianCloudRasterSettings,
     (),
 );
 
@@ -281,11 +329,11 @@ type GpuGaussianBundleQuery = (
     Entity,
     &'static GaussianCloudHandle,
     &'static SortedEntriesHandle,
-    &'static GaussianCloudSettings,
+    &'static GaussianCloudRasterSettings,
     &'static texture::GpuTextureBuffers,
 );
 
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn queue_gaussians(
     gaussian_cloud_uniform: Res<ComponentUniforms<GaussianCloudUniform>>,
     transparent_3d_draw_functions: Res<DrawFunctions<Transparent3d>>,
@@ -304,7 +352,20 @@ fn queue_gaussians(
             Option<&Msaa>,
         ),
     >,
-    gaussian_splatting_bundles: Query<GpuGaussianBundleQuery>,
+    gaussian_splatting_bundles: Query<
+        (
+            GpuGaussianBundleQuery,
+            &GaussianCloudCentroid,
+            &GaussianCloudAppearance,
+            &GaussianCloudDebugSettings,
+            &GaussianCloudLighting,
+        ),
+        (
+            Without<GlobalSortMember>,
+            Without<material::GaussianMaterialMarker>,
+        ),
+    >,
+    global_sort_members: Query<(), With<GlobalSortMember>>,
 ) {
     let warmup = views.iter().any(|(_, _, camera, _, _)| camera.warmup);
     if warmup {
@@ -329,57 +390,69 @@ fn queue_gaussians(
             continue;
         };
 
-        for (
-            _entity,
-            cloud_handle,
-            sorted_entries_handle,
-            settings,
-            _,
-        ) in &gaussian_splatting_bundles {
+        let msaa = msaa.cloned().unwrap_or_default();
+        let rangefinder = view.rangefinder3d();
+
+        for (render_entity, visible_entity) in visible_entities.iter::<With<GaussianCloudHandle>>() {
+            // drawn through the merged cloud
+            if global_sort_members.contains(*render_entity) {
+                continue;
+            }
+
+            let Ok((
+                (
+                    _entity,
+                    cloud_handle,
+                    sorted_entries_handle,
+                    raster_settings,
+                    _,
+                ),
+                centroid,
+                appearance,
+                debug_settings,
+                lighting,
+            )) = gaussian_splatting_bundles.get(*render_entity) else {
+                continue;
+            };
+
+            // rasterized by `tile::TileRasterizeNode`
+            if raster_settings.backend == GaussianCloudBackend::Tile {
+                continue;
+            }
+
             if gaussian_clouds.get(cloud_handle).is_none() {
-                return;
+                continue;
             }
 
             if sorted_entries.get(sorted_entries_handle).is_none() {
-                return;
+                continue;
             }
 
-            let msaa = msaa.cloned().unwrap_or_default();
-
             let key = GaussianCloudPipelineKey {
-                aabb: settings.aabb,
-                opacity_adaptive_radius: settings.opacity_adaptive_radius,
-                visualize_bounding_box: settings.visualize_bounding_box,
-                draw_mode: settings.draw_mode,
-                gaussian_mode: settings.gaussian_mode,
-                rasterize_mode: settings.rasterize_mode,
                 sample_count: msaa.samples(),
                 hdr: view.hdr,
+                pass: GaussianCloudPass::Color,
+                lighting: lighting.is_lit(),
+                receive_shadows: lighting.is_lit() && lighting.receive_shadows,
+                ..GaussianCloudPipelineKey::from_settings(appearance, raster_settings, debug_settings)
             };
 
             let pipeline = pipelines.specialize(&pipeline_cache, &custom_pipeline, key);
 
-            // // TODO: distance to gaussian cloud centroid
-            // let rangefinder = view.rangefinder3d();
-
-            for (render_entity, visible_entity) in visible_entities.iter::<With<GaussianCloudHandle>>() {
-                transparent_phase.add(Transparent3d {
-                    entity: (*render_entity, *visible_entity),
-                    draw_function: draw_custom,
-                    distance: 0.0,
-                    // distance: rangefinder
-                    //     .distance_translation(&mesh_instance.transforms.transform.translation),
-                    pipeline,
-                    batch_range: 0..1,
-                    extra_index: PhaseItemExtraIndex::NONE,
-                });
-            }
+            transparent_phase.add(Transparent3d {
+                entity: (*render_entity, *visible_entity),
+                draw_function: draw_custom,
+                distance: rangefinder.distance_translation(&centroid.0),
+                pipeline,
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::NONE,
+            });
         }
     }
 }
 
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct GaussianCloudPipeline {
     shader: Handle<Shader>,
     pub gaussian_cloud_layout: BindGroupLayout,
@@ -392,7 +465,8 @@ impl FromWorld for GaussianCloudPipeline {
     fn from_world(render_world: &mut World) -> Self {
         let render_device = render_world.resource::<RenderDevice>();
 
-        let view_layout_entries = vec![
+        #[allow(unused_mut)]
+        let mut view_layout_entries = vec![
             BindGroupLayoutEntry {
                 binding: 0,
                 visibility: ShaderStages::all(),
@@ -415,6 +489,9 @@ impl FromWorld for GaussianCloudPipeline {
             },
         ];
 
+        #[cfg(feature = "lighting")]
+        view_layout_entries.extend(crate::lighting::direct::view_layout_entries());
+
         let view_layout = render_device.create_bind_group_layout(
             Some("gaussian_view_layout"),
             &view_layout_entries,
@@ -422,18 +499,7 @@ impl FromWorld for GaussianCloudPipeline {
 
         let gaussian_uniform_layout = render_device.create_bind_group_layout(
             Some("gaussian_uniform_layout"),
-            &[
-                BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStages::all(),
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: true,
-                        min_binding_size: Some(GaussianCloudUniform::min_size()),
-                    },
-                    count: None,
-                },
-            ],
+            &gaussian_uniform_layout_entries(),
         );
 
         #[cfg(not(feature = "morph_particles"))]
@@ -477,6 +543,33 @@ impl FromWorld for GaussianCloudPipeline {
     }
 }
 
+/// group 1 entries, the gaussian uniform and volume mask voxels
+fn gaussian_uniform_layout_entries() -> Vec<BindGroupLayoutEntry> {
+    vec![
+        BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::all(),
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: Some(GaussianCloudUniform::min_size()),
+            },
+            count: None,
+        },
+        #[cfg(feature = "buffer_storage")]
+        BindGroupLayoutEntry {
+            binding: 1,
+            visibility: ShaderStages::all(),
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: BufferSize::new(std::mem::size_of::<u32>() as u64),
+            },
+            count: None,
+        },
+    ]
+}
+
 // TODO: allow setting shader defines via API
 // TODO: separate shader defines for each pipeline
 pub struct ShaderDefines {
@@ -492,6 +585,10 @@ pub struct ShaderDefines {
     pub sorting_buffer_size: u32,
 
     pub temporal_sort_window_size: u32,
+
+    pub tile_size: u32,
+    pub tile_workgroup_invocations: u32,
+    pub tile_max_gaussians: u32,
 }
 
 impl ShaderDefines {
@@ -517,6 +614,7 @@ impl Default for ShaderDefines {
         let workgroup_entries_c = workgroup_invocations_c * entries_per_invocation_c;
         let sorting_buffer_size = radix_base * radix_digit_places *
             std::mem::size_of::<u32>() as u32 + 5 * std::mem::size_of::<u32>() as u32;
+        let tile_size = 16;
 
         Self {
             radix_bits_per_digit,
@@ -531,6 +629,10 @@ impl Default for ShaderDefines {
             sorting_buffer_size,
 
             temporal_sort_window_size: 16,
+
+            tile_size,
+            tile_workgroup_invocations: tile_size * tile_size,
+            tile_max_gaussians: 1024,
         }
     }
 }
@@ -553,6 +655,13 @@ pub fn shader_defs(
         ShaderDefVal::UInt("WORKGROUP_ENTRIES_C".into(), defines.workgroup_entries_c),
 
         ShaderDefVal::UInt("TEMPORAL_SORT_WINDOW_SIZE".into(), defines.temporal_sort_window_size),
+
+        ShaderDefVal::UInt("TILE_SIZE".into(), defines.tile_size),
+        ShaderDefVal::UInt("TILE_WORKGROUP_INVOCATIONS".into(), defines.tile_workgroup_invocations),
+        ShaderDefVal::UInt("TILE_MAX_GAUSSIANS".into(), defines.tile_max_gaussians),
+
+        ShaderDefVal::UInt("MAX_VOLUME_MASKS".into(), volume_mask::MAX_VOLUME_MASKS as u32),
+        ShaderDefVal::UInt("VOLUME_MASK_VOXEL_RESOLUTION".into(), volume_mask::VOLUME_MASK_VOXEL_RESOLUTION as u32),
     ];
 
     if key.aabb {
@@ -610,6 +719,9 @@ pub fn shader_defs(
     #[cfg(feature = "webgl2")]
     shader_defs.push("WEBGL2".into());
 
+    #[cfg(feature = "material_noise")]
+    shader_defs.push("NOISE_MATERIAL".into());
+
     match key.gaussian_mode {
         GaussianMode::Gaussian3d => shader_defs.push("GAUSSIAN_3D".into()),
         GaussianMode::GaussianSurfel => shader_defs.push("GAUSSIAN_SURFEL".into()),
@@ -627,9 +739,48 @@ pub fn shader_defs(
         GaussianCloudDrawMode::HighlightSelected => shader_defs.push("HIGHLIGHT_SELECTED".into()),
     }
 
+    if key.pass == GaussianCloudPass::Pick {
+        shader_defs.push("GAUSSIAN_PICK".into());
+    }
+
+    if key.mip_filter {
+        shader_defs.push("MIP_FILTER".into());
+
+        // the 3d filter is a plane of the storage buffer cloud layout
+        #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+        shader_defs.push("MIP_FILTER_3D".into());
+
+        #[cfg(not(all(feature = "buffer_storage", not(feature = "packed"))))]
+        bevy::log::warn_once!("the per gaussian 3d mip filter needs the unpacked `buffer_storage` layout, only the 2d filter is applied");
+    }
+
+    #[cfg(feature = "lighting")]
+    if key.lighting {
+        shader_defs.extend(crate::lighting::direct::shader_defs());
+
+        if key.receive_shadows {
+            shader_defs.push("RECEIVE_SHADOWS".into());
+        }
+
+        // pbr attributes are a plane of the storage buffer cloud layout
+        #[cfg(all(feature = "buffer_storage", not(feature = "packed")))]
+        shader_defs.push("PBR_MATERIAL".into());
+    }
+
     shader_defs
 }
 
+/// render pass a `GaussianCloudPipeline` is specialized for
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum GaussianCloudPass {
+    #[default]
+    Color,
+    DepthPrepass,
+    Pick,
+    /// depth only, into the shadow map of a bevy light view
+    Shadow,
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Default)]
 pub struct GaussianCloudPipelineKey {
     pub aabb: bool,
@@ -640,6 +791,30 @@ pub struct GaussianCloudPipelineKey {
     pub rasterize_mode: GaussianCloudRasterize,
     pub sample_count: u32,
     pub hdr: bool,
+    pub pass: GaussianCloudPass,
+    pub lighting: bool,
+    pub receive_shadows: bool,
+    pub mip_filter: bool,
+}
+
+impl GaussianCloudPipelineKey {
+    /// cloud dependent part of the key, view and pass fields are left at their defaults
+    pub fn from_settings(
+        appearance: &GaussianCloudAppearance,
+        raster_settings: &GaussianCloudRasterSettings,
+        debug_settings: &GaussianCloudDebugSettings,
+    ) -> Self {
+        Self {
+            aabb: raster_settings.aabb,
+            opacity_adaptive_radius: raster_settings.opacity_adaptive_radius,
+            visualize_bounding_box: debug_settings.visualize_bounding_box,
+            draw_mode: raster_settings.draw_mode,
+            gaussian_mode: appearance.gaussian_mode,
+            rasterize_mode: raster_settings.rasterize_mode,
+            mip_filter: raster_settings.mip_filter,
+            ..default()
+        }
+    }
 }
 
 impl SpecializedRenderPipeline for GaussianCloudPipeline {
@@ -654,8 +829,66 @@ impl SpecializedRenderPipeline for GaussianCloudPipeline {
             TextureFormat::Rgba8UnormSrgb
         };
 
+        let (label, entry_point, targets) = match key.pass {
+            GaussianCloudPass::Color => (
+                "gaussian cloud render pipeline",
+                "fs_main",
+                vec![Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            ),
+            // the depth prepass only writes depth of sufficiently opaque splat fragments
+            GaussianCloudPass::DepthPrepass => (
+                "gaussian cloud depth prepass pipeline",
+                "fs_depth",
+                vec![],
+            ),
+            GaussianCloudPass::Shadow => (
+                "gaussian cloud shadow pipeline",
+                "fs_depth",
+                vec![],
+            ),
+            // gaussian index and cloud entity, the last (nearest) opaque enough fragment wins
+            GaussianCloudPass::Pick => (
+                "gaussian cloud pick pipeline",
+                "fs_pick",
+                vec![
+                    Some(ColorTargetState {
+                        format: TextureFormat::R32Uint,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    }),
+                    Some(ColorTargetState {
+                        format: TextureFormat::R32Uint,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    }),
+                ],
+            ),
+        };
+
+        // picking ignores scene depth, the view depth texture may be multisampled
+        let depth_stencil = (key.pass != GaussianCloudPass::Pick).then_some(DepthStencilState {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: matches!(key.pass, GaussianCloudPass::DepthPrepass | GaussianCloudPass::Shadow),
+            depth_compare: CompareFunction::GreaterEqual,
+            stencil: StencilState {
+                front: StencilFaceState::IGNORE,
+                back: StencilFaceState::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+            bias: DepthBiasState {
+                constant: 0,
+                slope_scale: 0.0,
+                clamp: 0.0,
+            },
+        });
+
         RenderPipelineDescriptor {
-            label: Some("gaussian cloud render pipeline".into()),
+            label: Some(label.into()),
             layout: vec![
                 self.view_layout.clone(),
                 self.gaussian_uniform_layout.clone(),
@@ -671,12 +904,8 @@ impl SpecializedRenderPipeline for GaussianCloudPipeline {
             fragment: Some(FragmentState {
                 shader: self.shader.clone(),
                 shader_defs,
-                entry_point: "fs_main".into(),
-                targets: vec![Some(ColorTargetState {
-                    format,
-                    blend: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
-                    write_mask: ColorWrites::ALL,
-                })],
+                entry_point: entry_point.into(),
+                targets,
             }),
             primitive: PrimitiveState {
                 topology: PrimitiveTopology::TriangleStrip,
@@ -687,22 +916,7 @@ impl SpecializedRenderPipeline for GaussianCloudPipeline {
                 conservative: false,
                 polygon_mode: PolygonMode::Fill,
             },
-            depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth32Float,
-                depth_write_enabled: false,
-                depth_compare: CompareFunction::GreaterEqual,
-                stencil: StencilState {
-                    front: StencilFaceState::IGNORE,
-                    back: StencilFaceState::IGNORE,
-                    read_mask: 0,
-                    write_mask: 0,
-                },
-                bias: DepthBiasState {
-                    constant: 0,
-                    slope_scale: 0.0,
-                    clamp: 0.0,
-                },
-            }),
+            depth_stencil,
             multisample: MultisampleState {
                 count: key.sample_count,
                 mask: !0,
@@ -729,35 +943,87 @@ pub struct GaussianCloudUniform {
     pub global_scale: f32,
     pub count: u32,
     pub count_root_ceil: u32,
+    pub depth_alpha_threshold: f32,
+    /// main world entity index of the cloud, written by the pick pass
+    pub entity_index: u32,
+    pub volume_mask_count: u32,
+    pub volume_masks: [volume_mask::GaussianVolumeMaskUniform; volume_mask::MAX_VOLUME_MASKS],
+    pub reveal: reveal::GaussianRevealUniform,
+    /// blend towards the relit color, zero for unlit clouds
+    pub relight: f32,
+    pub noise: crate::material::noise::GaussianNoiseUniform,
+    pub color_grading: color_grading::GaussianColorGradingUniform,
+    pub max_sh_band: u32,
+}
+
+/// what the last `extract_gaussians` saw, clouds are re-extracted only when an input of their uniform changes
+#[derive(Default)]
+pub struct ExtractedGaussianClouds {
+    /// main world entity to the gaussian count and present optional components it was extracted with
+    clouds: HashMap<Entity, (usize, [bool; 4])>,
+    volume_mask_count: usize,
 }
 
 #[allow(clippy::type_complexity)]
 pub fn extract_gaussians(
     mut commands: Commands,
-    mut prev_commands_len: Local<usize>,
+    mut extracted: Local<ExtractedGaussianClouds>,
     asset_server: Res<AssetServer>,
     gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
     gaussians_query: Extract<
         Query<(
-            RenderEntity,
+            Entity,
+            Ref<RenderEntity>,
             &ViewVisibility,
-            &GaussianCloudHandle,
-            &SortedEntriesHandle,
-            &GaussianCloudSettings,
-            &GlobalTransform,
+            Ref<GaussianCloudHandle>,
+            Ref<SortedEntriesHandle>,
+            Ref<GaussianCloudSortSettings>,
+            Ref<GaussianCloudAppearance>,
+            Ref<GaussianCloudRasterSettings>,
+            Ref<GaussianCloudDebugSettings>,
+            Ref<GlobalTransform>,
+            Option<Ref<Aabb>>,
+            Option<Ref<reveal::GaussianCloudReveal>>,
+            Option<Ref<GaussianCloudLighting>>,
+            Option<Ref<NoiseMaterial>>,
+        )>,
+    >,
+    volume_masks: Extract<
+        Query<(
+            Ref<volume_mask::VolumeMask>,
+            Ref<GlobalTransform>,
+            Ref<InheritedVisibility>,
         )>,
     >,
+    volume_mask_voxels: Extract<Res<volume_mask::VolumeMaskVoxels>>,
 ) {
-    let mut commands_list = Vec::with_capacity(*prev_commands_len);
+    let mut commands_list = Vec::new();
+    let mut visible = HashMap::with_capacity(extracted.clouds.len());
     // let visible_gaussians = gaussians_query.iter().filter(|(_, vis, ..)| vis.is_visible());
 
+    // masks apply to every cloud, any mask edit re-extracts all of them
+    let volume_masks_changed = volume_mask_voxels.is_changed()
+        || volume_masks.iter().len() != extracted.volume_mask_count
+        || volume_masks.iter().any(|(mask, transform, visibility)| {
+            mask.is_changed() || transform.is_changed() || visibility.is_changed()
+        });
+    extracted.volume_mask_count = volume_masks.iter().len();
+
     for (
+        main_entity,
         entity,
         visibility,
         cloud_handle,
         sorted_entries,
-        settings,
+        sort_settings,
+        appearance,
+        raster_settings,
+        debug_settings,
         transform,
+        aabb,
+        reveal,
+        lighting,
+        noise,
     ) in gaussians_query.iter() {
         if !visibility.get() {
             continue;
@@ -769,31 +1035,85 @@ pub fn extract_gaussians(
             }
         }
 
-        if gaussian_cloud_res.get(cloud_handle).is_none() {
+        let Some(cloud) = gaussian_cloud_res.get(&*cloud_handle) else {
+            continue;
+        };
+
+        // removed optional components and asset reloads don't flag a change on the entity
+        let state = (
+            cloud.count,
+            [aabb.is_some(), reveal.is_some(), lighting.is_some(), noise.is_some()],
+        );
+        visible.insert(main_entity, state);
+
+        let changed = extracted.clouds.get(&main_entity) != Some(&state)
+            || volume_masks_changed
+            || entity.is_changed()
+            || cloud_handle.is_changed()
+            || sorted_entries.is_changed()
+            || sort_settings.is_changed()
+            || appearance.is_changed()
+            || raster_settings.is_changed()
+            || debug_settings.is_changed()
+            || transform.is_changed()
+            || aabb.as_ref().is_some_and(Ref::is_changed)
+            || reveal.as_ref().is_some_and(Ref::is_changed)
+            || lighting.as_ref().is_some_and(Ref::is_changed)
+            || noise.as_ref().is_some_and(Ref::is_changed);
+        if !changed {
             continue;
         }
 
-        let cloud = gaussian_cloud_res.get(cloud_handle).unwrap();
+        let (volume_mask_count, volume_masks) = volume_mask::volume_mask_uniforms(
+            main_entity,
+            volume_masks.iter().map(|(mask, transform, visibility)| {
+                (mask.into_inner(), transform.into_inner(), visibility.into_inner())
+            }),
+            &volume_mask_voxels,
+        );
 
         let settings_uniform = GaussianCloudUniform {
             transform: transform.compute_matrix(),
-            global_opacity: settings.global_opacity,
-            global_scale: settings.global_scale,
+            global_opacity: appearance.global_opacity,
+            global_scale: appearance.global_scale,
             count: cloud.count as u32,
             count_root_ceil: (cloud.count as f32).sqrt().ceil() as u32,
+            depth_alpha_threshold: raster_settings.depth_alpha_threshold,
+            entity_index: main_entity.index(),
+            volume_mask_count,
+            volume_masks,
+            reveal: reveal.as_deref().map(Into::into).unwrap_or_default(),
+            relight: lighting.as_deref()
+                .filter(|lighting| lighting.is_lit())
+                .map_or(0.0, |lighting| lighting.relight),
+            noise: noise.as_deref()
+                .filter(|_| cfg!(feature = "material_noise"))
+                .map(Into::into)
+                .unwrap_or_default(),
+            color_grading: (&appearance.color_grading).into(),
+            max_sh_band: appearance.max_sh_band,
         };
 
         commands_list.push((
-            entity,
+            entity.id(),
             GpuGaussianSplattingBundle {
-                settings: settings.clone(),
+                sort_settings: sort_settings.clone(),
+                appearance: appearance.clone(),
+                raster_settings: raster_settings.clone(),
+                debug_settings: debug_settings.clone(),
                 settings_uniform,
                 sorted_entries: sorted_entries.clone(),
                 cloud_handle: cloud_handle.clone(),
+                centroid: GaussianCloudCentroid(
+                    transform.transform_point(aabb.map_or(Vec3::ZERO, |aabb| aabb.center.into())),
+                ),
+                lighting: lighting.as_deref().cloned().unwrap_or(GaussianCloudLighting::UNLIT),
             },
         ));
     }
-    *prev_commands_len = commands_list.len();
+
+    // hidden or despawned clouds are extracted in full once they show up again
+    extracted.clouds = visible;
     commands.insert_or_spawn_batch(commands_list);
 }
 
@@ -809,6 +1129,29 @@ pub struct GaussianCloudBindGroup {
     pub sorted_bind_group: BindGroup,
 }
 
+/// group 1 entries matching `gaussian_uniform_layout_entries`
+fn gaussian_uniform_bind_group_entries<'a>(
+    model: &'a Buffer,
+    #[cfg(feature = "buffer_storage")]
+    volume_mask_voxels: &'a volume_mask::GpuVolumeMaskVoxels,
+) -> Vec<BindGroupEntry<'a>> {
+    vec![
+        BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer: model,
+                offset: 0,
+                size: GaussianCloudUniform::min_size().into(),
+            }),
+        },
+        #[cfg(feature = "buffer_storage")]
+        BindGroupEntry {
+            binding: 1,
+            resource: volume_mask_voxels.buffer.as_entire_binding(),
+        },
+    ]
+}
+
 #[allow(clippy::too_many_arguments)]
 fn queue_gaussian_bind_group(
     mut commands: Commands,
@@ -820,6 +1163,8 @@ fn queue_gaussian_bind_group(
     gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
     sorted_entries_res: Res<RenderAssets<GpuSortedEntry>>,
     gaussian_clouds: Query<GpuGaussianBundleQuery>,
+    #[cfg(feature = "buffer_storage")]
+    volume_mask_voxels: Res<volume_mask::GpuVolumeMaskVoxels>,
     #[cfg(feature = "buffer_texture")]
     gpu_images: Res<RenderAssets<bevy::render::texture::GpuImage>>,
 ) {
@@ -831,16 +1176,11 @@ fn queue_gaussian_bind_group(
     groups.base_bind_group = Some(render_device.create_bind_group(
         "gaussian_uniform_bind_group",
         &gaussian_cloud_pipeline.gaussian_uniform_layout,
-        &[
-            BindGroupEntry {
-                binding: 0,
-                resource: BindingResource::Buffer(BufferBinding {
-                    buffer: model,
-                    offset: 0,
-                    size: GaussianCloudUniform::min_size().into(),
-                }),
-            },
-        ],
+        &gaussian_uniform_bind_group_entries(
+            model,
+            #[cfg(feature = "buffer_storage")]
+            &volume_mask_voxels,
+        ),
     ));
 
     for query in gaussian_clouds.iter() {
@@ -925,6 +1265,12 @@ pub struct GaussianViewBindGroup {
     pub value: BindGroup,
 }
 
+/// views drawing gaussian clouds, light views are included to cast shadows
+#[cfg(feature = "lighting")]
+type GaussianViewFilter = Or<(With<GaussianCamera>, With<crate::lighting::direct::GaussianShadowView>)>;
+#[cfg(not(feature = "lighting"))]
+type GaussianViewFilter = With<GaussianCamera>;
+
 pub fn queue_gaussian_view_bind_groups(
     mut commands: Commands,
     render_device: Res<RenderDevice>,
@@ -935,9 +1281,11 @@ pub fn queue_gaussian_view_bind_groups(
             Entity,
             &ExtractedView,
         ),
-        With<GaussianCamera>,
+        GaussianViewFilter,
     >,
     globals_buffer: Res<GlobalsBuffer>,
+    #[cfg(feature = "lighting")]
+    view_lighting: crate::lighting::direct::GaussianViewLighting,
 ) {
     if let (
         Some(view_binding),
@@ -952,7 +1300,8 @@ pub fn queue_gaussian_view_bind_groups(
         ) in &views {
             let layout = &gaussian_cloud_pipeline.view_layout;
 
-            let entries = vec![
+            #[allow(unused_mut)]
+            let mut entries = vec![
                 BindGroupEntry {
                     binding: 0,
                     resource: view_binding.clone(),
@@ -963,6 +1312,14 @@ pub fn queue_gaussian_view_bind_groups(
                 },
             ];
 
+            #[cfg(feature = "lighting")]
+            {
+                let Some(lighting_entries) = view_lighting.bind_group_entries(entity) else {
+                    continue;
+                };
+                entries.extend(lighting_entries);
+            }
+
             let view_bind_group = render_device.create_bind_group(
                 "gaussian_view_bind_group",
                 layout,
@@ -1043,10 +1400,14 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetGaussianUniformBindGr
 
 pub struct DrawGaussianInstanced;
 impl<P: PhaseItem> RenderCommand<P> for DrawGaussianInstanced {
-    type Param = SRes<RenderAssets<GpuGaussianCloud>>;
+    type Param = (
+        SRes<RenderAssets<GpuGaussianCloud>>,
+        SRes<RenderAssets<GpuSortedEntry>>,
+    );
     type ViewQuery = Read<SortTrigger>;
     type ItemQuery = (
         Read<GaussianCloudHandle>,
+        Read<SortedEntriesHandle>,
         Read<GaussianCloudBindGroup>,
     );
 
@@ -1056,39 +1417,106 @@ impl<P: PhaseItem> RenderCommand<P> for DrawGaussianInstanced {
         view: &'w SortTrigger,
         entity: Option<(
             &'w GaussianCloudHandle,
+            &'w SortedEntriesHandle,
             &'w GaussianCloudBindGroup,
         )>,
-        gaussian_clouds: SystemParamItem<'w, '_, Self::Param>,
+        (
+            gaussian_clouds,
+            sorted_entries,
+        ): SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        let (handle, bind_groups) = entity.expect("gaussian cloud entity not found");
+        let (handle, sorted_entries_handle, bind_groups) = entity.expect("gaussian cloud entity not found");
 
         let gpu_gaussian_cloud = match gaussian_clouds.into_inner().get(handle) {
             Some(gpu_gaussian_cloud) => gpu_gaussian_cloud,
             None => return RenderCommandResult::Skip,
         };
 
-        pass.set_bind_group(
-            2,
-            &bind_groups.cloud_bind_group,
-            &[],
+        draw_gaussian_cloud(
+            pass,
+            view.camera_index,
+            gpu_gaussian_cloud,
+            sorted_entries.into_inner().get(sorted_entries_handle),
+            bind_groups,
         );
 
-        // TODO: align dynamic offset to `min_storage_buffer_offset_alignment`
-        pass.set_bind_group(
-            3,
-            &bind_groups.sorted_bind_group,
-            &[
-                view.camera_index as u32 * std::mem::size_of::<SortEntry>() as u32 * gpu_gaussian_cloud.count as u32,
-            ],
-        );
+        RenderCommandResult::Success
+    }
+}
 
-        #[cfg(feature = "webgl2")]
-        pass.draw(0..4, 0..gpu_gaussian_cloud.count as u32);
+fn set_gaussian_cloud_bind_groups<'w>(
+    pass: &mut TrackedRenderPass<'w>,
+    camera_index: usize,
+    gpu_gaussian_cloud: &'w GpuGaussianCloud,
+    bind_groups: &'w GaussianCloudBindGroup,
+) {
+    pass.set_bind_group(
+        2,
+        &bind_groups.cloud_bind_group,
+        &[],
+    );
+
+    // TODO: align dynamic offset to `min_storage_buffer_offset_alignment`
+    pass.set_bind_group(
+        3,
+        &bind_groups.sorted_bind_group,
+        &[
+            camera_index as u32 * std::mem::size_of::<SortEntry>() as u32 * gpu_gaussian_cloud.count as u32,
+        ],
+    );
+}
 
-        #[cfg(not(feature = "webgl2"))]
-        pass.draw_indirect(&gpu_gaussian_cloud.draw_indirect_buffer, 0);
+/// binds the cloud and sorted entries (groups 2 and 3) and issues the instanced draw
+pub fn draw_gaussian_cloud<'w>(
+    pass: &mut TrackedRenderPass<'w>,
+    camera_index: usize,
+    gpu_gaussian_cloud: &'w GpuGaussianCloud,
+    sorted_entries: Option<&'w GpuSortedEntry>,
+    bind_groups: &'w GaussianCloudBindGroup,
+) {
+    set_gaussian_cloud_bind_groups(pass, camera_index, gpu_gaussian_cloud, bind_groups);
+
+    // culled instance count written by the CPU sorts, the radix sort writes into the cloud's indirect buffer instead
+    let culled_draw = sorted_entries
+        .and_then(|sorted_entries| {
+            sorted_entries.visible_counts
+                .get(camera_index)
+                .copied()
+                .flatten()
+                .map(|visible_count| (sorted_entries, visible_count))
+        });
 
-        RenderCommandResult::Success
+    #[cfg(feature = "webgl2")]
+    {
+        let instance_count = culled_draw
+            .map(|(_, visible_count)| visible_count)
+            .unwrap_or(gpu_gaussian_cloud.count as u32);
+
+        pass.draw(0..4, 0..instance_count);
+    }
+
+    #[cfg(not(feature = "webgl2"))]
+    match culled_draw {
+        Some((sorted_entries, _)) => pass.draw_indirect(
+            &sorted_entries.draw_indirect_buffer,
+            (camera_index * std::mem::size_of::<wgpu::util::DrawIndirectArgs>()) as u64,
+        ),
+        None => pass.draw_indirect(&gpu_gaussian_cloud.draw_indirect_buffer, 0),
     }
 }
+
+/// draws every gaussian of the camera's sorted entries, including the ones culled from its view
+///
+/// sorts keep culled gaussians at the end of the camera's entry range, views that see more than the
+/// camera (e.g. shadow casting lights) draw the whole range.
+pub fn draw_gaussian_cloud_unculled<'w>(
+    pass: &mut TrackedRenderPass<'w>,
+    camera_index: usize,
+    gpu_gaussian_cloud: &'w GpuGaussianCloud,
+    bind_groups: &'w GaussianCloudBindGroup,
+) {
+    set_gaussian_cloud_bind_groups(pass, camera_index, gpu_gaussian_cloud, bind_groups);
+
+    pass.draw(0..4, 0..gpu_gaussian_cloud.count as u32);
+}
@@ -1,6 +1,9 @@
 #define_import_path bevy_gaussian_splatting::packed
 
-#import bevy_gaussian_splatting::bindings::points
+#import bevy_gaussian_splatting::bindings::{
+    gaussian_uniforms,
+    points,
+}
 #import bevy_gaussian_splatting::spherical_harmonics::{
     spherical_harmonics_lookup,
     srgb_to_linear,
@@ -18,7 +21,7 @@ fn get_color(
     ray_direction: vec3<f32>,
 ) -> vec3<f32> {
     let sh = get_spherical_harmonics(index);
-    let color = spherical_harmonics_lookup(ray_direction, sh);
+    let color = spherical_harmonics_lookup(ray_direction, sh, gaussian_uniforms.max_sh_band);
     return srgb_to_linear(color);
 }
 
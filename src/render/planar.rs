@@ -18,19 +18,35 @@ use crate::{
         GaussianCloudPipeline,
         GpuGaussianCloud,
     },
-    material::spherical_harmonics::SphericalHarmonicCoefficients,
+    material::{
+        pbr::PbrMaterial,
+        spherical_harmonics::SphericalHarmonicCoefficients,
+    },
 };
 
 #[cfg(feature = "f16")]
 use crate::gaussian::f16::RotationScaleOpacityPacked128;
 
 
+/// binding of the pbr material plane, after every layout of the cloud planes
+#[cfg(feature = "lighting")]
+pub const PBR_MATERIAL_BINDING: u32 = 4;
+
+/// binding of the mip-splatting 3d filter plane
+pub const FILTER_3D_BINDING: u32 = 5;
+
+
 #[cfg(feature = "f16")]
 #[derive(Debug, Clone)]
 pub struct PlanarBuffers {
     position_visibility: Buffer,
     spherical_harmonics: Buffer,
 
+    #[cfg(feature = "lighting")]
+    pbr_material: Buffer,
+
+    filter_3d: Buffer,
+
     #[cfg(feature = "precompute_covariance_3d")]
     covariance_3d_opacity: Buffer,
 
@@ -45,6 +61,11 @@ pub struct PlanarBuffers {
     position_visibility: Buffer,
     spherical_harmonics: Buffer,
 
+    #[cfg(feature = "lighting")]
+    pbr_material: Buffer,
+
+    filter_3d: Buffer,
+
     #[cfg(feature = "precompute_covariance_3d")]
     covariance_3d_opacity: Buffer,
 
@@ -90,6 +111,11 @@ pub fn prepare_cloud(
         position_visibility,
         spherical_harmonics,
 
+        #[cfg(feature = "lighting")]
+        pbr_material: prepare_pbr_material(render_device, cloud),
+
+        filter_3d: prepare_filter_3d(render_device, cloud),
+
         #[cfg(feature = "precompute_covariance_3d")]
         covariance_3d_opacity,
         #[cfg(not(feature = "precompute_covariance_3d"))]
@@ -131,6 +157,11 @@ pub fn prepare_cloud(
         position_visibility,
         spherical_harmonics,
 
+        #[cfg(feature = "lighting")]
+        pbr_material: prepare_pbr_material(render_device, cloud),
+
+        filter_3d: prepare_filter_3d(render_device, cloud),
+
         #[cfg(feature = "precompute_covariance_3d")]
         covariance_3d_opacity,
         #[cfg(not(feature = "precompute_covariance_3d"))]
@@ -141,6 +172,45 @@ pub fn prepare_cloud(
 }
 
 
+/// clouds without pbr attributes bind a single absent material
+#[cfg(feature = "lighting")]
+fn prepare_pbr_material(
+    render_device: &RenderDevice,
+    cloud: &GaussianCloud,
+) -> Buffer {
+    let absent = [PbrMaterial::default()];
+    let pbr_material = match cloud.pbr_material.is_empty() {
+        true => absent.as_slice(),
+        false => cloud.pbr_material.as_slice(),
+    };
+
+    render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("planar_pbr_material_buffer"),
+        contents: bytemuck::cast_slice(pbr_material),
+        usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
+    })
+}
+
+
+/// unfiltered clouds bind a single zero filter
+fn prepare_filter_3d(
+    render_device: &RenderDevice,
+    cloud: &GaussianCloud,
+) -> Buffer {
+    let unfiltered = [0.0_f32];
+    let filter_3d = match cloud.filter_3d.is_empty() {
+        true => unfiltered.as_slice(),
+        false => cloud.filter_3d.as_slice(),
+    };
+
+    render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("planar_filter_3d_buffer"),
+        contents: bytemuck::cast_slice(filter_3d),
+        usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
+    })
+}
+
+
 #[cfg(feature = "f16")]
 pub fn get_bind_group_layout(
     render_device: &RenderDevice,
@@ -179,6 +249,27 @@ pub fn get_bind_group_layout(
                 },
                 count: None,
             },
+            #[cfg(feature = "lighting")]
+            BindGroupLayoutEntry {
+                binding: PBR_MATERIAL_BINDING,
+                visibility: ShaderStages::all(),
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(std::mem::size_of::<PbrMaterial>() as u64),
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: FILTER_3D_BINDING,
+                visibility: ShaderStages::all(),
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(std::mem::size_of::<f32>() as u64),
+                },
+                count: None,
+            },
         ],
     )
 }
@@ -232,6 +323,27 @@ pub fn get_bind_group_layout(
                 },
                 count: None,
             },
+            #[cfg(feature = "lighting")]
+            BindGroupLayoutEntry {
+                binding: PBR_MATERIAL_BINDING,
+                visibility: ShaderStages::all(),
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(std::mem::size_of::<PbrMaterial>() as u64),
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: FILTER_3D_BINDING,
+                visibility: ShaderStages::all(),
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(std::mem::size_of::<f32>() as u64),
+                },
+                count: None,
+            },
         ],
     )
 }
@@ -282,6 +394,23 @@ pub fn get_bind_group(
                     size: BufferSize::new(cloud.planar.rotation_scale_opacity.size()),
                 }),
             },
+            #[cfg(feature = "lighting")]
+            BindGroupEntry {
+                binding: PBR_MATERIAL_BINDING,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &cloud.planar.pbr_material,
+                    offset: 0,
+                    size: BufferSize::new(cloud.planar.pbr_material.size()),
+                }),
+            },
+            BindGroupEntry {
+                binding: FILTER_3D_BINDING,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &cloud.planar.filter_3d,
+                    offset: 0,
+                    size: BufferSize::new(cloud.planar.filter_3d.size()),
+                }),
+            },
         ],
     )
 }
@@ -329,6 +458,23 @@ pub fn get_bind_group(
                     size: BufferSize::new(cloud.planar.scale_opacity.size()),
                 }),
             },
+            #[cfg(feature = "lighting")]
+            BindGroupEntry {
+                binding: PBR_MATERIAL_BINDING,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &cloud.planar.pbr_material,
+                    offset: 0,
+                    size: BufferSize::new(cloud.planar.pbr_material.size()),
+                }),
+            },
+            BindGroupEntry {
+                binding: FILTER_3D_BINDING,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &cloud.planar.filter_3d,
+                    offset: 0,
+                    size: BufferSize::new(cloud.planar.filter_3d.size()),
+                }),
+            },
         ],
     )
 }
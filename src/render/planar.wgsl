@@ -2,12 +2,14 @@
 
 #ifdef PRECOMPUTE_COVARIANCE_3D
 #import bevy_gaussian_splatting::bindings::{
+    gaussian_uniforms,
     position_visibility,
     spherical_harmonics,
     covariance_3d_opacity,
 }
 #else
 #import bevy_gaussian_splatting::bindings::{
+    gaussian_uniforms,
     position_visibility,
     spherical_harmonics,
     rotation,
@@ -16,6 +18,10 @@
 }
 #endif
 
+#ifdef MIP_FILTER_3D
+#import bevy_gaussian_splatting::bindings::filter_3d
+#endif
+
 #import bevy_gaussian_splatting::spherical_harmonics::{
     spherical_harmonics_lookup,
     srgb_to_linear,
@@ -29,7 +35,7 @@ fn get_color(
     ray_direction: vec3<f32>,
 ) -> vec3<f32> {
     let sh = get_spherical_harmonics(index);
-    let color = spherical_harmonics_lookup(ray_direction, sh);
+    let color = spherical_harmonics_lookup(ray_direction, sh, gaussian_uniforms.max_sh_band);
     return srgb_to_linear(color);
 }
 
@@ -109,7 +115,7 @@ fn get_color(
     ray_direction: vec3<f32>,
 ) -> vec3<f32> {
     let sh = get_spherical_harmonics(index);
-    let color = spherical_harmonics_lookup(ray_direction, sh);
+    let color = spherical_harmonics_lookup(ray_direction, sh, gaussian_uniforms.max_sh_band);
     return srgb_to_linear(color);
 }
 
@@ -137,3 +143,11 @@ fn get_visibility(index: u32) -> f32 {
     return position_visibility[index].w;
 }
 #endif
+
+
+#ifdef MIP_FILTER_3D
+// standard deviation of the 3d smoothing filter in cloud space, zero for unfiltered clouds
+fn get_filter_3d(index: u32) -> f32 {
+    return filter_3d[min(index, arrayLength(&filter_3d) - 1u)];
+}
+#endif
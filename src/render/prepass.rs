@@ -0,0 +1,303 @@
+use bevy::{
+    prelude::*,
+    core_pipeline::core_3d::graph::{
+        Core3d,
+        Node3d,
+    },
+    ecs::query::QueryItem,
+    render::{
+        camera::ExtractedCamera,
+        extract_component::DynamicUniformIndex,
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+            ViewNode,
+            ViewNodeRunner,
+        },
+        render_phase::TrackedRenderPass,
+        render_resource::*,
+        renderer::RenderContext,
+        view::{
+            ExtractedView,
+            RenderVisibleEntities,
+            ViewDepthTexture,
+            ViewUniformOffset,
+        },
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+};
+
+use crate::{
+    camera::GaussianCamera,
+    gaussian::{
+        cloud::GaussianCloudHandle,
+        settings::{
+            GaussianCloudAppearance,
+            GaussianCloudBackend,
+            GaussianCloudDebugSettings,
+            GaussianCloudRasterSettings,
+        },
+    },
+    render::{
+        draw_gaussian_cloud,
+        draw_gaussian_cloud_unculled,
+        GaussianCloudBindGroup,
+        GaussianCloudPass,
+        GaussianCloudPipeline,
+        GaussianCloudPipelineKey,
+        GaussianCloudUniform,
+        GaussianUniformBindGroups,
+        GaussianViewBindGroup,
+        GpuGaussianCloud,
+    },
+    sort::{
+        GlobalSortMember,
+        GpuSortedEntry,
+        SortTrigger,
+        SortedEntriesHandle,
+    },
+};
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GaussianDepthPrepassLabel;
+
+
+#[derive(Default)]
+pub struct GaussianDepthPrepassPlugin;
+
+impl Plugin for GaussianDepthPrepassPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .add_render_graph_node::<ViewNodeRunner<GaussianDepthPrepassNode>>(
+                    Core3d,
+                    GaussianDepthPrepassLabel,
+                )
+                .add_render_graph_edges(
+                    Core3d,
+                    (
+                        Node3d::EndPrepasses,
+                        GaussianDepthPrepassLabel,
+                        Node3d::StartMainPass,
+                    ),
+                );
+
+            render_app.add_systems(
+                Render,
+                queue_depth_prepass.in_set(RenderSet::Queue),
+            );
+        }
+    }
+}
+
+
+/// clouds of a view drawn into the depth prepass, with their specialized pipelines
+#[derive(Component, Default)]
+pub struct GaussianDepthPrepassPhase {
+    pub items: Vec<(Entity, CachedRenderPipelineId)>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_depth_prepass(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    custom_pipeline: Res<GaussianCloudPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GaussianCloudPipeline>>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &RenderVisibleEntities,
+            Option<&Msaa>,
+        ),
+        With<GaussianCamera>,
+    >,
+    gaussian_clouds: Query<
+        (
+            &GaussianCloudAppearance,
+            &GaussianCloudRasterSettings,
+            &GaussianCloudDebugSettings,
+        ),
+        Without<GlobalSortMember>,
+    >,
+) {
+    for (
+        view_entity,
+        view,
+        visible_entities,
+        msaa,
+    ) in views.iter() {
+        let msaa = msaa.cloned().unwrap_or_default();
+
+        let items = visible_entities.iter::<With<GaussianCloudHandle>>()
+            .filter_map(|(render_entity, _)| {
+                let (
+                    appearance,
+                    raster_settings,
+                    debug_settings,
+                ) = gaussian_clouds.get(*render_entity).ok()?;
+
+                if !raster_settings.depth_prepass || raster_settings.backend != GaussianCloudBackend::Instanced {
+                    return None;
+                }
+
+                let key = GaussianCloudPipelineKey {
+                    sample_count: msaa.samples(),
+                    hdr: view.hdr,
+                    pass: GaussianCloudPass::DepthPrepass,
+                    ..GaussianCloudPipelineKey::from_settings(appearance, raster_settings, debug_settings)
+                };
+
+                let pipeline = pipelines.specialize(&pipeline_cache, &custom_pipeline, key);
+
+                Some((*render_entity, pipeline))
+            })
+            .collect();
+
+        commands.entity(view_entity).insert(GaussianDepthPrepassPhase { items });
+    }
+}
+
+
+#[derive(Default)]
+pub struct GaussianDepthPrepassNode;
+
+impl ViewNode for GaussianDepthPrepassNode {
+    type ViewQuery = (
+        &'static ExtractedCamera,
+        &'static ViewDepthTexture,
+        &'static GaussianCamera,
+        &'static GaussianViewBindGroup,
+        &'static ViewUniformOffset,
+        &'static SortTrigger,
+        &'static GaussianDepthPrepassPhase,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (
+            camera,
+            depth,
+            gaussian_camera,
+            view_bind_group,
+            view_uniform_offset,
+            sort_trigger,
+            phase,
+        ): QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        if gaussian_camera.warmup || phase.items.is_empty() {
+            return Ok(());
+        }
+
+        let Some(uniform_bind_group) = world.resource::<GaussianUniformBindGroups>().base_bind_group.as_ref() else {
+            return Ok(());
+        };
+
+        let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("gaussian cloud depth prepass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(depth.get_attachment(StoreOp::Store)),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        if let Some(viewport) = camera.viewport.as_ref() {
+            pass.set_camera_viewport(viewport);
+        }
+
+        pass.set_bind_group(
+            0,
+            &view_bind_group.value,
+            &[view_uniform_offset.offset],
+        );
+
+        draw_depth_phase(
+            &mut pass,
+            world,
+            uniform_bind_group,
+            sort_trigger.camera_index,
+            true,
+            &phase.items,
+        );
+
+        Ok(())
+    }
+}
+
+
+/// draws depth only clouds, the pass must have the view bind group set
+///
+/// `culled` limits each cloud to the gaussians the camera's sort kept in view.
+pub fn draw_depth_phase<'w>(
+    pass: &mut TrackedRenderPass<'w>,
+    world: &'w World,
+    uniform_bind_group: &'w BindGroup,
+    camera_index: usize,
+    culled: bool,
+    items: &[(Entity, CachedRenderPipelineId)],
+) {
+    let pipeline_cache = world.resource::<PipelineCache>();
+    let gaussian_clouds = world.resource::<RenderAssets<GpuGaussianCloud>>();
+    let sorted_entries = world.resource::<RenderAssets<GpuSortedEntry>>();
+
+    for (entity, pipeline_id) in items.iter() {
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else {
+            continue;
+        };
+
+        let Ok(entity) = world.get_entity(*entity) else {
+            continue;
+        };
+
+        let (
+            Some(handle),
+            Some(sorted_entries_handle),
+            Some(bind_groups),
+            Some(uniform_index),
+        ) = (
+            entity.get::<GaussianCloudHandle>(),
+            entity.get::<SortedEntriesHandle>(),
+            entity.get::<GaussianCloudBindGroup>(),
+            entity.get::<DynamicUniformIndex<GaussianCloudUniform>>(),
+        ) else {
+            continue;
+        };
+
+        let Some(gpu_gaussian_cloud) = gaussian_clouds.get(handle) else {
+            continue;
+        };
+
+        pass.set_render_pipeline(pipeline);
+        pass.set_bind_group(
+            1,
+            uniform_bind_group,
+            &[uniform_index.index()],
+        );
+
+        if culled {
+            draw_gaussian_cloud(
+                pass,
+                camera_index,
+                gpu_gaussian_cloud,
+                sorted_entries.get(sorted_entries_handle),
+                bind_groups,
+            );
+        } else {
+            draw_gaussian_cloud_unculled(
+                pass,
+                camera_index,
+                gpu_gaussian_cloud,
+                bind_groups,
+            );
+        }
+    }
+}
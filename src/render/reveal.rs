@@ -0,0 +1,118 @@
+use bevy::{
+    prelude::*,
+    asset::load_internal_asset,
+    render::render_resource::ShaderType,
+};
+
+
+const REVEAL_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(519083476);
+
+
+#[derive(Default)]
+pub struct RevealPlugin;
+
+impl Plugin for RevealPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            REVEAL_SHADER_HANDLE,
+            "reveal.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<GaussianCloudReveal>();
+        app.register_type::<GaussianCloudRevealMode>();
+    }
+}
+
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum GaussianCloudRevealMode {
+    /// front is a sphere growing around `origin`
+    #[default]
+    Radial,
+    /// front is a plane moving along `direction` from `extent` behind `origin` to `extent` ahead of it
+    Planar,
+}
+
+
+/// dissolves a cloud in along a moving front, gaussians ahead of the front are hidden
+///
+/// distances are measured in the local space of the cloud, animate `progress` to play the effect.
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GaussianCloudReveal {
+    pub mode: GaussianCloudRevealMode,
+    /// 0.0 hides every gaussian within `extent` of the origin, 1.0 reveals them
+    pub progress: f32,
+    pub origin: Vec3,
+    /// travel direction of a planar front
+    pub direction: Vec3,
+    /// distance covered by a radial front at full progress, half the sweep of a planar front
+    pub extent: f32,
+    /// width of the band behind the front over which gaussians fade in
+    pub feather: f32,
+}
+
+impl Default for GaussianCloudReveal {
+    fn default() -> Self {
+        Self {
+            mode: GaussianCloudRevealMode::default(),
+            progress: 1.0,
+            origin: Vec3::ZERO,
+            direction: Vec3::Y,
+            extent: 10.0,
+            feather: 0.5,
+        }
+    }
+}
+
+
+#[derive(Debug, Default, Clone, Copy, ShaderType)]
+pub struct GaussianRevealUniform {
+    pub origin: Vec3,
+    pub front: f32,
+    pub direction: Vec3,
+    pub feather: f32,
+    /// 0 disables the reveal, otherwise radial (1) or planar (2)
+    pub mode: u32,
+}
+
+impl From<&GaussianCloudReveal> for GaussianRevealUniform {
+    fn from(reveal: &GaussianCloudReveal) -> Self {
+        let feather = reveal.feather.max(1e-4);
+        let progress = reveal.progress.clamp(0.0, 1.0);
+
+        // planar fronts start behind the origin so gaussians on both sides of it are hidden at zero progress
+        let front = match reveal.mode {
+            GaussianCloudRevealMode::Radial => progress * (reveal.extent + feather),
+            GaussianCloudRevealMode::Planar => progress * (2.0 * reveal.extent + feather) - reveal.extent,
+        };
+
+        Self {
+            origin: reveal.origin,
+            front,
+            direction: reveal.direction.normalize_or(Vec3::Y),
+            feather,
+            mode: match reveal.mode {
+                GaussianCloudRevealMode::Radial => 1,
+                GaussianCloudRevealMode::Planar => 2,
+            },
+        }
+    }
+}
@@ -0,0 +1,27 @@
+#define_import_path bevy_gaussian_splatting::reveal
+
+#import bevy_gaussian_splatting::bindings::gaussian_uniforms
+
+
+const REVEAL_NONE: u32 = 0u;
+const REVEAL_RADIAL: u32 = 1u;
+const REVEAL_PLANAR: u32 = 2u;
+
+
+// opacity multiplier of a gaussian at a cloud space position, zero ahead of the reveal front
+fn reveal_opacity(local_position: vec3<f32>) -> f32 {
+    let reveal = gaussian_uniforms.reveal;
+
+    if (reveal.mode == REVEAL_NONE) {
+        return 1.0;
+    }
+
+    let offset = local_position - reveal.origin;
+
+    var distance = length(offset);
+    if (reveal.mode == REVEAL_PLANAR) {
+        distance = dot(offset, reveal.direction);
+    }
+
+    return 1.0 - smoothstep(reveal.front - reveal.feather, reveal.front, distance);
+}
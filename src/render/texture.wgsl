@@ -111,9 +111,11 @@ fn get_color(
         v5.y,
     );
 
-    color += shc[ 1] * r1 * ray_direction.y;
-    color += shc[ 2] * r2 * ray_direction.z;
-    color += shc[ 3] * r3 * ray_direction.x;
+    if (gaussian_uniforms.max_sh_band >= 1u) {
+        color += shc[ 1] * r1 * ray_direction.y;
+        color += shc[ 2] * r2 * ray_direction.z;
+        color += shc[ 3] * r3 * ray_direction.x;
+    }
 #endif
 
 #if SH_COEFF_COUNT > 26
@@ -159,11 +161,13 @@ fn get_color(
         v13.x,
     );
 
-    color += shc[ 4] * r4 * ray_direction.x * ray_direction.y;
-    color += shc[ 5] * r5 * ray_direction.y * ray_direction.z;
-    color += shc[ 6] * r6 * (2.0 * rds.z - rds.x - rds.y);
-    color += shc[ 7] * r7 * ray_direction.x * ray_direction.z;
-    color += shc[ 8] * r8 * (rds.x - rds.y);
+    if (gaussian_uniforms.max_sh_band >= 2u) {
+        color += shc[ 4] * r4 * ray_direction.x * ray_direction.y;
+        color += shc[ 5] * r5 * ray_direction.y * ray_direction.z;
+        color += shc[ 6] * r6 * (2.0 * rds.z - rds.x - rds.y);
+        color += shc[ 7] * r7 * ray_direction.x * ray_direction.z;
+        color += shc[ 8] * r8 * (rds.x - rds.y);
+    }
 #endif
 
 #if SH_COEFF_COUNT > 47
@@ -221,13 +225,15 @@ fn get_color(
         v23.y,
     );
 
-    color += shc[ 9] * r9 * ray_direction.y * (3.0 * rds.x - rds.y);
-    color += shc[10] * r10 * ray_direction.x * ray_direction.y * ray_direction.z;
-    color += shc[11] * r11 * ray_direction.y * (4.0 * rds.z - rds.x - rds.y);
-    color += shc[12] * r12 * ray_direction.z * (2.0 * rds.z - 3.0 * rds.x - 3.0 * rds.y);
-    color += shc[13] * r13 * ray_direction.x * (4.0 * rds.z - rds.x - rds.y);
-    color += shc[14] * r14 * ray_direction.z * (rds.x - rds.y);
-    color += shc[15] * r15 * ray_direction.x * (rds.x - 3.0 * rds.y);
+    if (gaussian_uniforms.max_sh_band >= 3u) {
+        color += shc[ 9] * r9 * ray_direction.y * (3.0 * rds.x - rds.y);
+        color += shc[10] * r10 * ray_direction.x * ray_direction.y * ray_direction.z;
+        color += shc[11] * r11 * ray_direction.y * (4.0 * rds.z - rds.x - rds.y);
+        color += shc[12] * r12 * ray_direction.z * (2.0 * rds.z - 3.0 * rds.x - 3.0 * rds.y);
+        color += shc[13] * r13 * ray_direction.x * (4.0 * rds.z - rds.x - rds.y);
+        color += shc[14] * r14 * ray_direction.z * (rds.x - rds.y);
+        color += shc[15] * r15 * ray_direction.x * (rds.x - 3.0 * rds.y);
+    }
 #endif
 
     return srgb_to_linear(color);
@@ -266,7 +272,7 @@ fn get_color(
     ray_direction: vec3<f32>,
 ) -> vec3<f32> {
     let sh = get_spherical_harmonics(index);
-    let color = spherical_harmonics_lookup(ray_direction, sh);
+    let color = spherical_harmonics_lookup(ray_direction, sh, gaussian_uniforms.max_sh_band);
     return srgb_to_linear(color);
 }
 #endif
@@ -0,0 +1,1007 @@
+use std::sync::{
+    atomic::{
+        AtomicU32,
+        AtomicU8,
+        Ordering,
+    },
+    Arc,
+};
+
+use bevy::{
+    prelude::*,
+    asset::load_internal_asset,
+    log::warn_once,
+    core_pipeline::{
+        core_3d::graph::{
+            Core3d,
+            Node3d,
+        },
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    render::{
+        extract_component::DynamicUniformIndex,
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+            ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::*,
+        renderer::{
+            RenderContext,
+            RenderDevice,
+        },
+        texture::{
+            CachedTexture,
+            GpuImage,
+            TextureCache,
+        },
+        view::{
+            ExtractedView,
+            RenderVisibleEntities,
+            ViewTarget,
+            ViewUniformOffset,
+        },
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+};
+
+use crate::{
+    camera::GaussianCamera,
+    gaussian::{
+        cloud::GaussianCloudHandle,
+        settings::{
+            GaussianCloudAppearance,
+            GaussianCloudBackend,
+            GaussianCloudDebugSettings,
+            GaussianCloudRasterSettings,
+        },
+    },
+    render::{
+        aov::{
+            copy_aov_targets,
+            GaussianAovTargets,
+        },
+        GaussianCloudBindGroup,
+        GaussianCloudPass,
+        GaussianCloudPipeline,
+        GaussianCloudPipelineKey,
+        GaussianCloudUniform,
+        GaussianUniformBindGroups,
+        GaussianViewBindGroup,
+        GpuGaussianCloud,
+        ShaderDefines,
+        shader_defs,
+    },
+    sort::GlobalSortMember,
+};
+
+
+const TILE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(451236234);
+const TILE_COMPOSITE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(451236235);
+
+/// size of `ProjectedGaussian` in `tile.wgsl`
+const PROJECTED_GAUSSIAN_SIZE: u64 = 80;
+
+pub const TILE_COLOR_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+pub const TILE_DEPTH_FORMAT: TextureFormat = TextureFormat::R32Float;
+pub const TILE_NORMAL_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+pub const TILE_ALPHA_FORMAT: TextureFormat = TextureFormat::R32Float;
+pub const TILE_GAUSSIAN_ID_FORMAT: TextureFormat = TextureFormat::Rg32Uint;
+
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct TileRasterizeLabel;
+
+
+#[derive(Default)]
+pub struct TileRasterizePlugin;
+
+impl Plugin for TileRasterizePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            TILE_SHADER_HANDLE,
+            "tile.wgsl",
+            Shader::from_wgsl
+        );
+
+        load_internal_asset!(
+            app,
+            TILE_COMPOSITE_SHADER_HANDLE,
+            "tile_composite.wgsl",
+            Shader::from_wgsl
+        );
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .add_render_graph_node::<ViewNodeRunner<TileRasterizeNode>>(
+                    Core3d,
+                    TileRasterizeLabel,
+                )
+                .add_render_graph_edges(
+                    Core3d,
+                    (
+                        Node3d::MainTransparentPass,
+                        TileRasterizeLabel,
+                        Node3d::EndMainPass,
+                    ),
+                );
+
+            render_app
+                .add_systems(
+                    Render,
+                    (
+                        queue_tile_pipelines.in_set(RenderSet::Queue),
+                        prepare_tile_buffers.in_set(RenderSet::PrepareResources),
+                        prepare_tile_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                        map_tile_entry_readbacks.in_set(RenderSet::Cleanup),
+                    ),
+                );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<TileRasterizePipeline>()
+                .init_resource::<SpecializedComputePipelines<TileRasterizePipeline>>()
+                .init_resource::<TileCompositePipeline>()
+                .init_resource::<SpecializedRenderPipelines<TileCompositePipeline>>();
+        }
+    }
+}
+
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum TileStage {
+    Preprocess,
+    Scan,
+    Binning,
+    Rasterize,
+}
+
+impl TileStage {
+    fn entry_point(&self) -> &'static str {
+        match self {
+            TileStage::Preprocess => "tile_preprocess",
+            TileStage::Scan => "tile_scan",
+            TileStage::Binning => "tile_binning",
+            TileStage::Rasterize => "tile_rasterize",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct TileRasterizeKey {
+    pub cloud: GaussianCloudPipelineKey,
+    pub stage: TileStage,
+    /// also write the normal, alpha and gaussian id outputs
+    pub aov: bool,
+}
+
+
+#[derive(Resource)]
+pub struct TileRasterizePipeline {
+    pub tile_layout: BindGroupLayout,
+    pub tile_aov_layout: BindGroupLayout,
+    pub view_layout: BindGroupLayout,
+    pub gaussian_uniform_layout: BindGroupLayout,
+    pub gaussian_cloud_layout: BindGroupLayout,
+}
+
+impl FromWorld for TileRasterizePipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+        let gaussian_cloud_pipeline = render_world.resource::<GaussianCloudPipeline>();
+
+        let storage_entry = |binding: u32| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let storage_texture_entry = |binding: u32, format: TextureFormat| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::StorageTexture {
+                access: StorageTextureAccess::WriteOnly,
+                format,
+                view_dimension: TextureViewDimension::D2,
+            },
+            count: None,
+        };
+
+        let tile_entries = [
+            storage_entry(0),
+            storage_entry(1),
+            storage_entry(2),
+            storage_texture_entry(3, TILE_COLOR_FORMAT),
+            storage_texture_entry(4, TILE_DEPTH_FORMAT),
+        ];
+
+        let tile_layout = render_device.create_bind_group_layout(
+            Some("tile_rasterize_layout"),
+            &tile_entries,
+        );
+
+        // requires `max_storage_textures_per_shader_stage` >= 5
+        let tile_aov_layout = render_device.create_bind_group_layout(
+            Some("tile_rasterize_aov_layout"),
+            &[
+                tile_entries.as_slice(),
+                &[
+                    storage_texture_entry(5, TILE_NORMAL_FORMAT),
+                    storage_texture_entry(6, TILE_ALPHA_FORMAT),
+                    storage_texture_entry(7, TILE_GAUSSIAN_ID_FORMAT),
+                ],
+            ].concat(),
+        );
+
+        TileRasterizePipeline {
+            tile_layout,
+            tile_aov_layout,
+            view_layout: gaussian_cloud_pipeline.view_layout.clone(),
+            gaussian_uniform_layout: gaussian_cloud_pipeline.gaussian_uniform_layout.clone(),
+            gaussian_cloud_layout: gaussian_cloud_pipeline.gaussian_cloud_layout.clone(),
+        }
+    }
+}
+
+impl SpecializedComputePipeline for TileRasterizePipeline {
+    type Key = TileRasterizeKey;
+
+    fn specialize(&self, key: Self::Key) -> ComputePipelineDescriptor {
+        let mut shader_defs = shader_defs(key.cloud);
+
+        let tile_layout = if key.aov {
+            shader_defs.push("TILE_AOV".into());
+            self.tile_aov_layout.clone()
+        } else {
+            self.tile_layout.clone()
+        };
+
+        ComputePipelineDescriptor {
+            label: Some(format!("gaussian cloud {}", key.stage.entry_point()).into()),
+            layout: vec![
+                self.view_layout.clone(),
+                self.gaussian_uniform_layout.clone(),
+                self.gaussian_cloud_layout.clone(),
+                tile_layout,
+            ],
+            push_constant_ranges: vec![],
+            shader: TILE_SHADER_HANDLE,
+            shader_defs,
+            entry_point: key.stage.entry_point().into(),
+            zero_initialize_workgroup_memory: true,
+        }
+    }
+}
+
+
+#[derive(Resource)]
+pub struct TileCompositePipeline {
+    pub composite_layout: BindGroupLayout,
+}
+
+impl FromWorld for TileCompositePipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+
+        let composite_layout = render_device.create_bind_group_layout(
+            Some("tile_composite_layout"),
+            &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: false },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        );
+
+        TileCompositePipeline {
+            composite_layout,
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for TileCompositePipeline {
+    type Key = bool;
+
+    fn specialize(&self, hdr: Self::Key) -> RenderPipelineDescriptor {
+        let format = if hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::Rgba8UnormSrgb
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("gaussian cloud tile composite pipeline".into()),
+            layout: vec![self.composite_layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: TILE_COMPOSITE_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: "fs_composite".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+
+pub struct TileStagePipelines {
+    pub preprocess: CachedComputePipelineId,
+    pub scan: CachedComputePipelineId,
+    pub binning: CachedComputePipelineId,
+    pub rasterize: CachedComputePipelineId,
+}
+
+#[derive(Component)]
+pub struct TileRasterizePipelines {
+    pub color: TileStagePipelines,
+    pub aov: TileStagePipelines,
+}
+
+#[derive(Component)]
+pub struct TileCompositePipelineId(pub CachedRenderPipelineId);
+
+#[allow(clippy::too_many_arguments)]
+fn queue_tile_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    tile_pipeline: Res<TileRasterizePipeline>,
+    mut tile_pipelines: ResMut<SpecializedComputePipelines<TileRasterizePipeline>>,
+    composite_pipeline: Res<TileCompositePipeline>,
+    mut composite_pipelines: ResMut<SpecializedRenderPipelines<TileCompositePipeline>>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+        ),
+        With<GaussianCamera>,
+    >,
+    gaussian_clouds: Query<(
+        Entity,
+        &GaussianCloudAppearance,
+        &GaussianCloudRasterSettings,
+        &GaussianCloudDebugSettings,
+    )>,
+) {
+    for (
+        entity,
+        appearance,
+        raster_settings,
+        debug_settings,
+    ) in gaussian_clouds.iter() {
+        if raster_settings.backend != GaussianCloudBackend::Tile {
+            continue;
+        }
+
+        let cloud = GaussianCloudPipelineKey {
+            sample_count: 1,
+            hdr: false,
+            pass: GaussianCloudPass::Color,
+            ..GaussianCloudPipelineKey::from_settings(appearance, raster_settings, debug_settings)
+        };
+
+        let mut specialize = |aov| {
+            let mut specialize_stage = |stage| tile_pipelines.specialize(
+                &pipeline_cache,
+                &tile_pipeline,
+                TileRasterizeKey {
+                    cloud,
+                    stage,
+                    aov,
+                },
+            );
+
+            TileStagePipelines {
+                preprocess: specialize_stage(TileStage::Preprocess),
+                scan: specialize_stage(TileStage::Scan),
+                binning: specialize_stage(TileStage::Binning),
+                rasterize: specialize_stage(TileStage::Rasterize),
+            }
+        };
+
+        commands.entity(entity).insert(TileRasterizePipelines {
+            color: specialize(false),
+            aov: specialize(true),
+        });
+    }
+
+    for (
+        entity,
+        view,
+    ) in views.iter() {
+        let pipeline = composite_pipelines.specialize(
+            &pipeline_cache,
+            &composite_pipeline,
+            view.hdr,
+        );
+
+        commands.entity(entity).insert(TileCompositePipelineId(pipeline));
+    }
+}
+
+
+const TILE_READBACK_IDLE: u8 = 0;
+const TILE_READBACK_COPIED: u8 = 1;
+const TILE_READBACK_MAPPING: u8 = 2;
+
+#[derive(Component)]
+pub struct TileRasterizeBuffers {
+    pub projected: Buffer,
+    /// required entry count, then per tile counts and per tile cursors into `tile_entries`
+    pub tile_bins: Buffer,
+    /// gaussian indices of every tile, in tile order
+    pub tile_entries: Buffer,
+    pub gaussian_capacity: usize,
+    pub tile_count: usize,
+    pub entry_capacity: usize,
+    /// largest entry count required by a cloud of the view, read back from `tile_bins`
+    pub required_entries: Arc<AtomicU32>,
+    readback: Buffer,
+    readback_state: Arc<AtomicU8>,
+}
+
+impl TileRasterizeBuffers {
+    pub fn new(
+        render_device: &RenderDevice,
+        gaussian_capacity: usize,
+        tile_count: usize,
+        entry_capacity: usize,
+    ) -> Self {
+        let projected = render_device.create_buffer(&BufferDescriptor {
+            label: Some("tile projected gaussians buffer"),
+            size: gaussian_capacity as u64 * PROJECTED_GAUSSIAN_SIZE,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let tile_bins = render_device.create_buffer(&BufferDescriptor {
+            label: Some("tile bins buffer"),
+            size: ((1 + 2 * tile_count) * std::mem::size_of::<u32>()) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let tile_entries = render_device.create_buffer(&BufferDescriptor {
+            label: Some("tile entries buffer"),
+            size: (entry_capacity.max(1) * std::mem::size_of::<u32>()) as u64,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let readback = render_device.create_buffer(&BufferDescriptor {
+            label: Some("tile required entries readback buffer"),
+            size: std::mem::size_of::<u32>() as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            projected,
+            tile_bins,
+            tile_entries,
+            gaussian_capacity,
+            tile_count,
+            entry_capacity,
+            required_entries: Arc::new(AtomicU32::new(0)),
+            readback,
+            readback_state: Arc::new(AtomicU8::new(TILE_READBACK_IDLE)),
+        }
+    }
+}
+
+/// per-pixel outputs of the tile rasterizer for the most recently rasterized cloud of a view
+#[derive(Component)]
+pub struct TileRasterizeTextures {
+    /// premultiplied color, alpha holds accumulated opacity (1 - transmittance)
+    pub color: CachedTexture,
+    /// expected view-space depth
+    pub depth: CachedTexture,
+    /// written for views with `GaussianAovTargets`
+    pub aov: Option<TileAovTextures>,
+}
+
+pub struct TileAovTextures {
+    /// expected world-space normal, alpha holds accumulated opacity
+    pub normal: CachedTexture,
+    pub alpha: CachedTexture,
+    /// index of the gaussian with the largest blending weight and the main world entity index of
+    /// its cloud, `u32::MAX` where none contributes
+    pub gaussian_id: CachedTexture,
+}
+
+pub fn tile_grid(viewport_size: UVec2) -> UVec2 {
+    let tile_size = ShaderDefines::default().tile_size;
+    viewport_size.map(|extent| extent.div_ceil(tile_size))
+}
+
+fn tile_clouds<'w>(
+    world: &'w World,
+    visible_entities: &'w RenderVisibleEntities,
+) -> impl Iterator<Item = EntityRef<'w>> {
+    visible_entities.iter::<With<GaussianCloudHandle>>()
+        .filter_map(|(render_entity, _)| world.get_entity(*render_entity).ok())
+        .filter(|entity| !entity.contains::<GlobalSortMember>())
+        .filter(|entity| {
+            entity.get::<GaussianCloudRasterSettings>()
+                .is_some_and(|raster_settings| raster_settings.backend == GaussianCloudBackend::Tile)
+        })
+}
+
+#[allow(clippy::type_complexity)]
+fn prepare_tile_buffers(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    mut texture_cache: ResMut<TextureCache>,
+    gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &RenderVisibleEntities,
+            Option<&TileRasterizeBuffers>,
+            Has<GaussianAovTargets>,
+        ),
+        With<GaussianCamera>,
+    >,
+    gaussian_clouds: Query<(
+        &GaussianCloudHandle,
+        &GaussianCloudRasterSettings,
+    )>,
+) {
+    for (
+        entity,
+        view,
+        visible_entities,
+        buffers,
+        aov,
+    ) in views.iter() {
+        let defines = ShaderDefines::default();
+
+        let gaussian_count = visible_entities.iter::<With<GaussianCloudHandle>>()
+            .filter_map(|(render_entity, _)| gaussian_clouds.get(*render_entity).ok())
+            .filter(|(_, raster_settings)| raster_settings.backend == GaussianCloudBackend::Tile)
+            .filter_map(|(handle, _)| gaussian_cloud_res.get(handle))
+            .map(|cloud| cloud.count)
+            .max()
+            .unwrap_or(0);
+
+        if gaussian_count == 0 {
+            continue;
+        }
+
+        let viewport_size = view.viewport.zw().max(UVec2::ONE);
+        let grid = tile_grid(viewport_size);
+        let tile_count = (grid.x * grid.y) as usize;
+
+        // starts at `tile_max_gaussians` per tile and grows to the largest entry count read back
+        let required_entries = buffers
+            .map(|buffers| buffers.required_entries.load(Ordering::Relaxed) as usize)
+            .unwrap_or(0);
+        let entry_capacity = (tile_count * defines.tile_max_gaussians as usize)
+            .max(required_entries.next_power_of_two());
+
+        let reuse_buffers = buffers.is_some_and(|buffers| {
+            buffers.gaussian_capacity >= gaussian_count
+                && buffers.tile_count == tile_count
+                && buffers.entry_capacity >= required_entries
+        });
+
+        if !reuse_buffers {
+            let buffers = TileRasterizeBuffers::new(
+                &render_device,
+                gaussian_count,
+                tile_count,
+                entry_capacity,
+            );
+            buffers.required_entries.store(required_entries as u32, Ordering::Relaxed);
+
+            commands.entity(entity).insert(buffers);
+        }
+
+        let mut texture = |label: &'static str, format: TextureFormat| texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some(label),
+                size: Extent3d {
+                    width: viewport_size.x,
+                    height: viewport_size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC,
+                view_formats: &[],
+            },
+        );
+
+        let aov = aov.then(|| TileAovTextures {
+            normal: texture("tile_normal_texture", TILE_NORMAL_FORMAT),
+            alpha: texture("tile_alpha_texture", TILE_ALPHA_FORMAT),
+            gaussian_id: texture("tile_gaussian_id_texture", TILE_GAUSSIAN_ID_FORMAT),
+        });
+
+        commands.entity(entity).insert(TileRasterizeTextures {
+            color: texture("tile_color_texture", TILE_COLOR_FORMAT),
+            depth: texture("tile_depth_texture", TILE_DEPTH_FORMAT),
+            aov,
+        });
+    }
+}
+
+
+fn map_tile_entry_readbacks(
+    render_device: Res<RenderDevice>,
+    views: Query<&TileRasterizeBuffers>,
+) {
+    for buffers in views.iter() {
+        if buffers.readback_state.compare_exchange(
+            TILE_READBACK_COPIED,
+            TILE_READBACK_MAPPING,
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+        ).is_err() {
+            continue;
+        }
+
+        let mapped_buffer = buffers.readback.clone();
+        let required_entries = buffers.required_entries.clone();
+        let readback_state = buffers.readback_state.clone();
+
+        buffers.readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            if result.is_ok() {
+                let required = {
+                    let data = mapped_buffer.slice(..).get_mapped_range();
+                    bytemuck::cast_slice::<u8, u32>(&data)[0]
+                };
+                mapped_buffer.unmap();
+
+                required_entries.store(required, Ordering::Relaxed);
+            }
+
+            readback_state.store(TILE_READBACK_IDLE, Ordering::Release);
+        });
+    }
+
+    render_device.poll(wgpu::Maintain::Poll);
+}
+
+
+#[derive(Component)]
+pub struct TileRasterizeBindGroups {
+    pub tile_bind_group: BindGroup,
+    pub composite_bind_group: BindGroup,
+}
+
+fn prepare_tile_bind_groups(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    tile_pipeline: Res<TileRasterizePipeline>,
+    composite_pipeline: Res<TileCompositePipeline>,
+    views: Query<(
+        Entity,
+        &TileRasterizeBuffers,
+        &TileRasterizeTextures,
+    )>,
+) {
+    for (
+        entity,
+        buffers,
+        textures,
+    ) in views.iter() {
+        let mut entries = vec![
+            BindGroupEntry {
+                binding: 0,
+                resource: buffers.projected.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: buffers.tile_bins.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: buffers.tile_entries.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 3,
+                resource: BindingResource::TextureView(&textures.color.default_view),
+            },
+            BindGroupEntry {
+                binding: 4,
+                resource: BindingResource::TextureView(&textures.depth.default_view),
+            },
+        ];
+
+        if let Some(aov) = textures.aov.as_ref() {
+            entries.extend([
+                BindGroupEntry {
+                    binding: 5,
+                    resource: BindingResource::TextureView(&aov.normal.default_view),
+                },
+                BindGroupEntry {
+                    binding: 6,
+                    resource: BindingResource::TextureView(&aov.alpha.default_view),
+                },
+                BindGroupEntry {
+                    binding: 7,
+                    resource: BindingResource::TextureView(&aov.gaussian_id.default_view),
+                },
+            ]);
+        }
+
+        let tile_layout = if textures.aov.is_some() {
+            &tile_pipeline.tile_aov_layout
+        } else {
+            &tile_pipeline.tile_layout
+        };
+
+        let tile_bind_group = render_device.create_bind_group(
+            "tile_rasterize_bind_group",
+            tile_layout,
+            &entries,
+        );
+
+        let composite_bind_group = render_device.create_bind_group(
+            "tile_composite_bind_group",
+            &composite_pipeline.composite_layout,
+            &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&textures.color.default_view),
+                },
+            ],
+        );
+
+        commands.entity(entity).insert(TileRasterizeBindGroups {
+            tile_bind_group,
+            composite_bind_group,
+        });
+    }
+}
+
+
+#[derive(Default)]
+pub struct TileRasterizeNode;
+
+impl ViewNode for TileRasterizeNode {
+    type ViewQuery = (
+        &'static ExtractedView,
+        &'static ViewTarget,
+        &'static RenderVisibleEntities,
+        &'static GaussianCamera,
+        &'static GaussianViewBindGroup,
+        &'static ViewUniformOffset,
+        &'static TileRasterizeBuffers,
+        &'static TileRasterizeBindGroups,
+        &'static TileRasterizeTextures,
+        &'static TileCompositePipelineId,
+        Option<&'static GaussianAovTargets>,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (
+            view,
+            view_target,
+            visible_entities,
+            gaussian_camera,
+            view_bind_group,
+            view_uniform_offset,
+            buffers,
+            bind_groups,
+            textures,
+            composite_pipeline_id,
+            aov_targets,
+        ): QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        if gaussian_camera.warmup {
+            return Ok(());
+        }
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let gaussian_uniforms = world.resource::<GaussianUniformBindGroups>();
+        let gaussian_cloud_res = world.resource::<RenderAssets<GpuGaussianCloud>>();
+
+        let Some(uniform_bind_group) = gaussian_uniforms.base_bind_group.as_ref() else {
+            return Ok(());
+        };
+
+        let Some(composite_pipeline) = pipeline_cache.get_render_pipeline(composite_pipeline_id.0) else {
+            return Ok(());
+        };
+
+        let defines = ShaderDefines::default();
+        let grid = tile_grid(view.viewport.zw().max(UVec2::ONE));
+
+        // each cloud is composited over the previous ones, so draw them back to front
+        let view_position = view.world_from_view.translation();
+        let mut clouds = tile_clouds(world, visible_entities)
+            .map(|entity| {
+                let distance = entity.get::<GaussianCloudUniform>()
+                    .map(|uniform| uniform.transform.w_axis.truncate().distance_squared(view_position))
+                    .unwrap_or(0.0);
+
+                (entity, distance)
+            })
+            .collect::<Vec<_>>();
+        clouds.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        let cloud_count = clouds.len();
+
+        render_context.command_encoder().clear_buffer(&buffers.tile_bins, 0, None);
+
+        for (cloud_entity, _) in clouds {
+            let (
+                Some(handle),
+                Some(cloud_bind_group),
+                Some(uniform_index),
+                Some(pipelines),
+            ) = (
+                cloud_entity.get::<GaussianCloudHandle>(),
+                cloud_entity.get::<GaussianCloudBindGroup>(),
+                cloud_entity.get::<DynamicUniformIndex<GaussianCloudUniform>>(),
+                cloud_entity.get::<TileRasterizePipelines>(),
+            ) else {
+                continue;
+            };
+
+            let Some(cloud) = gaussian_cloud_res.get(handle) else {
+                continue;
+            };
+
+            if cloud.count > buffers.gaussian_capacity {
+                continue;
+            }
+
+            // the bind group layout follows the view's textures, see `prepare_tile_bind_groups`
+            let stage_pipelines = if textures.aov.is_some() {
+                &pipelines.aov
+            } else {
+                &pipelines.color
+            };
+
+            let (
+                Some(preprocess),
+                Some(scan),
+                Some(binning),
+                Some(rasterize),
+            ) = (
+                pipeline_cache.get_compute_pipeline(stage_pipelines.preprocess),
+                pipeline_cache.get_compute_pipeline(stage_pipelines.scan),
+                pipeline_cache.get_compute_pipeline(stage_pipelines.binning),
+                pipeline_cache.get_compute_pipeline(stage_pipelines.rasterize),
+            ) else {
+                continue;
+            };
+
+            let command_encoder = render_context.command_encoder();
+            // keeps the required entry count of previous clouds
+            command_encoder.clear_buffer(&buffers.tile_bins, std::mem::size_of::<u32>() as u64, None);
+
+            {
+                let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("gaussian cloud tile rasterize"),
+                    timestamp_writes: None,
+                });
+
+                pass.set_bind_group(
+                    0,
+                    &view_bind_group.value,
+                    &[view_uniform_offset.offset],
+                );
+                pass.set_bind_group(
+                    1,
+                    uniform_bind_group,
+                    &[uniform_index.index()],
+                );
+                pass.set_bind_group(
+                    2,
+                    &cloud_bind_group.cloud_bind_group,
+                    &[],
+                );
+                pass.set_bind_group(
+                    3,
+                    &bind_groups.tile_bind_group,
+                    &[],
+                );
+
+                let workgroups = (cloud.count as u32).div_ceil(defines.tile_workgroup_invocations);
+
+                pass.set_pipeline(preprocess);
+                pass.dispatch_workgroups(workgroups, 1, 1);
+
+                pass.set_pipeline(scan);
+                pass.dispatch_workgroups(1, 1, 1);
+
+                pass.set_pipeline(binning);
+                pass.dispatch_workgroups(workgroups, 1, 1);
+
+                pass.set_pipeline(rasterize);
+                pass.dispatch_workgroups(grid.x, grid.y, 1);
+            }
+
+            let mut composite_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("gaussian cloud tile composite"),
+                color_attachments: &[Some(view_target.get_unsampled_color_attachment())],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            composite_pass.set_viewport(
+                view.viewport.x as f32,
+                view.viewport.y as f32,
+                view.viewport.z as f32,
+                view.viewport.w as f32,
+                0.0,
+                1.0,
+            );
+            composite_pass.set_render_pipeline(composite_pipeline);
+            composite_pass.set_bind_group(0, &bind_groups.composite_bind_group, &[]);
+            composite_pass.draw(0..3, 0..1);
+        }
+
+        if buffers.readback_state.compare_exchange(
+            TILE_READBACK_IDLE,
+            TILE_READBACK_COPIED,
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+        ).is_ok() {
+            render_context.command_encoder().copy_buffer_to_buffer(
+                &buffers.tile_bins,
+                0,
+                &buffers.readback,
+                0,
+                std::mem::size_of::<u32>() as u64,
+            );
+        }
+
+        if let Some(aov_targets) = aov_targets {
+            if cloud_count > 1 {
+                warn_once!("gaussian aov targets only hold the nearest of several tile clouds, merge them with `GlobalSort`");
+            }
+
+            copy_aov_targets(
+                render_context.command_encoder(),
+                world.resource::<RenderAssets<GpuImage>>(),
+                textures,
+                aov_targets,
+            );
+        }
+
+        Ok(())
+    }
+}
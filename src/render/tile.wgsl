@@ -0,0 +1,509 @@
+#import bevy_gaussian_splatting::bindings::{
+    view,
+    gaussian_uniforms,
+}
+#import bevy_gaussian_splatting::color_grading::color_grade
+#import bevy_gaussian_splatting::gaussian_3d::compute_cov2d_3dgs
+#import bevy_gaussian_splatting::helpers::get_rotation_matrix
+#import bevy_gaussian_splatting::reveal::reveal_opacity
+#import bevy_gaussian_splatting::volume_mask::{
+    volume_mask_hidden,
+    volume_mask_selected,
+}
+#import bevy_gaussian_splatting::transform::{
+    world_to_clip,
+    in_frustum,
+}
+
+#ifdef NOISE_MATERIAL
+#import bevy_gaussian_splatting::noise_material::{
+    noise_material_color,
+    noise_material_position,
+}
+#endif
+
+#ifdef PACKED
+#import bevy_gaussian_splatting::packed::{
+    get_position,
+    get_color,
+    get_visibility,
+    get_opacity,
+}
+#ifndef PRECOMPUTE_COVARIANCE_3D
+#import bevy_gaussian_splatting::packed::{
+    get_rotation,
+    get_scale,
+}
+#endif
+#else
+
+#ifdef BUFFER_STORAGE
+#import bevy_gaussian_splatting::planar::{
+    get_position,
+    get_color,
+    get_visibility,
+    get_opacity,
+}
+#ifndef PRECOMPUTE_COVARIANCE_3D
+#import bevy_gaussian_splatting::planar::{
+    get_rotation,
+    get_scale,
+}
+#endif
+#endif
+
+#endif
+
+#ifdef BUFFER_TEXTURE
+#import bevy_gaussian_splatting::texture::{
+    get_position,
+    get_color,
+    get_visibility,
+    get_opacity,
+}
+#ifndef PRECOMPUTE_COVARIANCE_3D
+#import bevy_gaussian_splatting::texture::{
+    get_rotation,
+    get_scale,
+}
+#endif
+#endif
+
+
+struct ProjectedGaussian {
+    mean_2d: vec2<f32>,
+    depth: f32,
+    radius: f32,
+    conic_opacity: vec4<f32>,
+    color: vec4<f32>,
+    normal: vec4<f32>,
+    tile_rect: vec4<u32>,
+};
+
+// per tile gaussian counts followed by per tile cursors into `tile_entries`, the cursors start at
+// the exclusive prefix sum of the counts and end at the end of each tile's entry range
+struct TileBins {
+    required_entries: atomic<u32>,
+    bins: array<atomic<u32>>,
+};
+
+@group(3) @binding(0) var<storage, read_write> projected: array<ProjectedGaussian>;
+@group(3) @binding(1) var<storage, read_write> tile_bins: TileBins;
+@group(3) @binding(2) var<storage, read_write> tile_entries: array<u32>;
+@group(3) @binding(3) var output_color: texture_storage_2d<rgba16float, write>;
+@group(3) @binding(4) var output_depth: texture_storage_2d<r32float, write>;
+
+#ifdef TILE_AOV
+@group(3) @binding(5) var output_normal: texture_storage_2d<rgba16float, write>;
+@group(3) @binding(6) var output_alpha: texture_storage_2d<r32float, write>;
+@group(3) @binding(7) var output_gaussian_id: texture_storage_2d<rg32uint, write>;
+#endif
+
+const NO_GAUSSIAN_ID: u32 = 0xFFFFFFFFu;
+
+
+fn tile_grid() -> vec2<u32> {
+    let viewport = vec2<u32>(view.viewport.zw);
+    return (viewport + vec2<u32>(#{TILE_SIZE}u - 1u)) / #{TILE_SIZE}u;
+}
+
+fn tile_count() -> u32 {
+    let grid = tile_grid();
+    return grid.x * grid.y;
+}
+
+fn tile_cursor(tile: u32) -> u32 {
+    return tile_count() + tile;
+}
+
+// front to back, ties broken by index to keep the order stable between frames
+fn entry_before(a: u32, b: u32) -> bool {
+    let depth_a = projected[a].depth;
+    let depth_b = projected[b].depth;
+    return depth_a < depth_b || (depth_a == depth_b && a < b);
+}
+
+// world space axis of least extent, facing the camera
+fn gaussian_normal(index: u32, position: vec3<f32>) -> vec3<f32> {
+#ifdef PRECOMPUTE_COVARIANCE_3D
+    return vec3<f32>(0.0);
+#else
+    let scale = get_scale(index);
+    let axes = transpose(get_rotation_matrix(get_rotation(index)));
+
+    var axis = 0u;
+    if (scale.y < scale[axis]) {
+        axis = 1u;
+    }
+    if (scale.z < scale[axis]) {
+        axis = 2u;
+    }
+
+    let T = mat3x3<f32>(
+        gaussian_uniforms.transform[0].xyz,
+        gaussian_uniforms.transform[1].xyz,
+        gaussian_uniforms.transform[2].xyz,
+    );
+    let normal = normalize(T * axes[axis]);
+
+    return select(normal, -normal, dot(normal, view.world_position - position) < 0.0);
+#endif
+}
+
+fn empty_projection() -> ProjectedGaussian {
+    var output: ProjectedGaussian;
+    output.radius = 0.0;
+    output.tile_rect = vec4<u32>(0u);
+    return output;
+}
+
+
+@compute @workgroup_size(#{TILE_WORKGROUP_INVOCATIONS})
+fn tile_preprocess(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+) {
+    let index = global_id.x;
+    if (index >= gaussian_uniforms.count) {
+        return;
+    }
+
+#ifdef NOISE_MATERIAL
+    let position = vec4<f32>(noise_material_position(get_position(index)), 1.0);
+#else
+    let position = vec4<f32>(get_position(index), 1.0);
+#endif
+    let transformed_position = (gaussian_uniforms.transform * position).xyz;
+    let projected_position = world_to_clip(transformed_position);
+
+    var discard_gaussian = !in_frustum(projected_position.xyz);
+
+    discard_gaussian |= volume_mask_hidden(transformed_position);
+
+#ifdef DRAW_SELECTED
+    discard_gaussian |= !volume_mask_selected(transformed_position, get_visibility(index) > 0.5);
+#endif
+
+    var opacity = get_opacity(index) * gaussian_uniforms.global_opacity * reveal_opacity(position.xyz);
+    discard_gaussian |= opacity < 1.0 / 255.0;
+
+    if (discard_gaussian) {
+        projected[index] = empty_projection();
+        return;
+    }
+
+    // cov2d is expressed in half-pixel units, see `compute_cov2d_3dgs`
+    let filtered_cov2d = compute_cov2d_3dgs(transformed_position, index);
+    let cov2d = filtered_cov2d.xyz * 0.25;
+    opacity *= filtered_cov2d.w;
+
+    let det = cov2d.x * cov2d.z - cov2d.y * cov2d.y;
+    if (det <= 0.0) {
+        projected[index] = empty_projection();
+        return;
+    }
+
+    let det_inv = 1.0 / det;
+    let conic = vec3<f32>(
+        cov2d.z * det_inv,
+        -cov2d.y * det_inv,
+        cov2d.x * det_inv,
+    );
+
+    let mid = 0.5 * (cov2d.x + cov2d.z);
+    let lambda = mid + sqrt(max(0.1, mid * mid - det));
+
+#ifdef OPACITY_ADAPTIVE_RADIUS
+    let cutoff = sqrt(max(9.0 + 2.0 * log(opacity), 0.000001));
+#else
+    let cutoff = 3.0;
+#endif
+
+    let radius = ceil(cutoff * sqrt(lambda));
+
+    let viewport = view.viewport.zw;
+    let mean_2d = vec2<f32>(
+        (projected_position.x + 1.0) * 0.5 * viewport.x,
+        (1.0 - projected_position.y) * 0.5 * viewport.y,
+    );
+
+    let grid = tile_grid();
+    let rect_min = clamp(
+        vec2<i32>(floor((mean_2d - radius) / f32(#{TILE_SIZE}))),
+        vec2<i32>(0),
+        vec2<i32>(grid),
+    );
+    let rect_max = clamp(
+        vec2<i32>(ceil((mean_2d + radius) / f32(#{TILE_SIZE}))),
+        vec2<i32>(0),
+        vec2<i32>(grid),
+    );
+
+    let ray_direction = normalize(transformed_position - view.world_position);
+    let view_position = view.view_from_world * vec4<f32>(transformed_position, 1.0);
+
+    var output: ProjectedGaussian;
+    output.mean_2d = mean_2d;
+    output.depth = -view_position.z;
+    output.radius = radius;
+    output.conic_opacity = vec4<f32>(conic, opacity);
+    var rgb = get_color(index, ray_direction);
+#ifdef NOISE_MATERIAL
+    rgb = noise_material_color(rgb, position.xyz);
+#endif
+    output.color = vec4<f32>(color_grade(rgb), 1.0);
+    output.normal = vec4<f32>(gaussian_normal(index, transformed_position), 0.0);
+    output.tile_rect = vec4<u32>(
+        vec2<u32>(rect_min),
+        vec2<u32>(rect_max),
+    );
+
+#ifdef HIGHLIGHT_SELECTED
+    if (volume_mask_selected(transformed_position, get_visibility(index) > 0.5)) {
+        output.color = vec4<f32>(0.3, 1.0, 0.1, 1.0);
+    }
+#endif
+
+    projected[index] = output;
+
+    for (var y = u32(rect_min.y); y < u32(rect_max.y); y = y + 1u) {
+        for (var x = u32(rect_min.x); x < u32(rect_max.x); x = x + 1u) {
+            atomicAdd(&tile_bins.bins[y * grid.x + x], 1u);
+        }
+    }
+}
+
+
+var<workgroup> scan_sums: array<u32, #{TILE_WORKGROUP_INVOCATIONS}>;
+
+// single workgroup exclusive prefix sum of the tile counts into the tile cursors
+@compute @workgroup_size(#{TILE_WORKGROUP_INVOCATIONS})
+fn tile_scan(
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    let tiles = tile_count();
+    let chunk = (tiles + #{TILE_WORKGROUP_INVOCATIONS}u - 1u) / #{TILE_WORKGROUP_INVOCATIONS}u;
+    let chunk_start = min(local_index * chunk, tiles);
+    let chunk_end = min(chunk_start + chunk, tiles);
+
+    var sum = 0u;
+    for (var tile = chunk_start; tile < chunk_end; tile = tile + 1u) {
+        sum += atomicLoad(&tile_bins.bins[tile]);
+    }
+
+    scan_sums[local_index] = sum;
+    workgroupBarrier();
+
+    // inclusive hillis-steele scan of the chunk sums
+    for (var offset = 1u; offset < #{TILE_WORKGROUP_INVOCATIONS}u; offset = offset << 1u) {
+        var value = scan_sums[local_index];
+        if (local_index >= offset) {
+            value += scan_sums[local_index - offset];
+        }
+        workgroupBarrier();
+        scan_sums[local_index] = value;
+        workgroupBarrier();
+    }
+
+    var cursor = scan_sums[local_index] - sum;
+    for (var tile = chunk_start; tile < chunk_end; tile = tile + 1u) {
+        atomicStore(&tile_bins.bins[tile_cursor(tile)], cursor);
+        cursor += atomicLoad(&tile_bins.bins[tile]);
+    }
+
+    if (local_index == #{TILE_WORKGROUP_INVOCATIONS}u - 1u) {
+        atomicMax(&tile_bins.required_entries, scan_sums[local_index]);
+    }
+}
+
+
+@compute @workgroup_size(#{TILE_WORKGROUP_INVOCATIONS})
+fn tile_binning(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+) {
+    let index = global_id.x;
+    if (index >= gaussian_uniforms.count) {
+        return;
+    }
+
+    let rect = projected[index].tile_rect;
+    let grid = tile_grid();
+
+    for (var y = rect.y; y < rect.w; y = y + 1u) {
+        for (var x = rect.x; x < rect.z; x = x + 1u) {
+            let slot = atomicAdd(&tile_bins.bins[tile_cursor(y * grid.x + x)], 1u);
+
+            // `required_entries` is read back to grow the entry buffer when it overflows
+            if (slot < arrayLength(&tile_entries)) {
+                tile_entries[slot] = index;
+            }
+        }
+    }
+}
+
+
+var<workgroup> tile_depths: array<f32, #{TILE_MAX_GAUSSIANS}>;
+var<workgroup> tile_indices: array<u32, #{TILE_MAX_GAUSSIANS}>;
+var<workgroup> tile_range: vec2<u32>;
+
+@compute @workgroup_size(#{TILE_SIZE}, #{TILE_SIZE})
+fn tile_rasterize(
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    let grid = tile_grid();
+    let tile = workgroup_id.y * grid.x + workgroup_id.x;
+
+    if (local_index == 0u) {
+        let capacity = arrayLength(&tile_entries);
+        let end = atomicLoad(&tile_bins.bins[tile_cursor(tile)]);
+        let start = end - atomicLoad(&tile_bins.bins[tile]);
+        tile_range = vec2<u32>(min(start, capacity), min(end, capacity) - min(start, capacity));
+    }
+    let range = workgroupUniformLoad(&tile_range);
+    let start = range.x;
+    let count = range.y;
+
+    // tiles beyond the workgroup capacity are sorted in place in global memory
+    let in_workgroup = count <= #{TILE_MAX_GAUSSIANS}u;
+
+    if (in_workgroup) {
+        for (var i = local_index; i < #{TILE_MAX_GAUSSIANS}u; i = i + #{TILE_WORKGROUP_INVOCATIONS}u) {
+            if (i < count) {
+                let index = tile_entries[start + i];
+                tile_indices[i] = index;
+                tile_depths[i] = projected[index].depth;
+            } else {
+                tile_indices[i] = 0u;
+                tile_depths[i] = 3.40282e+38;
+            }
+        }
+        workgroupBarrier();
+
+        // bitonic sort of the tile list, front to back
+        for (var k = 2u; k <= #{TILE_MAX_GAUSSIANS}u; k = k << 1u) {
+            for (var j = k >> 1u; j > 0u; j = j >> 1u) {
+                for (var t = local_index; t < #{TILE_MAX_GAUSSIANS}u / 2u; t = t + #{TILE_WORKGROUP_INVOCATIONS}u) {
+                    let a = 2u * j * (t / j) + (t % j);
+                    let b = a + j;
+                    let ascending = (a & k) == 0u;
+
+                    if ((tile_depths[a] > tile_depths[b]) == ascending) {
+                        let depth = tile_depths[a];
+                        tile_depths[a] = tile_depths[b];
+                        tile_depths[b] = depth;
+
+                        let index = tile_indices[a];
+                        tile_indices[a] = tile_indices[b];
+                        tile_indices[b] = index;
+                    }
+                }
+                workgroupBarrier();
+            }
+        }
+    } else {
+        // ascending-only bitonic network, entries past `count` act as +inf and are never swapped
+        var size = 1u;
+        while (size < count) {
+            size = size << 1u;
+        }
+
+        for (var k = 2u; k <= size; k = k << 1u) {
+            for (var t = local_index; t < size / 2u; t = t + #{TILE_WORKGROUP_INVOCATIONS}u) {
+                let half_block = k >> 1u;
+                let a = (t / half_block) * k + (t % half_block);
+                let b = (t / half_block) * k + k - 1u - (t % half_block);
+
+                if (b < count && entry_before(tile_entries[start + b], tile_entries[start + a])) {
+                    let index = tile_entries[start + a];
+                    tile_entries[start + a] = tile_entries[start + b];
+                    tile_entries[start + b] = index;
+                }
+            }
+            storageBarrier();
+
+            for (var j = k >> 2u; j > 0u; j = j >> 1u) {
+                for (var t = local_index; t < size / 2u; t = t + #{TILE_WORKGROUP_INVOCATIONS}u) {
+                    let a = 2u * j * (t / j) + (t % j);
+                    let b = a + j;
+
+                    if (b < count && entry_before(tile_entries[start + b], tile_entries[start + a])) {
+                        let index = tile_entries[start + a];
+                        tile_entries[start + a] = tile_entries[start + b];
+                        tile_entries[start + b] = index;
+                    }
+                }
+                storageBarrier();
+            }
+        }
+    }
+
+    let pixel = workgroup_id.xy * #{TILE_SIZE}u + local_id.xy;
+    if (any(pixel >= vec2<u32>(view.viewport.zw))) {
+        return;
+    }
+
+    let pixel_center = vec2<f32>(pixel) + 0.5;
+
+    var transmittance = 1.0;
+    var color = vec3<f32>(0.0);
+    var depth = 0.0;
+    var normal = vec3<f32>(0.0);
+
+    var max_weight = 0.0;
+    var gaussian_id = NO_GAUSSIAN_ID;
+
+    for (var i = 0u; i < count; i = i + 1u) {
+        var index = tile_entries[start + i];
+        if (in_workgroup) {
+            index = tile_indices[i];
+        }
+        let gaussian = projected[index];
+
+        let d = gaussian.mean_2d - pixel_center;
+        let conic = gaussian.conic_opacity.xyz;
+        let power = -0.5 * (conic.x * d.x * d.x + conic.z * d.y * d.y) - conic.y * d.x * d.y;
+
+        if (power > 0.0) {
+            continue;
+        }
+
+        let alpha = min(0.99, gaussian.conic_opacity.w * exp(power));
+        if (alpha < 1.0 / 255.0) {
+            continue;
+        }
+
+        let weight = alpha * transmittance;
+        color += gaussian.color.rgb * weight;
+        depth += gaussian.depth * weight;
+        normal += gaussian.normal.xyz * weight;
+
+        if (weight > max_weight) {
+            max_weight = weight;
+            gaussian_id = index;
+        }
+
+        transmittance *= 1.0 - alpha;
+
+        // early termination once the pixel is saturated
+        if (transmittance < 0.0001) {
+            break;
+        }
+    }
+
+    let accumulated_alpha = 1.0 - transmittance;
+    let expected_depth = depth / max(accumulated_alpha, 0.000001);
+
+    textureStore(output_color, vec2<i32>(pixel), vec4<f32>(color, accumulated_alpha));
+    textureStore(output_depth, vec2<i32>(pixel), vec4<f32>(expected_depth, 0.0, 0.0, 0.0));
+
+#ifdef TILE_AOV
+    let normal_length = length(normal);
+    let expected_normal = select(vec3<f32>(0.0), normal / normal_length, normal_length > 0.0);
+
+    textureStore(output_normal, vec2<i32>(pixel), vec4<f32>(expected_normal, accumulated_alpha));
+    textureStore(output_alpha, vec2<i32>(pixel), vec4<f32>(accumulated_alpha, 0.0, 0.0, 0.0));
+    let entity_index = select(gaussian_uniforms.entity_index, NO_GAUSSIAN_ID, gaussian_id == NO_GAUSSIAN_ID);
+    textureStore(output_gaussian_id, vec2<i32>(pixel), vec4<u32>(gaussian_id, entity_index, 0u, 0u));
+#endif
+}
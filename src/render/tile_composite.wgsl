@@ -0,0 +1,17 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+
+@group(0) @binding(0) var tile_color: texture_2d<f32>;
+
+
+@fragment
+fn fs_composite(input: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let texel = vec2<i32>(input.uv * vec2<f32>(textureDimensions(tile_color)));
+
+    // premultiplied color and accumulated alpha from `tile_rasterize`
+    return textureLoad(
+        tile_color,
+        texel,
+        0,
+    );
+}
@@ -0,0 +1,509 @@
+use bevy::{
+    prelude::*,
+    asset::load_internal_asset,
+    log::warn_once,
+    render::{
+        extract_resource::{
+            ExtractResource,
+            ExtractResourcePlugin,
+        },
+        mesh::PrimitiveTopology,
+        render_resource::*,
+    },
+    utils::HashMap,
+};
+
+#[cfg(feature = "buffer_storage")]
+use bevy::render::{
+    renderer::RenderDevice,
+    Render,
+    RenderApp,
+    RenderSet,
+};
+
+
+const VOLUME_MASK_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(762349812);
+
+/// volume masks applied to a single cloud, further masks are ignored
+pub const MAX_VOLUME_MASKS: usize = 4;
+
+/// voxels along each axis of the occupancy grid baked for mesh masks
+pub const VOLUME_MASK_VOXEL_RESOLUTION: usize = 32;
+
+const VOLUME_MASK_VOXEL_WORDS: usize = VOLUME_MASK_VOXEL_RESOLUTION.pow(3).div_ceil(32);
+
+
+#[derive(Default)]
+pub struct VolumeMaskPlugin;
+
+impl Plugin for VolumeMaskPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            VOLUME_MASK_SHADER_HANDLE,
+            "volume_mask.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<VolumeMask>();
+        app.register_type::<VolumeMaskMode>();
+        app.register_type::<VolumeMaskShape>();
+
+        app.init_resource::<VolumeMaskVoxels>();
+        app.add_plugins(ExtractResourcePlugin::<VolumeMaskVoxels>::default());
+
+        app.add_systems(PostUpdate, voxelize_mesh_masks);
+
+        #[cfg(feature = "buffer_storage")]
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_systems(
+                Render,
+                prepare_volume_mask_voxels.in_set(RenderSet::PrepareResources),
+            );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        #[cfg(feature = "buffer_storage")]
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<GpuVolumeMaskVoxels>();
+        }
+    }
+}
+
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Reflect,
+)]
+pub enum VolumeMaskMode {
+    /// hide gaussians inside the shape
+    #[default]
+    Hide,
+    /// hide gaussians outside the shape
+    Clip,
+    /// select gaussians inside the shape for the `Selected` and `HighlightSelected` draw modes,
+    /// replacing the cpu selection of masked clouds
+    Select,
+}
+
+impl VolumeMaskMode {
+    fn shader_index(&self) -> u32 {
+        match self {
+            VolumeMaskMode::Hide => 0,
+            VolumeMaskMode::Clip => 1,
+            VolumeMaskMode::Select => 2,
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub enum VolumeMaskShape {
+    Box(Cuboid),
+    Sphere(Sphere),
+    /// capsule along the local y axis
+    Capsule(Capsule3d),
+    /// closed triangle list mesh, baked into a `VOLUME_MASK_VOXEL_RESOLUTION` occupancy grid
+    Mesh(Handle<Mesh>),
+}
+
+impl Default for VolumeMaskShape {
+    fn default() -> Self {
+        Self::Box(Cuboid::default())
+    }
+}
+
+impl From<Mesh3d> for VolumeMaskShape {
+    fn from(mesh: Mesh3d) -> Self {
+        Self::Mesh(mesh.0)
+    }
+}
+
+impl VolumeMaskShape {
+    /// whether a point in the local space of the mask lies inside the shape, mesh shapes need their baked voxels
+    pub fn contains(
+        &self,
+        point: Vec3,
+        voxels: Option<&MeshVoxels>,
+    ) -> bool {
+        match self {
+            VolumeMaskShape::Box(cuboid) => point.abs().cmple(cuboid.half_size).all(),
+            VolumeMaskShape::Sphere(sphere) => point.length() <= sphere.radius,
+            VolumeMaskShape::Capsule(capsule) => {
+                let axis_y = point.y.clamp(-capsule.half_length, capsule.half_length);
+                (point - Vec3::Y * axis_y).length() <= capsule.radius
+            }
+            VolumeMaskShape::Mesh(_) => voxels.is_some_and(|voxels| voxels.contains(point)),
+        }
+    }
+}
+
+
+/// hides, clips or selects the gaussians of clouds by a shape placed with the entity transform
+///
+/// masks are evaluated on the gpu every frame, so their transforms can be animated freely. hidden
+/// masks are ignored.
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+#[require(
+    Transform,
+    Visibility,
+)]
+pub struct VolumeMask {
+    pub shape: VolumeMaskShape,
+    pub mode: VolumeMaskMode,
+    /// apply the mask to the outside of the shape instead of the inside
+    pub invert: bool,
+    /// cloud entity to mask, every cloud when `None`
+    pub cloud: Option<Entity>,
+}
+
+impl VolumeMask {
+    /// whether a world-space point counts as inside the mask, respecting `invert`
+    pub fn contains(
+        &self,
+        transform: &GlobalTransform,
+        point: Vec3,
+        voxels: &VolumeMaskVoxels,
+    ) -> bool {
+        let local_point = transform.affine().inverse().transform_point3(point);
+
+        let mesh_voxels = match &self.shape {
+            VolumeMaskShape::Mesh(mesh) => voxels.meshes.get(&mesh.id()),
+            _ => None,
+        };
+
+        self.shape.contains(local_point, mesh_voxels) != self.invert
+    }
+}
+
+
+/// occupancy grid of a closed mesh, one bit per voxel in x, y, z order
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshVoxels {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub words: Vec<u32>,
+    /// first word of the grid in `VolumeMaskVoxels::words`
+    pub offset: u32,
+}
+
+impl MeshVoxels {
+    /// bakes a closed triangle list mesh by ray parity along x through every voxel row
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+
+        let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+        let indices = match mesh.indices() {
+            Some(indices) => indices.iter().collect::<Vec<_>>(),
+            None => (0..positions.len()).collect(),
+        };
+
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        for position in positions {
+            min = min.min(Vec3::from(*position));
+            max = max.max(Vec3::from(*position));
+        }
+
+        let padding = (max - min).max_element() * 0.01;
+        if !padding.is_finite() || padding <= 0.0 {
+            return None;
+        }
+
+        let min = min - Vec3::splat(padding);
+        let max = max + Vec3::splat(padding);
+
+        let resolution = VOLUME_MASK_VOXEL_RESOLUTION;
+        let step = (max - min) / resolution as f32;
+        let row_center = |row: usize, axis: usize| min[axis] + (row as f32 + 0.5) * step[axis];
+
+        let mut rows = vec![Vec::<f32>::new(); resolution * resolution];
+
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]]
+                .map(|index| positions.get(index).copied().map(Vec3::from));
+            let (Some(a), Some(b), Some(c)) = (a, b, c) else {
+                continue;
+            };
+
+            let row_range = |axis: usize| {
+                let low = a[axis].min(b[axis]).min(c[axis]);
+                let high = a[axis].max(b[axis]).max(c[axis]);
+
+                let first = ((low - min[axis]) / step[axis] - 0.5).ceil().max(0.0) as usize;
+                let last = (((high - min[axis]) / step[axis] - 0.5).floor() + 1.0).clamp(0.0, resolution as f32) as usize;
+
+                first..last
+            };
+
+            let y_rows = row_range(1);
+            for z in row_range(2) {
+                for y in y_rows.clone() {
+                    let point = Vec2::new(row_center(y, 1), row_center(z, 2));
+
+                    if let Some(x) = intersect_row(a, b, c, point) {
+                        rows[y + z * resolution].push(x);
+                    }
+                }
+            }
+        }
+
+        let mut words = vec![0_u32; VOLUME_MASK_VOXEL_WORDS];
+
+        for (row_index, row) in rows.iter_mut().enumerate() {
+            row.sort_unstable_by(f32::total_cmp);
+
+            for span in row.chunks_exact(2) {
+                for x in 0..resolution {
+                    let center = row_center(x, 0);
+                    if center < span[0] || center >= span[1] {
+                        continue;
+                    }
+
+                    let voxel = x + row_index * resolution;
+                    words[voxel / 32] |= 1 << (voxel % 32);
+                }
+            }
+        }
+
+        Some(Self {
+            min,
+            max,
+            words,
+            offset: 0,
+        })
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        let uvw = (point - self.min) / (self.max - self.min);
+        if uvw.cmplt(Vec3::ZERO).any() || uvw.cmpge(Vec3::ONE).any() {
+            return false;
+        }
+
+        let resolution = VOLUME_MASK_VOXEL_RESOLUTION;
+        let voxel = (uvw * resolution as f32).as_uvec3().min(UVec3::splat(resolution as u32 - 1));
+        let index = voxel.x as usize + resolution * (voxel.y as usize + resolution * voxel.z as usize);
+
+        self.words[index / 32] & (1 << (index % 32)) != 0
+    }
+}
+
+/// x coordinate where the row through `point` (y, z) crosses a triangle, shared edges count exactly once
+fn intersect_row(a: Vec3, b: Vec3, c: Vec3, point: Vec2) -> Option<f32> {
+    let (a2, mut b2, mut c2) = (a.yz(), b.yz(), c.yz());
+    let (mut b, mut c) = (b, c);
+
+    let area = edge_function(a2, b2, c2);
+    if area == 0.0 {
+        return None;
+    }
+
+    if area < 0.0 {
+        std::mem::swap(&mut b2, &mut c2);
+        std::mem::swap(&mut b, &mut c);
+    }
+
+    let weights = [
+        (edge_function(b2, c2, point), b2, c2),
+        (edge_function(c2, a2, point), c2, a2),
+        (edge_function(a2, b2, point), a2, b2),
+    ];
+
+    for (weight, from, to) in weights {
+        let delta = to - from;
+        let top_left = delta.y < 0.0 || (delta.y == 0.0 && delta.x > 0.0);
+
+        if weight < 0.0 || (weight == 0.0 && !top_left) {
+            return None;
+        }
+    }
+
+    let total = weights[0].0 + weights[1].0 + weights[2].0;
+    Some((weights[0].0 * a.x + weights[1].0 * b.x + weights[2].0 * c.x) / total)
+}
+
+/// signed area of (from, to, point), evaluated with ordered endpoints so both triangles of a shared edge agree
+fn edge_function(from: Vec2, to: Vec2, point: Vec2) -> f32 {
+    let evaluate = |from: Vec2, to: Vec2| (to.x - from.x) * (point.y - from.y) - (to.y - from.y) * (point.x - from.x);
+
+    if (from.x, from.y) <= (to.x, to.y) {
+        evaluate(from, to)
+    } else {
+        -evaluate(to, from)
+    }
+}
+
+
+/// baked voxels of every mesh referenced by a `VolumeMask`, concatenated for upload
+#[derive(Resource, ExtractResource, Clone, Debug, Default)]
+pub struct VolumeMaskVoxels {
+    pub meshes: HashMap<AssetId<Mesh>, MeshVoxels>,
+    pub words: Vec<u32>,
+}
+
+fn voxelize_mesh_masks(
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    meshes: Res<Assets<Mesh>>,
+    masks: Query<&VolumeMask>,
+    mut voxels: ResMut<VolumeMaskVoxels>,
+) {
+    let modified = mesh_events.read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let referenced = masks.iter()
+        .filter_map(|mask| match &mask.shape {
+            VolumeMaskShape::Mesh(mesh) => Some(mesh.id()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let stale = voxels.meshes.keys().any(|id| !referenced.contains(id) || modified.contains(id));
+    let missing = referenced.iter().any(|id| !voxels.meshes.contains_key(id) && meshes.contains(*id));
+
+    if !stale && !missing {
+        return;
+    }
+
+    let voxels = &mut *voxels;
+    voxels.meshes.retain(|id, _| referenced.contains(id) && !modified.contains(id));
+
+    for id in referenced {
+        if voxels.meshes.contains_key(&id) {
+            continue;
+        }
+
+        let Some(mesh) = meshes.get(id) else {
+            continue;
+        };
+
+        match MeshVoxels::from_mesh(mesh) {
+            Some(mesh_voxels) => {
+                voxels.meshes.insert(id, mesh_voxels);
+            }
+            None => warn_once!("volume mask meshes must be closed triangle lists with positions"),
+        }
+    }
+
+    voxels.words.clear();
+    for mesh_voxels in voxels.meshes.values_mut() {
+        mesh_voxels.offset = voxels.words.len() as u32;
+        voxels.words.extend_from_slice(&mesh_voxels.words);
+    }
+}
+
+
+#[derive(ShaderType, Clone, Copy, Debug, Default)]
+pub struct GaussianVolumeMaskUniform {
+    pub mask_from_world: Mat4,
+    /// box half size, sphere radius, capsule radius and half length, or the mesh voxel grid min
+    pub params: Vec4,
+    /// mesh voxel grid max
+    pub voxel_max: Vec4,
+    /// shape, mode, invert and mesh voxel offset
+    pub flags: UVec4,
+}
+
+/// volume masks affecting a cloud entity, packed for `GaussianCloudUniform`
+pub fn volume_mask_uniforms<'a>(
+    cloud: Entity,
+    masks: impl Iterator<Item = (&'a VolumeMask, &'a GlobalTransform, &'a InheritedVisibility)>,
+    voxels: &VolumeMaskVoxels,
+) -> (u32, [GaussianVolumeMaskUniform; MAX_VOLUME_MASKS]) {
+    let mut uniforms = [GaussianVolumeMaskUniform::default(); MAX_VOLUME_MASKS];
+    let mut count = 0;
+
+    for (mask, transform, visibility) in masks {
+        if !visibility.get() || mask.cloud.is_some_and(|target| target != cloud) {
+            continue;
+        }
+
+        let (shape, params, voxel_max, offset) = match &mask.shape {
+            VolumeMaskShape::Box(cuboid) => (0, cuboid.half_size.extend(0.0), Vec4::ZERO, 0),
+            VolumeMaskShape::Sphere(sphere) => (1, Vec4::new(sphere.radius, 0.0, 0.0, 0.0), Vec4::ZERO, 0),
+            VolumeMaskShape::Capsule(capsule) => (2, Vec4::new(capsule.radius, capsule.half_length, 0.0, 0.0), Vec4::ZERO, 0),
+            VolumeMaskShape::Mesh(mesh) => {
+                #[cfg(not(feature = "buffer_storage"))]
+                warn_once!("mesh volume masks require the buffer_storage feature");
+
+                let Some(mesh_voxels) = voxels.meshes.get(&mesh.id()) else {
+                    continue;
+                };
+
+                (3, mesh_voxels.min.extend(0.0), mesh_voxels.max.extend(0.0), mesh_voxels.offset)
+            }
+        };
+
+        if count == MAX_VOLUME_MASKS {
+            warn_once!("gaussian clouds support at most {} volume masks", MAX_VOLUME_MASKS);
+            break;
+        }
+
+        uniforms[count] = GaussianVolumeMaskUniform {
+            mask_from_world: transform.compute_matrix().inverse(),
+            params,
+            voxel_max,
+            flags: UVec4::new(shape, mask.mode.shader_index(), mask.invert as u32, offset),
+        };
+        count += 1;
+    }
+
+    (count as u32, uniforms)
+}
+
+
+/// storage buffer with the concatenated mesh mask voxels, bound next to the gaussian uniforms
+#[cfg(feature = "buffer_storage")]
+#[derive(Resource)]
+pub struct GpuVolumeMaskVoxels {
+    pub buffer: Buffer,
+}
+
+#[cfg(feature = "buffer_storage")]
+impl FromWorld for GpuVolumeMaskVoxels {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        Self {
+            buffer: create_voxel_buffer(render_device, &[]),
+        }
+    }
+}
+
+#[cfg(feature = "buffer_storage")]
+fn create_voxel_buffer(render_device: &RenderDevice, words: &[u32]) -> Buffer {
+    // empty storage buffers can't be bound
+    let contents = if words.is_empty() { &[0] } else { words };
+
+    render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("volume mask voxels"),
+        contents: bytemuck::cast_slice(contents),
+        usage: BufferUsages::STORAGE,
+    })
+}
+
+#[cfg(feature = "buffer_storage")]
+fn prepare_volume_mask_voxels(
+    render_device: Res<RenderDevice>,
+    voxels: Option<Res<VolumeMaskVoxels>>,
+    mut gpu_voxels: ResMut<GpuVolumeMaskVoxels>,
+) {
+    let Some(voxels) = voxels.filter(|voxels| voxels.is_changed()) else {
+        return;
+    };
+
+    gpu_voxels.buffer = create_voxel_buffer(&render_device, &voxels.words);
+}
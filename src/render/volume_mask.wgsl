@@ -0,0 +1,106 @@
+#define_import_path bevy_gaussian_splatting::volume_mask
+
+#import bevy_gaussian_splatting::bindings::{
+    gaussian_uniforms,
+    VolumeMask,
+}
+
+#ifdef BUFFER_STORAGE
+#import bevy_gaussian_splatting::bindings::volume_mask_voxels
+#endif
+
+
+const VOLUME_MASK_BOX: u32 = 0u;
+const VOLUME_MASK_SPHERE: u32 = 1u;
+const VOLUME_MASK_CAPSULE: u32 = 2u;
+const VOLUME_MASK_MESH: u32 = 3u;
+
+const VOLUME_MASK_HIDE: u32 = 0u;
+const VOLUME_MASK_CLIP: u32 = 1u;
+const VOLUME_MASK_SELECT: u32 = 2u;
+
+
+fn mesh_voxel(
+    mask: VolumeMask,
+    local_position: vec3<f32>,
+) -> bool {
+#ifdef BUFFER_STORAGE
+    let uvw = (local_position - mask.params.xyz) / (mask.voxel_max.xyz - mask.params.xyz);
+    if (any(uvw < vec3<f32>(0.0)) || any(uvw >= vec3<f32>(1.0))) {
+        return false;
+    }
+
+    let resolution = #{VOLUME_MASK_VOXEL_RESOLUTION}u;
+    let voxel = min(
+        vec3<u32>(uvw * f32(resolution)),
+        vec3<u32>(resolution - 1u),
+    );
+    let index = voxel.x + resolution * (voxel.y + resolution * voxel.z);
+
+    let word = volume_mask_voxels[mask.flags.w + index / 32u];
+    return ((word >> (index % 32u)) & 1u) != 0u;
+#else
+    return false;
+#endif
+}
+
+fn volume_mask_contains(
+    mask: VolumeMask,
+    world_position: vec3<f32>,
+) -> bool {
+    let local_position = (mask.mask_from_world * vec4<f32>(world_position, 1.0)).xyz;
+
+    var inside = false;
+    if (mask.flags.x == VOLUME_MASK_BOX) {
+        inside = all(abs(local_position) <= mask.params.xyz);
+    } else if (mask.flags.x == VOLUME_MASK_SPHERE) {
+        inside = length(local_position) <= mask.params.x;
+    } else if (mask.flags.x == VOLUME_MASK_CAPSULE) {
+        let axis_y = clamp(local_position.y, -mask.params.y, mask.params.y);
+        inside = length(local_position - vec3<f32>(0.0, axis_y, 0.0)) <= mask.params.x;
+    } else if (mask.flags.x == VOLUME_MASK_MESH) {
+        inside = mesh_voxel(mask, local_position);
+    }
+
+    return inside != (mask.flags.z != 0u);
+}
+
+// whether a hide or clip mask removes the gaussian at `world_position`
+fn volume_mask_hidden(
+    world_position: vec3<f32>,
+) -> bool {
+    for (var i = 0u; i < gaussian_uniforms.volume_mask_count; i += 1u) {
+        let mask = gaussian_uniforms.volume_masks[i];
+        if (mask.flags.y == VOLUME_MASK_SELECT) {
+            continue;
+        }
+
+        let inside = volume_mask_contains(mask, world_position);
+        if ((mask.flags.y == VOLUME_MASK_HIDE) == inside) {
+            return true;
+        }
+    }
+
+    return false;
+}
+
+// selection state of a gaussian, select masks replace the cpu selection when present
+fn volume_mask_selected(
+    world_position: vec3<f32>,
+    selected: bool,
+) -> bool {
+    var masked = false;
+    var inside = false;
+
+    for (var i = 0u; i < gaussian_uniforms.volume_mask_count; i += 1u) {
+        let mask = gaussian_uniforms.volume_masks[i];
+        if (mask.flags.y != VOLUME_MASK_SELECT) {
+            continue;
+        }
+
+        masked = true;
+        inside |= volume_mask_contains(mask, world_position);
+    }
+
+    return select(selected, inside, masked);
+}
@@ -0,0 +1,288 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    render::view::NoFrustumCulling,
+    transform::TransformSystem,
+    utils::{
+        HashMap,
+        HashSet,
+    },
+};
+
+use crate::{
+    Gaussian,
+    GaussianCloud,
+    GaussianCloudAppearance,
+    GaussianCloudDebugSettings,
+    GaussianCloudHandle,
+    GaussianCloudRasterSettings,
+    GaussianCloudSortSettings,
+    sort::{
+        GlobalSortMember,
+        SortedEntriesHandle,
+    },
+};
+
+
+/// merges every visible gaussian cloud into a single world-space cloud, producing one sorted
+/// draw per view so that overlapping clouds blend correctly
+///
+/// the merged cloud is drawn with a single uniform, so member `GaussianCloudAppearance`,
+/// `GaussianCloudRasterSettings` and `GaussianCloudDebugSettings` (opacity, global scale, color
+/// grading, spherical harmonic band limit, reveal, ...) are ignored in favor of the settings here.
+/// members are transformed into world space on the cpu, only members whose cloud, transform or
+/// visibility changed are transformed again, but the merged cloud is re-uploaded on every change.
+#[derive(
+    Resource,
+    Clone,
+    Default,
+    Reflect,
+)]
+#[reflect(Resource)]
+pub struct GlobalSort {
+    pub enabled: bool,
+    pub sort_settings: GaussianCloudSortSettings,
+    pub appearance: GaussianCloudAppearance,
+    pub raster_settings: GaussianCloudRasterSettings,
+    pub debug_settings: GaussianCloudDebugSettings,
+}
+
+impl GlobalSort {
+    /// settings components of the merged cloud entity
+    pub fn settings(&self) -> (
+        GaussianCloudSortSettings,
+        GaussianCloudAppearance,
+        GaussianCloudRasterSettings,
+        GaussianCloudDebugSettings,
+    ) {
+        (
+            self.sort_settings.clone(),
+            self.appearance.clone(),
+            self.raster_settings.clone(),
+            self.debug_settings.clone(),
+        )
+    }
+}
+
+
+/// marks the entity rendering the merged cloud of all `GlobalSortMember` entities
+#[derive(
+    Component,
+    Clone,
+    Debug,
+    Default,
+    Reflect,
+)]
+#[reflect(Component)]
+pub struct MergedGaussianCloud {
+    /// range of each visible member's gaussians in the merged cloud
+    pub members: Vec<(Entity, Range<usize>)>,
+}
+
+
+#[derive(Default)]
+pub struct GlobalSortPlugin;
+
+impl Plugin for GlobalSortPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GlobalSort>();
+        app.init_resource::<GlobalSort>();
+
+        app.register_type::<MergedGaussianCloud>();
+
+        app.add_systems(
+            PostUpdate,
+            update_global_sort.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+
+/// applies an entity transform to a gaussian, non-uniform entity scale is approximated per axis
+/// and view-dependent spherical harmonic bands are not rotated
+pub fn transform_gaussian(
+    gaussian: &Gaussian,
+    transform: &GlobalTransform,
+) -> Gaussian {
+    let (scale, rotation, _) = transform.to_scale_rotation_translation();
+
+    let mut transformed = *gaussian;
+
+    let position = Vec3::from(gaussian.position_visibility.position);
+    transformed.position_visibility.position = transform.transform_point(position).into();
+
+    let [w, x, y, z] = gaussian.rotation.rotation;
+    let world_rotation = (rotation * Quat::from_xyzw(x, y, z, w)).normalize();
+    transformed.rotation.rotation = [
+        world_rotation.w,
+        world_rotation.x,
+        world_rotation.y,
+        world_rotation.z,
+    ];
+
+    transformed.scale_opacity.scale = (Vec3::from(gaussian.scale_opacity.scale) * scale.abs()).into();
+
+    transformed
+}
+
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_global_sort(
+    mut commands: Commands,
+    global_sort: Res<GlobalSort>,
+    mut gaussian_clouds_res: ResMut<Assets<GaussianCloud>>,
+    mut asset_events: EventReader<AssetEvent<GaussianCloud>>,
+    mut removed_clouds: RemovedComponents<GaussianCloudHandle>,
+    sources: Query<
+        (
+            Entity,
+            &GaussianCloudHandle,
+            &GlobalTransform,
+            &InheritedVisibility,
+            Has<GlobalSortMember>,
+        ),
+        Without<MergedGaussianCloud>,
+    >,
+    changed_sources: Query<
+        Entity,
+        (
+            With<GaussianCloudHandle>,
+            Without<MergedGaussianCloud>,
+            Or<(
+                Changed<GaussianCloudHandle>,
+                Changed<GlobalTransform>,
+                Changed<InheritedVisibility>,
+            )>,
+        ),
+    >,
+    merged: Query<
+        (
+            Entity,
+            &GaussianCloudHandle,
+        ),
+        With<MergedGaussianCloud>,
+    >,
+    mut member_gaussians: Local<HashMap<Entity, Vec<Gaussian>>>,
+) {
+    if !global_sort.enabled {
+        asset_events.clear();
+        removed_clouds.clear();
+        member_gaussians.clear();
+
+        for (entity, ..) in merged.iter() {
+            commands.entity(entity).despawn();
+        }
+
+        for (entity, _, _, _, member) in sources.iter() {
+            if member {
+                commands.entity(entity).remove::<GlobalSortMember>();
+            }
+        }
+
+        return;
+    }
+
+    for (entity, _, _, _, member) in sources.iter() {
+        if !member {
+            commands.entity(entity).insert(GlobalSortMember);
+        }
+    }
+
+    let source_ids = sources.iter()
+        .map(|(_, handle, ..)| handle.0.id())
+        .collect::<HashSet<_>>();
+
+    let modified_ids = asset_events.read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } |
+            AssetEvent::Modified { id } |
+            AssetEvent::LoadedWithDependencies { id } => source_ids.contains(id).then_some(*id),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    let mut removed = false;
+    for entity in removed_clouds.read() {
+        removed |= member_gaussians.remove(&entity).is_some();
+    }
+
+    if global_sort.is_changed() || merged.is_empty() {
+        member_gaussians.clear();
+    }
+
+    let mut dirty = removed;
+    for (entity, handle, transform, visibility, _) in sources.iter() {
+        let cached = member_gaussians.contains_key(&entity);
+        let changed = !cached
+            || modified_ids.contains(&handle.0.id())
+            || changed_sources.contains(entity);
+
+        if !changed {
+            continue;
+        }
+
+        if !visibility.get() {
+            dirty |= member_gaussians.remove(&entity).is_some();
+            continue;
+        }
+
+        let Some(cloud) = gaussian_clouds_res.get(handle) else {
+            continue;
+        };
+
+        let gaussians = cloud.gaussian_iter()
+            .map(|gaussian| transform_gaussian(&gaussian, transform))
+            .collect::<Vec<Gaussian>>();
+
+        member_gaussians.insert(entity, gaussians);
+        dirty = true;
+    }
+
+    if !dirty && !merged.is_empty() {
+        return;
+    }
+
+    // query order keeps the merged layout stable between rebuilds
+    let mut gaussians = Vec::new();
+    let mut members = Vec::new();
+    for (entity, ..) in sources.iter() {
+        let Some(member) = member_gaussians.get(&entity) else {
+            continue;
+        };
+
+        let start = gaussians.len();
+        gaussians.extend_from_slice(member);
+        members.push((entity, start..gaussians.len()));
+    }
+
+    if gaussians.is_empty() {
+        for (entity, ..) in merged.iter() {
+            commands.entity(entity).despawn();
+        }
+
+        return;
+    }
+
+    let cloud = GaussianCloud::from_gaussians(gaussians);
+
+    if let Ok((entity, handle)) = merged.get_single() {
+        gaussian_clouds_res.insert(handle.0.id(), cloud);
+
+        // entry count changed, sorted entries are recreated by the sort plugin
+        commands.entity(entity)
+            .insert((
+                global_sort.settings(),
+                MergedGaussianCloud { members },
+            ))
+            .remove::<SortedEntriesHandle>();
+    } else {
+        commands.spawn((
+            GaussianCloudHandle(gaussian_clouds_res.add(cloud)),
+            global_sort.settings(),
+            MergedGaussianCloud { members },
+            NoFrustumCulling,
+            Name::new("merged_gaussian_cloud"),
+        ));
+    }
+}
@@ -17,6 +17,10 @@ use bevy::{
             RenderAssetUsages,
             PrepareAssetError,
         },
+        primitives::{
+            Frustum,
+            Sphere,
+        },
         renderer::RenderDevice,
     },
     utils::{
@@ -30,14 +34,23 @@ use bytemuck::{
 };
 use static_assertions::assert_cfg;
 
+#[cfg(feature = "query_bvh")]
+use bevy::math::Affine3A;
+#[cfg(feature = "query_bvh")]
+use crate::query::bvh::GaussianBvh;
+
 use crate::{
     camera::GaussianCamera,
     GaussianCloud,
     GaussianCloudHandle,
-    GaussianCloudSettings,
+    GaussianCloudSortSettings,
 };
 
 
+// merging transforms each gaussian's rotation and scale, which precomputed covariance clouds do not keep
+#[cfg(not(feature = "precompute_covariance_3d"))]
+pub mod global;
+
 #[cfg(feature = "sort_radix")]
 pub mod radix;
 
@@ -104,17 +117,189 @@ impl Default for SortMode {
 )]
 #[reflect(Resource)]
 pub struct SortConfig {
+    /// minimum time between two sorts of the same view
     pub period_ms: usize,
+    /// camera translation (world units) since the last sort required to trigger a new sort
+    pub translation_threshold: f32,
+    /// camera rotation (radians) since the last sort required to trigger a new sort
+    pub rotation_threshold: f32,
+    /// CPU time a sort system may spend per frame, remaining views are deferred to the next frame
+    pub frame_budget_ms: usize,
 }
 
 impl Default for SortConfig {
     fn default() -> Self {
         Self {
             period_ms: 100,
+            translation_threshold: 0.01,
+            rotation_threshold: 0.01,
+            frame_budget_ms: 8,
         }
     }
 }
 
+impl SortConfig {
+    pub fn frame_budget(&self) -> Duration {
+        Duration::from_millis(self.frame_budget_ms as u64)
+    }
+}
+
+
+/// sort key of culled gaussians (-1.0), ordered behind every visible entry
+pub const CULLED_SORT_KEY: u32 = 0xbf80_0000;
+
+/// per-gaussian culling applied by the CPU sorts, culled gaussians are excluded from the draw
+#[derive(
+    Resource,
+    Debug,
+    Clone,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Resource)]
+pub struct CullConfig {
+    pub enabled: bool,
+    /// discard gaussians whose 3 sigma bounding sphere lies outside the view frustum
+    pub frustum: bool,
+    /// guard band around the frustum as a fraction of the view extent, keeps gaussians near the
+    /// screen edges drawn while the camera moves between throttled sorts
+    pub frustum_margin: f32,
+    /// discard gaussians with an opacity below this value
+    pub opacity_threshold: f32,
+    /// discard gaussians with a projected 3 sigma radius below this many pixels
+    pub projected_size_threshold: f32,
+}
+
+impl Default for CullConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            frustum: true,
+            frustum_margin: 0.2,
+            opacity_threshold: 1.0 / 255.0,
+            projected_size_threshold: 0.5,
+        }
+    }
+}
+
+/// camera state needed to cull gaussians of a single sort
+#[derive(Clone)]
+pub struct CullView {
+    pub frustum: Option<Frustum>,
+    pub clip_from_world: Mat4,
+    /// pixels per world unit at a clip-space w of 1
+    pub pixel_scale: f32,
+}
+
+impl CullView {
+    pub fn new(
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        frustum: Option<&Frustum>,
+        frustum_margin: f32,
+    ) -> Self {
+        let clip_from_view = camera.clip_from_view();
+        let viewport_height = camera.physical_viewport_size()
+            .map(|size| size.y as f32)
+            .unwrap_or(0.0);
+        let clip_from_world = clip_from_view * camera_transform.compute_matrix().inverse();
+
+        Self {
+            frustum: frustum.map(|frustum| guard_band_frustum(frustum, &clip_from_world, frustum_margin)),
+            clip_from_world,
+            pixel_scale: clip_from_view.y_axis.y.abs() * 0.5 * viewport_height,
+        }
+    }
+
+    /// the same view with per gaussian frustum tests disabled, for clouds culled with `frustum_mask`
+    pub fn without_frustum(&self) -> Self {
+        Self {
+            frustum: None,
+            ..self.clone()
+        }
+    }
+
+    /// per gaussian frustum visibility from a single bvh traversal, `None` when frustum culling is off
+    #[cfg(feature = "query_bvh")]
+    pub fn frustum_mask(
+        &self,
+        config: &CullConfig,
+        bvh: &GaussianBvh,
+        world_from_local: &Affine3A,
+        global_scale: f32,
+    ) -> Option<Vec<bool>> {
+        if !config.enabled || !config.frustum {
+            return None;
+        }
+
+        let frustum = self.frustum.as_ref()?;
+
+        let mut mask = vec![false; bvh.indices.len()];
+        for index in bvh.within_frustum(frustum, world_from_local, global_scale) {
+            mask[index] = true;
+        }
+
+        Some(mask)
+    }
+
+    pub fn is_culled(
+        &self,
+        config: &CullConfig,
+        position: Vec3A,
+        radius: f32,
+        opacity: f32,
+    ) -> bool {
+        if !config.enabled {
+            return false;
+        }
+
+        if opacity < config.opacity_threshold {
+            return true;
+        }
+
+        if config.frustum {
+            if let Some(frustum) = &self.frustum {
+                let sphere = Sphere {
+                    center: position,
+                    radius,
+                };
+
+                if !frustum.intersects_sphere(&sphere, true) {
+                    return true;
+                }
+            }
+        }
+
+        let w = (self.clip_from_world * position.extend(1.0)).w;
+        if w <= 0.0 {
+            return false;
+        }
+
+        radius * self.pixel_scale / w < config.projected_size_threshold
+    }
+}
+
+
+/// widens the side planes of `frustum` by `margin` of the view extent, near and far are kept
+pub fn guard_band_frustum(
+    frustum: &Frustum,
+    clip_from_world: &Mat4,
+    margin: f32,
+) -> Frustum {
+    if margin <= 0.0 {
+        return *frustum;
+    }
+
+    let shrink = 1.0 / (1.0 + margin);
+    let widened = Frustum::from_clip_from_world(
+        &(Mat4::from_scale(Vec3::new(shrink, shrink, 1.0)) * *clip_from_world),
+    );
+
+    let mut guarded = *frustum;
+    guarded.half_spaces[..4].copy_from_slice(&widened.half_spaces[..4]);
+    guarded
+}
+
 
 #[derive(Default)]
 pub struct SortPlugin;
@@ -130,17 +315,27 @@ impl Plugin for SortPlugin {
         #[cfg(feature = "sort_std")]
         app.add_plugins(std::StdSortPlugin);
 
+        #[cfg(not(feature = "precompute_covariance_3d"))]
+        app.add_plugins(global::GlobalSortPlugin);
+
         app.register_type::<SortConfig>();
         app.init_resource::<SortConfig>();
 
+        app.register_type::<CullConfig>();
+        app.init_resource::<CullConfig>();
+
         app.register_type::<SortedEntries>();
         app.register_type::<SortedEntriesHandle>();
         app.init_asset::<SortedEntries>();
         app.register_asset_reflect::<SortedEntries>();
 
         app.register_type::<SortTrigger>();
+        app.register_type::<SortStats>();
         app.add_plugins(ExtractComponentPlugin::<SortTrigger>::default());
 
+        app.register_type::<GlobalSortMember>();
+        app.add_plugins(ExtractComponentPlugin::<GlobalSortMember>::default());
+
         app.add_plugins(RenderAssetPlugin::<GpuSortedEntry>::default());
 
         app.add_systems(
@@ -172,9 +367,76 @@ pub struct SortTrigger {
     pub camera_index: usize,
     pub needs_sort: bool,
     pub last_camera_position: Vec3A,
+    pub last_camera_rotation: Quat,
     pub last_sort_time: Option<Instant>,
 }
 
+impl SortTrigger {
+    pub fn exceeds_motion_threshold(
+        &self,
+        transform: &GlobalTransform,
+        sort_config: &SortConfig,
+    ) -> bool {
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+
+        let translation_delta = self.last_camera_position.distance(translation.into());
+        let rotation_delta = self.last_camera_rotation.angle_between(rotation);
+
+        translation_delta > sort_config.translation_threshold
+            || rotation_delta > sort_config.rotation_threshold
+    }
+}
+
+
+/// cloud drawn as part of the merged global sort instead of individually
+#[derive(
+    Component,
+    ExtractComponent,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct GlobalSortMember;
+
+
+/// per-view sort timings, updated by the CPU sort systems
+#[derive(
+    Component,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Reflect,
+)]
+#[reflect(Component, Default)]
+pub struct SortStats {
+    pub sort_count: u64,
+    pub deferred_count: u64,
+    pub last_sort_duration: Duration,
+    pub average_sort_duration: Duration,
+}
+
+impl SortStats {
+    pub fn record_sort(&mut self, duration: Duration) {
+        self.sort_count += 1;
+        self.last_sort_duration = duration;
+
+        // exponential moving average, seeded by the first sample
+        self.average_sort_duration = if self.sort_count == 1 {
+            duration
+        } else {
+            self.average_sort_duration.mul_f32(0.9) + duration.mul_f32(0.1)
+        };
+    }
+
+    pub fn record_deferred(&mut self) {
+        self.deferred_count += 1;
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn update_sort_trigger(
     mut commands: Commands,
@@ -187,7 +449,7 @@ fn update_sort_trigger(
         ),
     >,
     mut existing_sort_triggers: Query<(
-        &Transform,
+        &GlobalTransform,
         &Camera,
         &mut SortTrigger,
     )>,
@@ -195,7 +457,10 @@ fn update_sort_trigger(
 ) {
     for entity in new_gaussian_cameras.iter() {
         commands.entity(entity)
-            .insert(SortTrigger::default());
+            .insert((
+                SortTrigger::default(),
+                SortStats::default(),
+            ));
     }
 
     for (
@@ -206,20 +471,32 @@ fn update_sort_trigger(
         if sort_trigger.last_sort_time.is_none() {
             assert!(camera.order >= 0, "camera order must be a non-negative index into gaussian cameras");
 
+            let (_, rotation, translation) = camera_transform.to_scale_rotation_translation();
+
             sort_trigger.camera_index = camera.order as usize;
             sort_trigger.needs_sort = true;
+            sort_trigger.last_camera_position = translation.into();
+            sort_trigger.last_camera_rotation = rotation;
             sort_trigger.last_sort_time = Some(Instant::now());
             continue;
-        } else if sort_trigger.last_sort_time.unwrap().elapsed() < Duration::from_millis(sort_config.period_ms as u64) {
+        }
+
+        // a deferred sort stays pending until a sort system has capacity for it
+        if sort_trigger.needs_sort {
+            continue;
+        }
+
+        if sort_trigger.last_sort_time.unwrap().elapsed() < Duration::from_millis(sort_config.period_ms as u64) {
             continue;
         }
 
-        let camera_position = camera_transform.compute_affine().translation;
-        let camera_movement = sort_trigger.last_camera_position != camera_position;
+        if sort_trigger.exceeds_motion_threshold(camera_transform, &sort_config) {
+            let (_, rotation, translation) = camera_transform.to_scale_rotation_translation();
 
-        if camera_movement {
             sort_trigger.needs_sort = true;
-            sort_trigger.last_camera_position = camera_position;
+            sort_trigger.last_camera_position = translation.into();
+            sort_trigger.last_camera_rotation = rotation;
+            sort_trigger.last_sort_time = Some(Instant::now());
         }
     }
 }
@@ -258,7 +535,7 @@ fn auto_insert_sorted_entries(
         (
             Entity,
             &GaussianCloudHandle,
-            &GaussianCloudSettings,
+            &GaussianCloudSortSettings,
         ),
         Without<SortedEntriesHandle>
     >,
@@ -281,10 +558,10 @@ fn auto_insert_sorted_entries(
     for (
         entity,
         gaussian_cloud_handle,
-        _settings,
+        _sort_settings,
     ) in gaussian_clouds.iter() {
         // // TODO: specialize vertex shader for sort mode (e.g. draw_indirect but no sort indirection)
-        // if settings.sort_mode == SortMode::None {
+        // if sort_settings.sort_mode == SortMode::None {
         //     continue;
         // }
 
@@ -409,6 +686,8 @@ pub struct SortedEntries {
     pub camera_count: usize,
     pub entry_count: usize,
     pub sorted: Vec<SortEntry>,
+    /// entries left after culling per camera, `None` draws every gaussian
+    pub visible_counts: Vec<Option<u32>>,
 
     #[cfg(feature = "buffer_texture")]
     pub texture: Handle<Image>,
@@ -439,6 +718,7 @@ impl SortedEntries {
             camera_count,
             entry_count,
             sorted,
+            visible_counts: vec![None; camera_count],
         };
 
         #[cfg(feature = "buffer_texture")]
@@ -446,6 +726,7 @@ impl SortedEntries {
             camera_count,
             entry_count,
             sorted,
+            visible_counts: vec![None; camera_count],
             texture: images.add(Image::new(
                 Extent3d {
                     width: cloud.len_sqrt_ceil() as u32,
@@ -479,9 +760,28 @@ impl RenderAsset for GpuSortedEntry {
 
         let count = source.sorted.len();
 
+        let draw_indirect_args = source.visible_counts.iter()
+            .flat_map(|visible_count| {
+                wgpu::util::DrawIndirectArgs {
+                    vertex_count: 4,
+                    instance_count: visible_count.unwrap_or_default(),
+                    first_vertex: 0,
+                    first_instance: 0,
+                }.as_bytes().to_vec()
+            })
+            .collect::<Vec<u8>>();
+
+        let draw_indirect_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("sorted_entry_draw_indirect_buffer"),
+            contents: &draw_indirect_args,
+            usage: BufferUsages::INDIRECT | BufferUsages::COPY_DST,
+        });
+
         Ok(GpuSortedEntry {
             sorted_entry_buffer,
             count,
+            visible_counts: source.visible_counts,
+            draw_indirect_buffer,
 
             #[cfg(feature = "buffer_texture")]
             texture: source.texture,
@@ -500,6 +800,9 @@ impl RenderAsset for GpuSortedEntry {
 pub struct GpuSortedEntry {
     pub sorted_entry_buffer: Buffer,
     pub count: usize,
+    pub visible_counts: Vec<Option<u32>>,
+    /// one `DrawIndirectArgs` per camera holding the culled instance count
+    pub draw_indirect_buffer: Buffer,
 
     #[cfg(feature = "buffer_texture")]
     pub texture: Handle<Image>,
@@ -57,7 +57,7 @@ use crate::{
         GaussianCloud,
         GaussianCloudHandle,
     },
-    GaussianCloudSettings,
+    GaussianCloudSortSettings,
     render::{
         GaussianCloudBindGroup,
         GaussianCloudPipeline,
@@ -68,6 +68,7 @@ use crate::{
         shader_defs,
     },
     sort::{
+        GlobalSortMember,
         GpuSortedEntry,
         SortEntry,
         SortedEntries,
@@ -370,21 +371,24 @@ pub fn queue_radix_bind_group(
     asset_server: Res<AssetServer>,
     gaussian_cloud_res: Res<RenderAssets<GpuGaussianCloud>>,
     sorted_entries_res: Res<RenderAssets<GpuSortedEntry>>,
-    gaussian_clouds: Query<(
-        Entity,
-        &GaussianCloudHandle,
-        &SortedEntriesHandle,
-        &GaussianCloudSettings,
-    )>,
+    gaussian_clouds: Query<
+        (
+            Entity,
+            &GaussianCloudHandle,
+            &SortedEntriesHandle,
+            &GaussianCloudSortSettings,
+        ),
+        Without<GlobalSortMember>,
+    >,
     sort_buffers: Res<RadixSortBuffers>,
 ) {
     for (
         entity,
         cloud_handle,
         sorted_entries_handle,
-        settings,
+        sort_settings,
     ) in gaussian_clouds.iter() {
-        if settings.sort_mode != SortMode::Radix {
+        if sort_settings.sort_mode != SortMode::Radix {
             continue;
         }
 
@@ -1,18 +1,28 @@
 use bevy::{
     prelude::*,
     math::Vec3A,
+    render::primitives::Frustum,
     utils::Instant,
 };
 use rayon::prelude::*;
 
+#[cfg(feature = "query_bvh")]
+use crate::query::bvh::GaussianBvhs;
+
 use crate::{
     camera::GaussianCamera,
     GaussianCloud,
+    GaussianCloudAppearance,
     GaussianCloudHandle,
-    GaussianCloudSettings,
+    GaussianCloudSortSettings,
     sort::{
+        CULLED_SORT_KEY,
+        CullConfig,
+        CullView,
+        GlobalSortMember,
         SortConfig,
         SortMode,
+        SortStats,
         SortTrigger,
         SortedEntries,
         SortedEntriesHandle,
@@ -29,40 +39,73 @@ impl Plugin for RayonSortPlugin {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn rayon_sort(
     asset_server: Res<AssetServer>,
     gaussian_clouds_res: Res<Assets<GaussianCloud>>,
-    gaussian_clouds: Query<(
-        &GaussianCloudHandle,
-        &SortedEntriesHandle,
-        &GaussianCloudSettings,
-        &GlobalTransform,
-    )>,
+    gaussian_clouds: Query<
+        (
+            &GaussianCloudHandle,
+            &SortedEntriesHandle,
+            &GaussianCloudSortSettings,
+            &GaussianCloudAppearance,
+            &GlobalTransform,
+        ),
+        Without<GlobalSortMember>,
+    >,
     mut sorted_entries_res: ResMut<Assets<SortedEntries>>,
     mut cameras: Query<
-        &mut SortTrigger,
+        (
+            &Camera,
+            &GlobalTransform,
+            Option<&Frustum>,
+            &mut SortTrigger,
+            Option<&mut SortStats>,
+        ),
         With<GaussianCamera>,
     >,
-    mut sort_config: ResMut<SortConfig>,
+    sort_config: Res<SortConfig>,
+    cull_config: Res<CullConfig>,
+    #[cfg(feature = "query_bvh")]
+    bvhs: Option<Res<GaussianBvhs>>,
 ) {
     // TODO: move sort to render world, use extracted views and update the existing buffer instead of creating new
 
-    let sort_start_time = Instant::now();
-    let mut performed_sort = false;
+    let frame_start_time = Instant::now();
 
-    for mut trigger in cameras.iter_mut() {
+    for (
+        camera,
+        camera_transform,
+        frustum,
+        mut trigger,
+        mut stats,
+    ) in cameras.iter_mut() {
         if !trigger.needs_sort {
             continue;
         }
 
+        if frame_start_time.elapsed() > sort_config.frame_budget() {
+            if let Some(stats) = stats.as_mut() {
+                stats.record_deferred();
+            }
+
+            continue;
+        }
+
+        let sort_start_time = Instant::now();
+        let mut performed_sort = false;
+
+        let cull_view = CullView::new(camera, camera_transform, frustum, cull_config.frustum_margin);
+        let cull_view_without_frustum = cull_view.without_frustum();
+
         for (
             gaussian_cloud_handle,
             sorted_entries_handle,
-            settings,
+            sort_settings,
+            appearance,
             transform,
         ) in gaussian_clouds.iter() {
-            if settings.sort_mode != SortMode::Rayon {
+            if sort_settings.sort_mode != SortMode::Rayon {
                 continue;
             }
 
@@ -87,6 +130,24 @@ pub fn rayon_sort(
                     let mut chunks = sorted_entries.sorted.chunks_mut(gaussians);
                     let chunk = chunks.nth(trigger.camera_index).unwrap();
 
+                    // clouds with a bvh cull whole subtrees against the frustum before the per gaussian tests
+                    #[cfg(feature = "query_bvh")]
+                    let frustum_mask = bvhs.as_ref()
+                        .and_then(|bvhs| bvhs.get(gaussian_cloud_handle))
+                        .filter(|bvh| bvh.indices.len() == gaussians)
+                        .and_then(|bvh| cull_view.frustum_mask(&cull_config, bvh, &transform.affine(), appearance.global_scale));
+                    #[cfg(not(feature = "query_bvh"))]
+                    let frustum_mask: Option<Vec<bool>> = None;
+
+                    let cloud_cull_view = if frustum_mask.is_some() {
+                        &cull_view_without_frustum
+                    } else {
+                        &cull_view
+                    };
+
+                    let (transform_scale, _, _) = transform.to_scale_rotation_translation();
+                    let radius_scale = 3.0 * appearance.global_scale * transform_scale.abs().max_element();
+
                     gaussian_cloud.position_par_iter()
                         .zip(chunk.par_iter_mut())
                         .enumerate()
@@ -94,28 +155,37 @@ pub fn rayon_sort(
                             let position = Vec3A::from_slice(position.as_ref());
                             let position = transform.affine().transform_point3a(position);
 
+                            sort_entry.index = idx as u32;
+
+                            let (max_scale, opacity) = gaussian_cloud.max_scale_opacity(idx);
+                            let opacity = opacity * appearance.global_opacity;
+
+                            let outside_frustum = frustum_mask.as_ref().is_some_and(|mask| !mask[idx]);
+
+                            if outside_frustum || cloud_cull_view.is_culled(&cull_config, position, max_scale * radius_scale, opacity) {
+                                sort_entry.key = CULLED_SORT_KEY;
+                                return;
+                            }
+
                             let delta = trigger.last_camera_position - position;
 
                             sort_entry.key = bytemuck::cast(delta.length_squared());
-                            sort_entry.index = idx as u32;
                         });
 
                     chunk.par_sort_unstable_by(|a, b| {
                         bytemuck::cast::<u32, f32>(b.key).partial_cmp(&bytemuck::cast::<u32, f32>(a.key)).unwrap_or(std::cmp::Ordering::Equal)
                     });
 
-                    // TODO: update DrawIndirect buffer during sort phase (GPU sort will override default DrawIndirect)
+                    let visible_count = chunk.partition_point(|sort_entry| sort_entry.key != CULLED_SORT_KEY);
+                    sorted_entries.visible_counts[trigger.camera_index] = Some(visible_count as u32);
                 }
             }
         }
-    }
-
-    let sort_end_time = Instant::now();
-    let delta = sort_end_time - sort_start_time;
 
-    if performed_sort {
-        sort_config.period_ms = sort_config.period_ms
-            .max(sort_config.period_ms * 4 / 5)
-            .max(4 * delta.as_millis() as usize);
+        if performed_sort {
+            if let Some(stats) = stats.as_mut() {
+                stats.record_sort(sort_start_time.elapsed());
+            }
+        }
     }
 }
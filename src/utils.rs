@@ -50,6 +50,9 @@ pub struct GaussianSplattingViewer {
     #[arg(long, value_enum, default_value_t = GaussianMode::Gaussian3d)]
     pub gaussian_mode: GaussianMode,
 
+    #[arg(long, default_value = "false")]
+    pub mip_filter: bool,
+
     #[arg(long, default_value = "0")]
     pub particle_count: usize,
 }
@@ -68,6 +71,7 @@ impl Default for GaussianSplattingViewer {
             input_file: "".to_string(),
             gaussian_count: 0,
             gaussian_mode: GaussianMode::Gaussian3d,
+            mip_filter: false,
             particle_count: 0,
         }
     }
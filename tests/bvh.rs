@@ -0,0 +1,114 @@
+use bevy::{
+    math::{
+        Affine3A,
+        Vec3A,
+    },
+    prelude::*,
+    render::primitives::Frustum,
+};
+
+use bevy_gaussian_splatting::{
+    GaussianCloud,
+    query::bvh::GaussianBvh,
+    random_gaussians,
+};
+
+
+fn brute_force(cloud: &GaussianCloud, filter: impl Fn(Vec3A) -> bool) -> Vec<usize> {
+    (0..cloud.len())
+        .filter(|index| filter(Vec3A::from(*cloud.position(*index))))
+        .collect()
+}
+
+fn sorted(mut indices: Vec<usize>) -> Vec<usize> {
+    indices.sort_unstable();
+    indices
+}
+
+
+#[test]
+fn test_bvh_radius_and_nearest() {
+    let cloud = random_gaussians(3000);
+    let bvh = GaussianBvh::new(&cloud);
+
+    let point = Vec3A::new(1.0, -2.0, 3.0);
+    let radius = 6.0;
+
+    let expected = brute_force(&cloud, |position| position.distance(point) <= radius);
+    assert_eq!(sorted(bvh.within_radius(&cloud, point, radius)), expected);
+    assert_eq!(bvh.count_within_radius(&cloud, point, radius), expected.len());
+
+    let mut by_distance = (0..cloud.len()).collect::<Vec<_>>();
+    by_distance.sort_by(|a, b| {
+        let a = Vec3A::from(*cloud.position(*a)).distance_squared(point);
+        let b = Vec3A::from(*cloud.position(*b)).distance_squared(point);
+        a.total_cmp(&b)
+    });
+
+    let nearest = bvh.nearest(&cloud, point, 10)
+        .into_iter()
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    assert_eq!(nearest, by_distance[..10]);
+}
+
+#[test]
+fn test_bvh_boxes() {
+    let cloud = random_gaussians(3000);
+    let bvh = GaussianBvh::new(&cloud);
+
+    let min = Vec3A::new(-5.0, -10.0, 0.0);
+    let max = Vec3A::new(8.0, 4.0, 12.0);
+
+    let expected = brute_force(&cloud, |position| position.cmpge(min).all() && position.cmple(max).all());
+    assert_eq!(sorted(bvh.within_aabb(&cloud, min, max)), expected);
+
+    let isometry = Isometry3d::new(Vec3::new(2.0, 1.0, -3.0), Quat::from_euler(EulerRot::XYZ, 0.4, 1.1, -0.3));
+    let half_size = Vec3A::new(6.0, 3.0, 9.0);
+
+    let expected = brute_force(&cloud, |position| {
+        let local = isometry.inverse() * Vec3::from(position);
+        local.abs().cmple(half_size.into()).all()
+    });
+    assert_eq!(sorted(bvh.within_obb(&cloud, isometry, half_size)), expected);
+}
+
+#[test]
+fn test_bvh_frustum() {
+    let cloud = random_gaussians(3000);
+    let bvh = GaussianBvh::new(&cloud);
+
+    let world_from_local = Affine3A::from_translation(Vec3::new(0.0, 0.0, -30.0));
+    let clip_from_world = Mat4::perspective_rh(0.6, 1.0, 0.1, 100.0);
+    let frustum = Frustum::from_clip_from_world(&clip_from_world);
+
+    let visible = bvh.within_frustum(&frustum, &world_from_local, 1.0);
+
+    // every gaussian center inside the frustum is kept, bounds only make the query conservative
+    for index in 0..cloud.len() {
+        let position = world_from_local.transform_point3a(Vec3A::from(*cloud.position(index)));
+        let inside = frustum.half_spaces.iter()
+            .all(|half_space| half_space.normal().dot(position) + half_space.d() >= 0.0);
+
+        if inside {
+            assert!(visible.contains(&index));
+        }
+    }
+
+    assert!(visible.len() < cloud.len());
+}
+
+#[test]
+fn test_bvh_refit() {
+    let mut cloud = random_gaussians(500);
+    let mut bvh = GaussianBvh::new(&cloud);
+
+    for index in 0..cloud.len() {
+        cloud.position_mut(index)[1] += 100.0;
+    }
+    bvh.refit(&cloud);
+
+    let point = Vec3A::new(0.0, 100.0, 0.0);
+    let expected = brute_force(&cloud, |position| position.distance(point) <= 8.0);
+    assert_eq!(sorted(bvh.within_radius(&cloud, point, 8.0)), expected);
+}
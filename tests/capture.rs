@@ -0,0 +1,40 @@
+#![cfg(feature = "capture")]
+
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::*,
+    render::render_resource::TextureFormat,
+};
+
+use bevy_gaussian_splatting::capture::{
+    GaussianCapture,
+    GaussianCaptureOutput,
+};
+
+
+#[test]
+fn test_capture_turntable() {
+    let target = Vec3::new(1.0, 0.0, -2.0);
+    let poses = GaussianCapture::turntable(target, 4.0, 1.0, 6);
+    assert_eq!(poses.len(), 6);
+
+    for pose in poses.iter() {
+        let offset = pose.translation - target;
+        assert!((offset.xz().length() - 4.0).abs() < 1e-4);
+        assert!((offset.y - 1.0).abs() < 1e-4);
+        assert!(pose.forward().dot((target - pose.translation).normalize()) > 0.999);
+    }
+
+    assert!(poses[0].translation.distance(poses[3].translation) > 7.9);
+}
+
+#[test]
+fn test_capture_output() {
+    let memory = GaussianCaptureOutput::Memory;
+    assert_eq!(memory.frame_path(0), None);
+    assert_eq!(memory.texture_format(), TextureFormat::Rgba8UnormSrgb);
+
+    let png = GaussianCaptureOutput::Png(PathBuf::from("frames"));
+    assert_eq!(png.frame_path(12), Some(PathBuf::from("frames").join("0012.png")));
+}
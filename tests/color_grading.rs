@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::{
+    GaussianCloudAppearance,
+    GaussianColorGrading,
+    material::spherical_harmonics::SH_DEGREE,
+    render::color_grading::GaussianColorGradingUniform,
+};
+
+
+#[test]
+fn test_color_grading_uniform() {
+    let identity = GaussianColorGradingUniform::default();
+    assert_eq!(identity.enabled, 0);
+    assert_eq!(identity.exposure, 1.0);
+    assert!(identity.balance.abs_diff_eq(Mat3::IDENTITY, 1e-3));
+
+    let brighter = GaussianColorGradingUniform::from(&GaussianColorGrading {
+        exposure: 1.0,
+        ..default()
+    });
+    assert_eq!(brighter.enabled, 1);
+    assert_eq!(brighter.exposure, 2.0);
+
+    let warmer = GaussianColorGradingUniform::from(&GaussianColorGrading {
+        temperature: 0.1,
+        ..default()
+    });
+    let white = warmer.balance * Vec3::ONE;
+    assert!(white.x > white.z);
+
+    assert_eq!(GaussianCloudAppearance::default().max_sh_band, SH_DEGREE as u32);
+}
@@ -0,0 +1,73 @@
+use bevy::{
+    math::Vec3A,
+    prelude::*,
+    render::primitives::Frustum,
+};
+
+use bevy_gaussian_splatting::sort::{
+    guard_band_frustum,
+    CullConfig,
+    CullView,
+};
+
+
+fn test_view() -> CullView {
+    let clip_from_view = Mat4::perspective_infinite_reverse_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1);
+
+    CullView {
+        frustum: None,
+        clip_from_world: clip_from_view,
+        pixel_scale: clip_from_view.y_axis.y * 0.5 * 1080.0,
+    }
+}
+
+#[test]
+fn test_cull_opacity() {
+    let view = test_view();
+    let config = CullConfig::default();
+
+    let position = Vec3A::new(0.0, 0.0, -5.0);
+
+    assert!(view.is_culled(&config, position, 1.0, 0.0));
+    assert!(!view.is_culled(&config, position, 1.0, 1.0));
+}
+
+#[test]
+fn test_cull_projected_size() {
+    let view = test_view();
+    let config = CullConfig::default();
+
+    let near = Vec3A::new(0.0, 0.0, -5.0);
+    let far = Vec3A::new(0.0, 0.0, -50000.0);
+
+    assert!(!view.is_culled(&config, near, 0.01, 1.0));
+    assert!(view.is_culled(&config, far, 0.01, 1.0));
+
+    let disabled = CullConfig {
+        enabled: false,
+        ..default()
+    };
+    assert!(!view.is_culled(&disabled, far, 0.01, 1.0));
+}
+
+#[test]
+fn test_cull_frustum_margin() {
+    let view = test_view();
+    let config = CullConfig::default();
+
+    let frustum = Frustum::from_clip_from_world(&view.clip_from_world);
+    let edge = Vec3A::new(5.5, 0.0, -5.0);
+
+    let exact = CullView {
+        frustum: Some(frustum),
+        ..view.clone()
+    };
+    assert!(exact.is_culled(&config, edge, 0.1, 1.0));
+
+    let guarded = CullView {
+        frustum: Some(guard_band_frustum(&frustum, &view.clip_from_world, config.frustum_margin)),
+        ..view
+    };
+    assert!(!guarded.is_culled(&config, edge, 0.1, 1.0));
+    assert!(guarded.is_culled(&config, Vec3A::new(8.0, 0.0, -5.0), 0.1, 1.0));
+}
@@ -0,0 +1,116 @@
+use bevy::{
+    math::Mat3,
+    prelude::*,
+};
+
+use bevy_gaussian_splatting::{
+    gaussian::covariance::{
+        compute_covariance_3d,
+        decompose_covariance_3d,
+        transform_covariance_3d,
+    },
+    query::edit::{
+        delete_gaussians,
+        duplicate_gaussians,
+        recolor_gaussians,
+        transform_gaussians,
+    },
+    random_gaussians,
+    Gaussian,
+    GaussianCloud,
+    SphericalHarmonicCoefficients,
+};
+
+
+fn assert_covariance_eq(a: [f32; 6], b: [f32; 6]) {
+    let scale = a.iter().chain(b.iter()).fold(0.0_f32, |max, value| max.max(value.abs()));
+
+    for (a, b) in a.iter().zip(b.iter()) {
+        assert!((a - b).abs() <= scale * 0.02 + 1e-6, "{a:?} != {b:?}");
+    }
+}
+
+
+/// fixed gaussians, rotations are unit quaternions when `rotation_length` is 1
+fn fixture_gaussians(count: usize, rotation_length: impl Fn(usize) -> f32) -> GaussianCloud {
+    (0..count)
+        .map(|index| {
+            let t = index as f32;
+            let rotation = Quat::from_euler(EulerRot::XYZ, t * 0.37, t * -0.61, t * 1.13);
+            let rotation = Vec4::new(rotation.w, rotation.x, rotation.y, rotation.z) * rotation_length(index);
+
+            Gaussian {
+                rotation: rotation.to_array().into(),
+                position_visibility: [t * 0.5, -t * 0.25, t, 1.0].into(),
+                scale_opacity: [
+                    0.1 + (index % 5) as f32 * 0.2,
+                    0.3 + (index % 3) as f32 * 0.3,
+                    0.05 + (index % 7) as f32 * 0.1,
+                    0.5,
+                ].into(),
+                spherical_harmonic: SphericalHarmonicCoefficients::default(),
+            }
+        })
+        .collect()
+}
+
+
+#[test]
+fn test_decompose_covariance() {
+    let rotation = Quat::from_euler(EulerRot::XYZ, 0.3, -1.2, 2.0);
+    let rotation = Vec4::new(rotation.w, rotation.x, rotation.y, rotation.z);
+    let scale = Vec3::new(0.5, 2.0, 0.1);
+
+    let cov3d = compute_covariance_3d(rotation, scale);
+    let (decomposed_rotation, decomposed_scale) = decompose_covariance_3d(cov3d);
+
+    assert_covariance_eq(compute_covariance_3d(decomposed_rotation, decomposed_scale), cov3d);
+}
+
+#[test]
+fn test_transform_gaussians() {
+    // loaded clouds carry unit rotations, taking the direct similarity path
+    let normalized = fixture_gaussians(64, |_| 1.0);
+    let unnormalized = fixture_gaussians(64, |index| 0.5 + (index % 3) as f32 * 0.4);
+
+    let indicies = (0..32).collect::<Vec<_>>();
+
+    for (original, transform) in [
+        (&normalized, Transform::from_rotation(Quat::from_rotation_y(0.7)).with_scale(Vec3::splat(2.0))),
+        (&unnormalized, Transform::from_rotation(Quat::from_rotation_x(-1.3)).with_scale(Vec3::splat(0.5))),
+        (&normalized, Transform::from_scale(Vec3::new(1.0, 3.0, 0.5))),
+    ] {
+        let mut cloud = original.clone();
+        transform_gaussians(&mut cloud, &indicies, &transform);
+
+        let linear = Mat3::from_quat(transform.rotation) * Mat3::from_diagonal(transform.scale);
+        for &index in &indicies {
+            assert_covariance_eq(
+                cloud.covariance_3d(index),
+                transform_covariance_3d(original.covariance_3d(index), linear),
+            );
+        }
+
+        assert_eq!(cloud.position(40), original.position(40));
+        assert_eq!(cloud.covariance_3d(40), original.covariance_3d(40));
+    }
+}
+
+#[test]
+fn test_delete_duplicate_recolor() {
+    let mut cloud = random_gaussians(100);
+
+    let deleted = delete_gaussians(&cloud, &[0, 5, 99]);
+    assert_eq!(deleted.len(), 97);
+    assert_eq!(deleted.position(0), cloud.position(1));
+    assert_eq!(deleted.position(4), cloud.position(6));
+
+    let duplicate = duplicate_gaussians(&cloud, &[3, 7]);
+    assert_eq!(duplicate.len(), 2);
+    assert_eq!(duplicate.position(1), cloud.position(7));
+    assert_eq!(duplicate.visibility(0), 1.0);
+
+    let red = Vec3::new(1.0, 0.0, 0.0);
+    recolor_gaussians(&mut cloud, &[2], red, 1.0);
+    assert!(cloud.spherical_harmonic(2).base_color().distance(red) < 0.01);
+}
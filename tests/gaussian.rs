@@ -1,5 +1,14 @@
+use bevy::prelude::*;
+
+#[allow(deprecated)]
 use bevy_gaussian_splatting::{
     GaussianCloud,
+    GaussianCloudAppearance,
+    GaussianCloudDebugSettings,
+    GaussianCloudHandle,
+    GaussianCloudRasterSettings,
+    GaussianCloudSettings,
+    GaussianCloudSortSettings,
     io::codec::GaussianCloudCodec,
     random_gaussians,
 };
@@ -15,3 +24,46 @@ fn test_codec() {
 
     assert_eq!(gaussians, decoded);
 }
+
+#[test]
+fn test_required_settings() {
+    let mut world = World::new();
+
+    let appearance = GaussianCloudAppearance {
+        global_opacity: 0.5,
+        ..default()
+    };
+    let entity = world.spawn((
+        GaussianCloudHandle::default(),
+        appearance.clone(),
+    )).id();
+
+    let entity = world.entity(entity);
+    assert_eq!(entity.get::<GaussianCloudAppearance>(), Some(&appearance));
+    assert_eq!(entity.get::<GaussianCloudRasterSettings>(), Some(&GaussianCloudRasterSettings::default()));
+    assert_eq!(entity.get::<GaussianCloudSortSettings>(), Some(&GaussianCloudSortSettings::default()));
+    assert_eq!(entity.get::<GaussianCloudDebugSettings>(), Some(&GaussianCloudDebugSettings::default()));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_settings() {
+    let mut world = World::new();
+
+    let entity = world.spawn((
+        GaussianCloudHandle::default(),
+        GaussianCloudSettings {
+            global_opacity: 0.5,
+            visualize_bounding_box: true,
+            depth_prepass: true,
+            ..default()
+        },
+    )).id();
+    world.flush();
+
+    let entity = world.entity(entity);
+    assert!(entity.get::<GaussianCloudSettings>().is_none());
+    assert_eq!(entity.get::<GaussianCloudAppearance>().unwrap().global_opacity, 0.5);
+    assert!(entity.get::<GaussianCloudDebugSettings>().unwrap().visualize_bounding_box);
+    assert!(entity.get::<GaussianCloudRasterSettings>().unwrap().depth_prepass);
+}
@@ -0,0 +1,148 @@
+#![cfg(not(feature = "precompute_covariance_3d"))]
+
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::{
+    random_gaussians,
+    sort::global::{
+        GlobalSort,
+        GlobalSortPlugin,
+        MergedGaussianCloud,
+    },
+    GaussianCloud,
+    GaussianCloudHandle,
+};
+
+
+fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+    ));
+    app.init_asset::<GaussianCloud>();
+    app.add_plugins(GlobalSortPlugin);
+    app.insert_resource(GlobalSort {
+        enabled: true,
+        ..default()
+    });
+
+    app
+}
+
+fn spawn_member(
+    app: &mut App,
+    cloud: GaussianCloud,
+    transform: Transform,
+) -> Entity {
+    let handle = app.world_mut().resource_mut::<Assets<GaussianCloud>>().add(cloud);
+
+    app.world_mut()
+        .spawn((
+            GaussianCloudHandle(handle),
+            transform,
+            InheritedVisibility::VISIBLE,
+        ))
+        .id()
+}
+
+fn merged_cloud(app: &mut App) -> (GaussianCloud, MergedGaussianCloud) {
+    let (handle, merged) = app.world_mut()
+        .query::<(&GaussianCloudHandle, &MergedGaussianCloud)>()
+        .single(app.world());
+    let merged = merged.clone();
+
+    let cloud = app.world()
+        .resource::<Assets<GaussianCloud>>()
+        .get(&handle.0)
+        .unwrap()
+        .clone();
+
+    (cloud, merged)
+}
+
+fn member_range(merged: &MergedGaussianCloud, entity: Entity) -> std::ops::Range<usize> {
+    merged.members.iter()
+        .find(|(member, _)| *member == entity)
+        .map(|(_, range)| range.clone())
+        .unwrap()
+}
+
+fn assert_member_positions(
+    merged_cloud: &GaussianCloud,
+    range: std::ops::Range<usize>,
+    source: &GaussianCloud,
+    transform: &Transform,
+) {
+    assert_eq!(range.len(), source.len());
+
+    for (index, merged_index) in range.enumerate() {
+        let expected = transform.transform_point(Vec3::from(*source.position(index)));
+        let position = Vec3::from(*merged_cloud.position(merged_index));
+
+        assert!(position.abs_diff_eq(expected, 1e-4), "{position:?} != {expected:?}");
+    }
+}
+
+
+#[test]
+fn test_global_sort_merge() {
+    let mut app = test_app();
+
+    let cloud_a = random_gaussians(8);
+    let cloud_b = random_gaussians(12);
+    let transform_a = Transform::from_xyz(10.0, 0.0, 0.0).with_scale(Vec3::splat(2.0));
+    let transform_b = Transform::from_rotation(Quat::from_rotation_y(1.2)).with_translation(Vec3::new(0.0, -3.0, 5.0));
+
+    let a = spawn_member(&mut app, cloud_a.clone(), transform_a);
+    let b = spawn_member(&mut app, cloud_b.clone(), transform_b);
+
+    app.update();
+
+    let (merged_cloud, merged) = merged_cloud(&mut app);
+    assert_eq!(merged.members.len(), 2);
+    assert_eq!(merged_cloud.len(), cloud_a.len() + cloud_b.len());
+
+    let range_a = member_range(&merged, a);
+    let range_b = member_range(&merged, b);
+    assert!(range_a.end <= range_b.start || range_b.end <= range_a.start);
+
+    assert_member_positions(&merged_cloud, range_a, &cloud_a, &transform_a);
+    assert_member_positions(&merged_cloud, range_b, &cloud_b, &transform_b);
+}
+
+#[test]
+fn test_global_sort_member_change() {
+    let mut app = test_app();
+
+    let cloud_a = random_gaussians(8);
+    let cloud_b = random_gaussians(12);
+    let transform_a = Transform::from_xyz(10.0, 0.0, 0.0);
+    let transform_b = Transform::from_xyz(-10.0, 0.0, 0.0);
+
+    let a = spawn_member(&mut app, cloud_a.clone(), transform_a);
+    let b = spawn_member(&mut app, cloud_b.clone(), transform_b);
+
+    app.update();
+    let (before_cloud, before) = merged_cloud(&mut app);
+
+    let moved_b = Transform::from_xyz(0.0, 4.0, -2.0).with_scale(Vec3::splat(0.5));
+    *app.world_mut().get_mut::<Transform>(b).unwrap() = moved_b;
+
+    app.update();
+    let (after_cloud, after) = merged_cloud(&mut app);
+
+    let range_a = member_range(&after, a);
+    let range_b = member_range(&after, b);
+    assert_eq!(range_a, member_range(&before, a));
+    assert_eq!(range_b, member_range(&before, b));
+
+    for index in range_a.clone() {
+        assert_eq!(after_cloud.position(index), before_cloud.position(index));
+        assert_eq!(after_cloud.rotation_scale_opacity(index), before_cloud.rotation_scale_opacity(index));
+    }
+
+    assert_member_positions(&after_cloud, range_a, &cloud_a, &transform_a);
+    assert_member_positions(&after_cloud, range_b, &cloud_b, &moved_b);
+}
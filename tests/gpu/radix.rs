@@ -39,8 +39,12 @@ use bevy_gaussian_splatting::{
     GaussianCamera,
     GaussianCloud,
     GaussianCloudHandle,
+    GaussianCloudSortSettings,
     random_gaussians,
-    sort::SortedEntries,
+    sort::{
+        SortedEntries,
+        SortMode,
+    },
 };
 
 use _harness::{
@@ -90,9 +94,8 @@ fn setup(
 
     commands.spawn((
         GaussianCloudHandle(cloud),
-        GaussianCloudSettings {
+        GaussianCloudSortSettings {
             sort_mode: SortMode::Radix,
-            ..default()
         },
         Name::new("gaussian_cloud"),
     ));
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::{
+    query::{
+        edit::{
+            delete_gaussians,
+            transform_gaussians,
+        },
+        history::{
+            EditCommand,
+            EditHistory,
+        },
+    },
+    random_gaussians,
+    GaussianCloud,
+};
+
+
+#[test]
+fn test_commands_round_trip() {
+    let original = random_gaussians(100);
+    let mut cloud = original.clone();
+
+    let delete = EditCommand::delete(&mut cloud, &[99, 0, 42, 5]);
+    assert_eq!(cloud, delete_gaussians(&original, &[0, 5, 42, 99]));
+
+    delete.undo(&mut cloud);
+    assert_eq!(cloud, original);
+
+    let indicies = [1, 2, 3];
+    let replace = EditCommand::replace(&mut cloud, &indicies, |cloud| {
+        transform_gaussians(cloud, &indicies, &Transform::from_xyz(1.0, 2.0, 3.0));
+    });
+    let transformed = cloud.clone();
+
+    replace.undo(&mut cloud);
+    assert_eq!(cloud, original);
+    replace.redo(&mut cloud);
+    assert_eq!(cloud, transformed);
+
+    let mut visibility = vec![1.0; cloud.len()];
+    visibility[7] = 0.0;
+    let hide = EditCommand::visibility(&cloud, &visibility).unwrap();
+
+    hide.redo(&mut cloud);
+    assert_eq!(cloud.visibility(7), 0.0);
+    hide.undo(&mut cloud);
+    assert_eq!(cloud, transformed);
+
+    assert!(EditCommand::visibility(&cloud, &vec![1.0; cloud.len()]).is_none());
+}
+
+#[test]
+fn test_delete_undo_ignores_padding() {
+    let original = random_gaussians(30);
+    let mut cloud = original.clone();
+
+    let delete = EditCommand::delete(&mut cloud, &[3, 17]);
+
+    // buffer_texture layouts pad the edited cloud to a square texture
+    cloud.append(&random_gaussians(6));
+
+    delete.undo(&mut cloud);
+    assert_eq!(cloud, original);
+}
+
+#[test]
+fn test_history_stacks() {
+    let mut clouds = Assets::<GaussianCloud>::default();
+
+    let original = random_gaussians(20);
+    let handle = clouds.add(original.clone());
+
+    let mut history = EditHistory {
+        max_entries: 2,
+        ..default()
+    };
+
+    for index in 0..3 {
+        let command = EditCommand::delete(clouds.get_mut(&handle).unwrap(), &[index]);
+        history.record(&handle, command);
+    }
+    assert_eq!(history.undo.len(), 2);
+    assert_eq!(clouds.get(&handle).unwrap().len(), 17);
+
+    assert!(history.undo(&mut clouds).is_some());
+    assert!(history.undo(&mut clouds).is_some());
+    assert!(history.undo(&mut clouds).is_none());
+    assert_eq!(clouds.get(&handle).unwrap().len(), 19);
+
+    assert!(history.redo(&mut clouds).is_some());
+    assert_eq!(clouds.get(&handle).unwrap().len(), 18);
+    assert!(history.can_redo());
+
+    let command = EditCommand::delete(clouds.get_mut(&handle).unwrap(), &[0]);
+    history.record(&handle, command);
+    assert!(!history.can_redo());
+}
@@ -1,6 +1,14 @@
+use bevy::prelude::*;
+
 use bevy_gaussian_splatting::{
     GaussianCloud,
-    io::codec::GaussianCloudCodec,
+    io::{
+        codec::GaussianCloudCodec,
+        writer::{
+            write_gaussian_cloud,
+            GaussianCloudFormat,
+        },
+    },
     random_gaussians,
 };
 
@@ -15,3 +23,178 @@ fn test_codec() {
 
     assert_eq!(gaussians, decoded);
 }
+
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_round_trip() {
+    use bevy_gaussian_splatting::{
+        io::ply::parse_ply,
+        material::spherical_harmonics::{
+            SH_CHANNELS,
+            SH_COEFF_COUNT_PER_CHANNEL,
+        },
+    };
+
+    let count = 100;
+    let cloud = random_gaussians(count);
+
+    let mut bytes = Vec::new();
+    write_gaussian_cloud(&cloud, &mut bytes, GaussianCloudFormat::Ply).unwrap();
+
+    let parsed = parse_ply(&mut std::io::Cursor::new(bytes)).unwrap();
+    assert!(parsed.len() >= count);
+
+    for (index, gaussian) in parsed.iter().take(count).enumerate() {
+        let (rotation, scale, opacity) = cloud.rotation_scale_opacity(index);
+
+        assert_eq!(gaussian.position_visibility.position, *cloud.position(index));
+        assert!((gaussian.scale_opacity.opacity - opacity).abs() < 1e-3);
+        assert!((Vec3::from(gaussian.scale_opacity.scale) - scale).abs().max_element() < 1e-3 * scale.max_element().max(1.0));
+        assert!((Vec4::from(gaussian.rotation.rotation) - rotation.normalize()).abs().max_element() < 1e-3);
+
+        for coefficient in 0..SH_COEFF_COUNT_PER_CHANNEL * SH_CHANNELS {
+            assert_eq!(gaussian.spherical_harmonic.get(coefficient), cloud.spherical_harmonic(index).get(coefficient));
+        }
+    }
+}
+
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_pbr_material() {
+    use bevy_gaussian_splatting::{
+        io::ply::parse_ply_materials,
+        material::pbr::PbrMaterial,
+    };
+
+    let count = 64;
+    let mut cloud = random_gaussians(count);
+    assert!(cloud.pbr_material(0).is_none());
+
+    cloud.set_pbr_material(
+        (0..count)
+            .map(|index| {
+                let t = index as f32 / count as f32;
+                PbrMaterial::new([t, 0.5, 1.0 - t], t, 1.0 - t)
+            })
+            .collect(),
+    );
+
+    let mut bytes = Vec::new();
+    write_gaussian_cloud(&cloud, &mut bytes, GaussianCloudFormat::Ply).unwrap();
+
+    let (gaussians, pbr_material) = parse_ply_materials(&mut std::io::Cursor::new(bytes)).unwrap();
+    assert!(gaussians.len() >= count);
+
+    for (index, material) in pbr_material.iter().take(count).enumerate() {
+        let expected = cloud.pbr_material(index).unwrap();
+
+        assert!(material.is_present());
+        assert_eq!(material, expected);
+    }
+
+    // clouds without pbr attributes keep an empty plane
+    let mut bytes = Vec::new();
+    write_gaussian_cloud(&random_gaussians(count), &mut bytes, GaussianCloudFormat::Ply).unwrap();
+
+    let (_, pbr_material) = parse_ply_materials(&mut std::io::Cursor::new(bytes)).unwrap();
+    assert!(pbr_material.is_empty());
+}
+
+
+#[cfg(feature = "io_ply")]
+#[test]
+fn test_ply_filter_3d() {
+    use bevy_gaussian_splatting::io::ply::parse_ply_cloud;
+
+    let count = 64;
+    let mut cloud = random_gaussians(count);
+    assert_eq!(cloud.filter_3d(0), 0.0);
+
+    cloud.set_filter_3d((0..count).map(|index| index as f32 * 0.001).collect());
+
+    let mut bytes = Vec::new();
+    write_gaussian_cloud(&cloud, &mut bytes, GaussianCloudFormat::Ply).unwrap();
+
+    let parsed = parse_ply_cloud(&mut std::io::Cursor::new(bytes)).unwrap();
+    for index in 0..count {
+        assert_eq!(parsed.filter_3d(index), cloud.filter_3d(index));
+    }
+
+    // subsets keep the filters of their gaussians
+    let subset = cloud.subset(&[3, 7]);
+    assert_eq!(subset.filter_3d(1), cloud.filter_3d(7));
+
+    let mut bytes = Vec::new();
+    write_gaussian_cloud(&random_gaussians(count), &mut bytes, GaussianCloudFormat::Ply).unwrap();
+
+    let parsed = parse_ply_cloud(&mut std::io::Cursor::new(bytes)).unwrap();
+    assert!(parsed.filter_3d.is_empty());
+}
+
+
+#[cfg(feature = "io_spz")]
+#[test]
+fn test_spz_header() {
+    use std::io::Read;
+
+    use bevy_gaussian_splatting::io::spz::{
+        SPZ_MAGIC,
+        SPZ_VERSION,
+    };
+
+    let count = 64;
+    let cloud = random_gaussians(count);
+
+    let mut bytes = Vec::new();
+    write_gaussian_cloud(&cloud, &mut bytes, GaussianCloudFormat::Spz).unwrap();
+
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(bytes.as_slice())
+        .read_to_end(&mut decompressed)
+        .unwrap();
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(decompressed[offset..offset + 4].try_into().unwrap());
+    assert_eq!(read_u32(0), SPZ_MAGIC);
+    assert_eq!(read_u32(4), SPZ_VERSION);
+    assert_eq!(read_u32(8) as usize, cloud.len());
+
+    let sh_degree = decompressed[12] as usize;
+    let sh_rest_count = (sh_degree + 1) * (sh_degree + 1) - 1;
+    let per_gaussian = 9 + 1 + 3 + 3 + 3 + sh_rest_count * 3;
+    assert_eq!(decompressed.len(), 16 + cloud.len() * per_gaussian);
+}
+
+
+#[test]
+fn test_format_from_path() {
+    assert_eq!(GaussianCloudFormat::from_path("scene.PLY"), Some(GaussianCloudFormat::Ply));
+    assert_eq!(GaussianCloudFormat::from_path("scene.spz"), Some(GaussianCloudFormat::Spz));
+    assert_eq!(GaussianCloudFormat::from_path("scene.gcloud"), Some(GaussianCloudFormat::Gcloud));
+    assert_eq!(GaussianCloudFormat::from_path("scene"), None);
+}
+
+
+#[cfg(feature = "io_bincode2")]
+#[test]
+fn test_bincode2_unversioned() {
+    use std::io::Write;
+
+    use flate2::{
+        Compression,
+        write::GzEncoder,
+    };
+
+    let gaussians = random_gaussians(100);
+
+    // clouds written before the format version lack the header and the two trailing vecs
+    let mut payload = bincode2::serialize(&gaussians).unwrap();
+    payload.truncate(payload.len() - 16);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&payload).unwrap();
+    let unversioned = encoder.finish().unwrap();
+
+    assert_eq!(GaussianCloud::decode(unversioned.as_slice()), gaussians);
+}
@@ -0,0 +1,123 @@
+use bevy_gaussian_splatting::lighting::GaussianCloudLighting;
+
+
+#[test]
+fn test_unlit() {
+    assert!(!GaussianCloudLighting::UNLIT.is_lit());
+    assert_eq!(GaussianCloudLighting::default().is_lit(), cfg!(feature = "lighting"));
+}
+
+#[cfg(feature = "lighting")]
+#[test]
+fn test_point_light_uniform() {
+    use bevy::{
+        pbr::ExtractedPointLight,
+        prelude::*,
+    };
+    use bevy_gaussian_splatting::lighting::direct::GaussianPointLightUniform;
+
+    let mut light = ExtractedPointLight {
+        color: LinearRgba::rgb(1.0, 0.5, 0.25),
+        intensity: 2.0,
+        range: 10.0,
+        radius: 0.0,
+        transform: GlobalTransform::from_xyz(0.0, 3.0, 0.0),
+        shadows_enabled: false,
+        shadow_depth_bias: 0.0,
+        shadow_normal_bias: 0.0,
+        shadow_map_near_z: 0.1,
+        spot_light_angles: None,
+        volumetric: false,
+        soft_shadows_enabled: false,
+    };
+
+    let point = GaussianPointLightUniform::from(&light);
+    assert_eq!(point.position, Vec3::new(0.0, 3.0, 0.0));
+    assert_eq!(point.color, Vec3::new(2.0, 1.0, 0.5));
+    assert_eq!((point.spot_scale, point.spot_offset), (0.0, 1.0));
+
+    light.spot_light_angles = Some((0.0, std::f32::consts::FRAC_PI_2));
+    let spot = GaussianPointLightUniform::from(&light);
+
+    // full intensity on the cone axis, none at the outer angle
+    let on_axis = spot.spot_scale + spot.spot_offset;
+    let at_outer = spot.spot_offset;
+    assert!((on_axis - 1.0).abs() < 1e-5);
+    assert!(at_outer.abs() < 1e-5);
+}
+
+#[cfg(feature = "lighting")]
+#[test]
+fn test_environment_projection() {
+    use bevy::{
+        prelude::*,
+        render::{
+            extract_component::ExtractComponent,
+            render_asset::RenderAssetUsages,
+            render_resource::{
+                Extent3d,
+                TextureDimension,
+                TextureFormat,
+            },
+        },
+    };
+    use bevy_gaussian_splatting::lighting::environmental::{
+        project_irradiance,
+        GaussianEnvironmentIrradiance,
+    };
+
+    let size = 8;
+    let cubemap = |face_color: &dyn Fn(usize) -> [f32; 4]| {
+        let data = (0..6)
+            .flat_map(|face| std::iter::repeat_n(face_color(face), size * size))
+            .flatten()
+            .flat_map(f32::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        Image::new(
+            Extent3d {
+                width: size as u32,
+                height: size as u32,
+                depth_or_array_layers: 6,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba32Float,
+            RenderAssetUsages::default(),
+        )
+    };
+
+    let uniform = GaussianEnvironmentIrradiance {
+        coefficients: project_irradiance(&cubemap(&|_| [0.25, 0.5, 1.0, 1.0])).unwrap(),
+        ..default()
+    };
+    for direction in [Vec3::X, Vec3::NEG_Y, Vec3::new(1.0, 1.0, -1.0)] {
+        assert!(uniform.evaluate(direction).abs_diff_eq(Vec3::new(0.25, 0.5, 1.0), 1e-3));
+    }
+
+    // a single lit face, bevy samples cubemaps with z flipped
+    let lit_face = |lit: usize| GaussianEnvironmentIrradiance {
+        coefficients: project_irradiance(&cubemap(&|face| if face == lit { [1.0; 4] } else { [0.0; 4] })).unwrap(),
+        ..default()
+    };
+
+    let sky = lit_face(2);
+    assert!(sky.evaluate(Vec3::Y).x > 4.0 * sky.evaluate(Vec3::NEG_Y).x);
+
+    let front = lit_face(4);
+    assert!(front.evaluate(Vec3::NEG_Z).x > 4.0 * front.evaluate(Vec3::Z).x);
+
+    // world normals are rotated into map space, a rotated map moves its lit face with it
+    let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+    let view_environment = GaussianEnvironmentIrradiance::extract_component((
+        &front,
+        &EnvironmentMapLight {
+            rotation,
+            ..default()
+        },
+    )).unwrap();
+
+    let lit_direction = rotation * Vec3::NEG_Z;
+    assert!(view_environment.rotation.mul_vec3(lit_direction).abs_diff_eq(Vec3::NEG_Z, 1e-5));
+    assert!(front.evaluate(view_environment.rotation * lit_direction).x > 4.0 * front.evaluate(view_environment.rotation * Vec3::NEG_Z).x);
+}
@@ -0,0 +1,53 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{
+        AsBindGroup,
+        ShaderRef,
+    },
+};
+
+use bevy_gaussian_splatting::render::material::{
+    GaussianMaterial,
+    GaussianMaterialPlugin,
+    GaussianMaterialShaders,
+};
+
+
+#[derive(Asset, AsBindGroup, Clone, TypePath)]
+struct HologramMaterial {
+    #[uniform(0)]
+    tint: LinearRgba,
+}
+
+impl GaussianMaterial for HologramMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/hologram.wgsl".into()
+    }
+}
+
+
+#[test]
+fn test_material_entry_shader() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+    ));
+    app.init_asset::<Shader>();
+    app.add_plugins(GaussianMaterialPlugin::<HologramMaterial>::default());
+    app.finish();
+
+    let shaders = app.world().resource::<GaussianMaterialShaders<HologramMaterial>>();
+    assert!(shaders.vertex.is_none());
+    assert!(shaders.fragment.is_some());
+
+    let entry = app.world().resource::<Assets<Shader>>().get(&shaders.entry).unwrap();
+    let source = match &entry.source {
+        bevy::render::render_resource::Source::Wgsl(source) => source,
+        _ => panic!("expected a wgsl entry shader"),
+    };
+
+    assert!(source.starts_with("#import \"shaders/hologram.wgsl\"::material_fragment\n"));
+    assert!(!source.contains("::material_vertex\n"));
+    assert!(source.contains("fn vs_points("));
+}
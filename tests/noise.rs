@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::material::noise::{
+    GaussianNoiseUniform,
+    NoiseMaterial,
+    NoiseMaterialMode,
+    MAX_NOISE_OCTAVES,
+};
+
+
+#[test]
+fn test_noise_uniform() {
+    let ridged = GaussianNoiseUniform::from(&NoiseMaterial {
+        octaves: 32,
+        color_strength: 2.0,
+        ..default()
+    });
+    assert_eq!(ridged.mode, 2);
+    assert_eq!(ridged.octaves, MAX_NOISE_OCTAVES);
+    assert_eq!(ridged.color_strength, 1.0);
+
+    let simplex = GaussianNoiseUniform::from(&NoiseMaterial {
+        mode: NoiseMaterialMode::Simplex,
+        ..default()
+    });
+    assert_eq!(simplex.mode, 1);
+
+    let reseeded = GaussianNoiseUniform::from(&NoiseMaterial {
+        seed: 7,
+        ..default()
+    });
+    assert_eq!(ridged.seed_offset, simplex.seed_offset);
+    assert_ne!(ridged.seed_offset, reseeded.seed_offset);
+    assert!(reseeded.seed_offset.cmpge(Vec3::ZERO).all() && reseeded.seed_offset.cmplt(Vec3::splat(289.0)).all());
+
+    assert_eq!(GaussianNoiseUniform::default().mode, 0);
+}
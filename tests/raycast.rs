@@ -0,0 +1,88 @@
+use bevy::math::Vec3A;
+
+use bevy_gaussian_splatting::{
+    Gaussian,
+    GaussianCloud,
+    query::{
+        bvh::GaussianBvh,
+        raycast::{
+            raycast_cloud,
+            raycast_gaussian,
+            GaussianRaycastSettings,
+        },
+    },
+    random_gaussians,
+};
+
+
+#[test]
+fn test_raycast_unit_gaussian() {
+    let cloud = GaussianCloud::from_gaussians(vec![
+        Gaussian {
+            rotation: [1.0, 0.0, 0.0, 0.0].into(),
+            position_visibility: [0.0, 0.0, 0.0, 1.0].into(),
+            scale_opacity: [1.0, 1.0, 1.0, 1.0].into(),
+            ..Default::default()
+        },
+    ]);
+    let bvh = GaussianBvh::new(&cloud);
+
+    // density exp(-2) is reached two standard deviations from the center
+    let settings = GaussianRaycastSettings {
+        density_threshold: (-2.0_f32).exp(),
+        ..Default::default()
+    };
+
+    let hit = raycast_cloud(
+        &cloud,
+        &bvh,
+        Vec3A::new(0.0, 0.0, 10.0),
+        Vec3A::NEG_Z,
+        1.0,
+        1.0,
+        &settings,
+    ).expect("ray through the center should hit");
+
+    assert_eq!(hit.index, 0);
+    assert!((hit.distance - 8.0).abs() < 1e-2);
+    assert!(hit.normal.abs_diff_eq(Vec3A::Z, 1e-3));
+
+    let miss = raycast_cloud(
+        &cloud,
+        &bvh,
+        Vec3A::new(3.0, 0.0, 10.0),
+        Vec3A::NEG_Z,
+        1.0,
+        1.0,
+        &settings,
+    );
+    assert!(miss.is_none());
+}
+
+#[test]
+fn test_raycast_bvh_matches_brute_force() {
+    let cloud = random_gaussians(2000);
+    let bvh = GaussianBvh::new(&cloud);
+    let settings = GaussianRaycastSettings::default();
+
+    let mut hits = 0;
+    for i in 0..64 {
+        let angle = i as f32 * 0.37;
+        let origin = Vec3A::new(40.0 * angle.cos(), 5.0 * angle.sin(), 40.0 * angle.sin());
+        let direction = (Vec3A::new(angle.sin(), angle.cos(), 0.0) * 4.0 - origin).normalize();
+
+        let expected = (0..cloud.len())
+            .filter_map(|index| {
+                raycast_gaussian(&cloud, index, origin, direction, 1.0, 1.0, settings.density_threshold)
+                    .map(|(distance, _)| distance)
+            })
+            .min_by(f32::total_cmp);
+
+        let hit = raycast_cloud(&cloud, &bvh, origin, direction, 1.0, 1.0, &settings);
+
+        assert_eq!(hit.map(|hit| hit.distance), expected);
+        hits += hit.is_some() as usize;
+    }
+
+    assert!(hits > 0);
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::render::reveal::{
+    GaussianCloudReveal,
+    GaussianCloudRevealMode,
+    GaussianRevealUniform,
+};
+
+
+#[test]
+fn test_reveal_uniform() {
+    let hidden = GaussianRevealUniform::from(&GaussianCloudReveal {
+        progress: 0.0,
+        ..default()
+    });
+    assert_eq!(hidden.mode, 1);
+    assert_eq!(hidden.front, 0.0);
+
+    let planar = GaussianRevealUniform::from(&GaussianCloudReveal {
+        mode: GaussianCloudRevealMode::Planar,
+        progress: 1.0,
+        direction: Vec3::new(0.0, 0.0, 4.0),
+        extent: 2.0,
+        feather: 0.5,
+        ..default()
+    });
+    assert_eq!(planar.mode, 2);
+    assert_eq!(planar.direction, Vec3::Z);
+    assert_eq!(planar.front, 2.5);
+
+    let planar_hidden = GaussianRevealUniform::from(&GaussianCloudReveal {
+        mode: GaussianCloudRevealMode::Planar,
+        progress: 0.0,
+        extent: 2.0,
+        ..default()
+    });
+    assert_eq!(planar_hidden.front, -2.0);
+
+    assert_eq!(GaussianRevealUniform::default().mode, 0);
+}
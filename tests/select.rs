@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::{
+    query::{
+        bvh::GaussianBvh,
+        select::{
+            SaveSelectionEvent,
+            Select,
+            SelectMode,
+        },
+        select_tools::{
+            polygon_contains,
+            BrushSelect,
+            ColorSelect,
+            OpacitySelect,
+            RectSelect,
+            SelectTool,
+            ViewportProjection,
+        },
+    },
+    random_gaussians,
+};
+
+
+#[test]
+fn test_select_modes() {
+    let mut select = Select::from_iter([1, 2, 3]);
+
+    select.apply(vec![3, 4], SelectMode::Add);
+    assert_eq!(select.indicies, vec![1, 2, 3, 4]);
+
+    select.apply(vec![1, 4, 7], SelectMode::Subtract);
+    assert_eq!(select.indicies, vec![2, 3]);
+
+    select.apply(vec![3, 5], SelectMode::Intersect);
+    assert_eq!(select.indicies, vec![3]);
+
+    select.apply(vec![9, 8], SelectMode::Replace);
+    assert_eq!(select.indicies, vec![8, 9]);
+    assert!(!select.completed);
+}
+
+#[test]
+fn test_save_selection_entity_path() {
+    let entity = Entity::from_raw(12);
+
+    let event = SaveSelectionEvent::new("output/live.ply");
+    assert_eq!(event.entity_path(entity), PathBuf::from(format!("output/live_{}.ply", entity)));
+
+    let event = SaveSelectionEvent::new("live").with_entity(entity);
+    assert_eq!(event.entity, Some(entity));
+    assert_eq!(event.entity_path(entity), PathBuf::from(format!("live_{}", entity)));
+}
+
+#[test]
+fn test_viewport_projection() {
+    let projection = ViewportProjection {
+        clip_from_local: Mat4::orthographic_rh(-1.0, 1.0, -1.0, 1.0, -10.0, 10.0),
+        viewport: Rect::new(0.0, 0.0, 200.0, 100.0),
+    };
+
+    assert_eq!(projection.project(Vec3::ZERO), Some(Vec2::new(100.0, 50.0)));
+    assert_eq!(projection.project(Vec3::new(-1.0, 1.0, 0.0)), Some(Vec2::new(0.0, 0.0)));
+    assert_eq!(projection.project(Vec3::new(0.0, 0.0, 20.0)), None);
+
+    let triangle = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(0.0, 10.0)];
+    assert!(polygon_contains(&triangle, Vec2::new(2.0, 2.0)));
+    assert!(!polygon_contains(&triangle, Vec2::new(6.0, 6.0)));
+}
+
+#[test]
+fn test_brush_and_attribute_select() {
+    let cloud = random_gaussians(2000);
+    let transform = GlobalTransform::from(Transform::from_xyz(5.0, 0.0, 0.0));
+
+    let brush = BrushSelect {
+        center: Vec3::new(5.0, 2.0, 0.0),
+        radius: 10.0,
+        ..default()
+    };
+    let expected = (0..cloud.len())
+        .filter(|&index| {
+            let position = Vec3::from(*cloud.position(index)) + Vec3::X * 5.0;
+            position.distance(brush.center) <= brush.radius
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(brush.select(&cloud, None, &transform, None), expected);
+
+    let opacity = OpacitySelect {
+        min: 0.25,
+        max: 0.75,
+        ..default()
+    };
+    for index in opacity.select(&cloud, None, &transform, None) {
+        let (_, value) = cloud.max_scale_opacity(index);
+        assert!((0.25..=0.75).contains(&value));
+    }
+
+    let target = cloud.spherical_harmonic(0).base_color().clamp(Vec3::ZERO, Vec3::ONE);
+    let color = ColorSelect {
+        color: Color::srgb(target.x, target.y, target.z),
+        tolerance: 0.01,
+        ..default()
+    };
+    assert!(color.select(&cloud, None, &transform, None).contains(&0));
+}
+
+#[test]
+fn test_bvh_select() {
+    let cloud = random_gaussians(4000);
+    let bvh = GaussianBvh::new(&cloud);
+    let transform = GlobalTransform::from(
+        Transform::from_xyz(1.0, -2.0, 0.5)
+            .with_rotation(Quat::from_rotation_y(0.7))
+            .with_scale(Vec3::new(0.5, 2.0, 1.0)),
+    );
+
+    let sorted = |mut indices: Vec<usize>| {
+        indices.sort_unstable();
+        indices
+    };
+
+    let brush = BrushSelect {
+        center: Vec3::new(2.0, -1.0, 0.0),
+        radius: 8.0,
+        ..default()
+    };
+    let expected = brush.select(&cloud, None, &transform, None);
+    assert!(!expected.is_empty());
+    assert_eq!(sorted(brush.select(&cloud, Some(&bvh), &transform, None)), expected);
+
+    let projection = ViewportProjection {
+        clip_from_local: Mat4::perspective_infinite_reverse_rh(1.0, 2.0, 0.1)
+            * Mat4::from_translation(Vec3::new(0.0, 0.0, -80.0))
+            * transform.compute_matrix(),
+        viewport: Rect::new(0.0, 0.0, 200.0, 100.0),
+    };
+    let rect = RectSelect {
+        camera: Entity::PLACEHOLDER,
+        rect: Rect::new(60.0, 20.0, 130.0, 70.0),
+        mode: SelectMode::Replace,
+        completed: false,
+    };
+    let expected = rect.select(&cloud, None, &transform, Some(&projection));
+    assert!(!expected.is_empty());
+    assert_eq!(sorted(rect.select(&cloud, Some(&bvh), &transform, Some(&projection))), expected);
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use bevy::{
+    math::Vec3A,
+    prelude::*,
+};
+
+use bevy_gaussian_splatting::sort::{
+    SortConfig,
+    SortStats,
+    SortTrigger,
+};
+
+
+fn test_config() -> SortConfig {
+    SortConfig {
+        translation_threshold: 1.0,
+        rotation_threshold: 0.5,
+        ..default()
+    }
+}
+
+fn assert_duration_eq(a: Duration, b: Duration) {
+    assert!(a.abs_diff(b) < Duration::from_micros(1), "{a:?} != {b:?}");
+}
+
+
+#[test]
+fn test_sort_trigger_translation_threshold() {
+    let config = test_config();
+    let trigger = SortTrigger {
+        last_camera_position: Vec3A::new(2.0, 0.0, 0.0),
+        last_camera_rotation: Quat::IDENTITY,
+        ..default()
+    };
+
+    let below = GlobalTransform::from_translation(Vec3::new(2.5, 0.0, 0.0));
+    let at = GlobalTransform::from_translation(Vec3::new(3.0, 0.0, 0.0));
+    let above = GlobalTransform::from_translation(Vec3::new(3.5, 0.0, 0.0));
+
+    assert!(!trigger.exceeds_motion_threshold(&below, &config));
+    assert!(!trigger.exceeds_motion_threshold(&at, &config));
+    assert!(trigger.exceeds_motion_threshold(&above, &config));
+}
+
+#[test]
+fn test_sort_trigger_rotation_threshold() {
+    let trigger = SortTrigger {
+        last_camera_position: Vec3A::ZERO,
+        last_camera_rotation: Quat::IDENTITY,
+        ..default()
+    };
+
+    let at_rotation = Quat::from_rotation_y(0.5);
+    let config = SortConfig {
+        rotation_threshold: Quat::IDENTITY.angle_between(at_rotation),
+        ..test_config()
+    };
+
+    let below = GlobalTransform::from_rotation(Quat::from_rotation_y(0.25));
+    let at = GlobalTransform::from_rotation(at_rotation);
+    let above = GlobalTransform::from_rotation(Quat::from_rotation_y(0.75));
+
+    assert!(!trigger.exceeds_motion_threshold(&below, &config));
+    assert!(!trigger.exceeds_motion_threshold(&at, &config));
+    assert!(trigger.exceeds_motion_threshold(&above, &config));
+}
+
+#[test]
+fn test_sort_stats_average() {
+    let mut stats = SortStats::default();
+
+    stats.record_sort(Duration::from_millis(100));
+    assert_eq!(stats.sort_count, 1);
+    assert_eq!(stats.last_sort_duration, Duration::from_millis(100));
+    assert_eq!(stats.average_sort_duration, Duration::from_millis(100));
+
+    stats.record_sort(Duration::from_millis(200));
+    assert_eq!(stats.sort_count, 2);
+    assert_eq!(stats.last_sort_duration, Duration::from_millis(200));
+    assert_duration_eq(stats.average_sort_duration, Duration::from_millis(110));
+
+    stats.record_sort(Duration::from_millis(10));
+    assert_duration_eq(stats.average_sort_duration, Duration::from_millis(100));
+
+    stats.record_deferred();
+    assert_eq!(stats.deferred_count, 1);
+    assert_eq!(stats.sort_count, 3);
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+use bevy_gaussian_splatting::render::volume_mask::{
+    MeshVoxels,
+    VolumeMask,
+    VolumeMaskMode,
+    VolumeMaskShape,
+    VolumeMaskVoxels,
+};
+
+
+#[test]
+fn test_primitive_masks() {
+    let voxels = VolumeMaskVoxels::default();
+    let transform = GlobalTransform::from(Transform::from_xyz(0.0, 2.0, 0.0));
+
+    let sphere = VolumeMask {
+        shape: VolumeMaskShape::Sphere(Sphere::new(1.0)),
+        ..default()
+    };
+    assert!(sphere.contains(&transform, Vec3::new(0.0, 2.5, 0.0), &voxels));
+    assert!(!sphere.contains(&transform, Vec3::ZERO, &voxels));
+
+    let inverted_box = VolumeMask {
+        shape: VolumeMaskShape::Box(Cuboid::new(2.0, 2.0, 2.0)),
+        mode: VolumeMaskMode::Clip,
+        invert: true,
+        cloud: None,
+    };
+    assert!(!inverted_box.contains(&transform, Vec3::new(0.5, 2.5, -0.5), &voxels));
+    assert!(inverted_box.contains(&transform, Vec3::new(1.5, 2.0, 0.0), &voxels));
+
+    let capsule = VolumeMaskShape::Capsule(Capsule3d::new(0.5, 2.0));
+    assert!(capsule.contains(Vec3::new(0.0, 1.4, 0.0), None));
+    assert!(!capsule.contains(Vec3::new(0.6, 0.0, 0.0), None));
+}
+
+#[test]
+fn test_mesh_voxels() {
+    let mesh = Mesh::from(Cuboid::new(2.0, 2.0, 2.0));
+    let voxels = MeshVoxels::from_mesh(&mesh).expect("cuboid meshes are closed triangle lists");
+
+    assert!(voxels.contains(Vec3::ZERO));
+    assert!(voxels.contains(Vec3::new(0.8, -0.8, 0.8)));
+    assert!(!voxels.contains(Vec3::new(1.2, 0.0, 0.0)));
+    assert!(!voxels.contains(Vec3::new(0.0, 3.0, 0.0)));
+
+    let sphere = Mesh::from(Sphere::new(1.0));
+    let voxels = MeshVoxels::from_mesh(&sphere).expect("sphere meshes are closed triangle lists");
+
+    assert!(voxels.contains(Vec3::new(0.0, 0.5, 0.2)));
+    assert!(!voxels.contains(Vec3::new(0.9, 0.9, 0.0)));
+}
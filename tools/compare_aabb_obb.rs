@@ -18,7 +18,8 @@ use bevy_gaussian_splatting::{
     GaussianCamera,
     GaussianCloud,
     GaussianCloudHandle,
-    GaussianCloudSettings,
+    GaussianCloudDebugSettings,
+    GaussianCloudRasterSettings,
     GaussianSplattingPlugin,
     utils::{
         setup_hooks,
@@ -51,11 +52,13 @@ pub fn setup_aabb_obb_compare(
                 ])
             )
         ),
-        GaussianCloudSettings {
+        GaussianCloudRasterSettings {
             aabb: true,
-            visualize_bounding_box: true,
             ..default()
         },
+        GaussianCloudDebugSettings {
+            visualize_bounding_box: true,
+        },
         Name::new("gaussian_cloud_aabb"),
     ));
 
@@ -78,11 +81,13 @@ pub fn setup_aabb_obb_compare(
                 ])
             )
         ),
-        GaussianCloudSettings {
+        GaussianCloudRasterSettings {
             aabb: false,
-            visualize_bounding_box: true,
             ..default()
         },
+        GaussianCloudDebugSettings {
+            visualize_bounding_box: true,
+        },
         Name::new("gaussian_cloud_obb"),
     ));
 
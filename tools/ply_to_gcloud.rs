@@ -4,15 +4,20 @@ use byte_unit::{
 };
 
 use bevy_gaussian_splatting::{
-    GaussianCloud,
     io::{
-        ply::parse_ply,
-        writer::write_gaussian_cloud_to_file,
+        ply::parse_ply_cloud,
+        writer::{
+            write_gaussian_cloud_to_file,
+            GaussianCloudFormat,
+        },
     },
 };
 
 #[cfg(feature = "query_sparse")]
-use bevy_gaussian_splatting::query::sparse::SparseSelect;
+use bevy_gaussian_splatting::query::{
+    bvh::GaussianBvh,
+    sparse::SparseSelect,
+};
 
 
 #[allow(dead_code)]
@@ -42,9 +47,7 @@ fn main() {
     let file = std::fs::File::open(&filename).expect("failed to open file");
     let mut reader = std::io::BufReader::new(file);
 
-    let mut cloud = GaussianCloud::from_gaussians(
-        parse_ply(&mut reader).expect("failed to parse ply file"),
-    );
+    let mut cloud = parse_ply_cloud(&mut reader).expect("failed to parse ply file");
 
     // TODO: prioritize mesh selection over export filter
     // println!("initial cloud size: {}", cloud.len());
@@ -60,7 +63,8 @@ fn main() {
 
     #[cfg(feature = "query_sparse")]
     {
-        let sparse_selection = SparseSelect::default().select(&cloud).invert(cloud.len());
+        let bvh = GaussianBvh::new(&cloud);
+        let sparse_selection = SparseSelect::default().select(&cloud, &bvh).invert(cloud.len());
 
         cloud = sparse_selection.indicies.iter()
             .map(|idx| cloud.gaussian(*idx))
@@ -71,7 +75,8 @@ fn main() {
     let base_filename = filename.split('.').next().expect("no extension").to_string();
     let gcloud_filename = base_filename + ".gcloud";
 
-    write_gaussian_cloud_to_file(&cloud, &gcloud_filename);
+    write_gaussian_cloud_to_file(&cloud, &gcloud_filename, GaussianCloudFormat::Gcloud)
+        .expect("failed to write gcloud file");
 
     let post_encode_bytes = Byte::from_u64(std::fs::metadata(&gcloud_filename).expect("failed to get metadata").len());
     println!("output file size: {}", post_encode_bytes.get_appropriate_unit(UnitType::Decimal));
@@ -19,7 +19,9 @@ use bevy_gaussian_splatting::{
     GaussianCloud,
     GaussianCloudHandle,
     GaussianMode,
-    GaussianCloudSettings,
+    GaussianCloudAppearance,
+    GaussianCloudDebugSettings,
+    GaussianCloudRasterSettings,
     GaussianSplattingPlugin,
     gaussian::f32::Rotation,
     utils::{
@@ -74,9 +76,8 @@ pub fn setup_surfel_compare(
     let cloud = gaussian_assets.add(GaussianCloud::from_gaussians(blue_gaussians));
     commands.spawn((
         GaussianCloudHandle(cloud),
-        GaussianCloudSettings {
+        GaussianCloudDebugSettings {
             visualize_bounding_box,
-            ..default()
         },
         Name::new("gaussian_cloud_3dgs"),
     ));
@@ -117,9 +118,14 @@ pub fn setup_surfel_compare(
     commands.spawn((
         Transform::from_translation(Vec3::new(spacing, spacing, 0.0)),
         GaussianCloudHandle(cloud),
-        GaussianCloudSettings {
+        GaussianCloudDebugSettings {
             visualize_bounding_box,
+        },
+        GaussianCloudRasterSettings {
             aabb: true,
+            ..default()
+        },
+        GaussianCloudAppearance {
             gaussian_mode: GaussianMode::GaussianSurfel,
             ..default()
         },
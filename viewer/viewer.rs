@@ -27,7 +27,8 @@ use bevy_gaussian_splatting::{
     GaussianCamera,
     GaussianCloud,
     GaussianCloudHandle,
-    GaussianCloudSettings,
+    GaussianCloudAppearance,
+    GaussianCloudRasterSettings,
     GaussianSplattingPlugin,
     random_gaussians,
     utils::{
@@ -77,10 +78,14 @@ fn setup_gaussian_cloud(
 
     commands.spawn((
         GaussianCloudHandle(cloud),
-        GaussianCloudSettings {
+        GaussianCloudAppearance {
             gaussian_mode: args.gaussian_mode,
             ..default()
         },
+        GaussianCloudRasterSettings {
+            mip_filter: args.mip_filter,
+            ..default()
+        },
         Name::new("gaussian_cloud"),
     ));
 
@@ -132,26 +137,15 @@ fn setup_particle_behavior(
 #[cfg(feature = "material_noise")]
 fn setup_noise_material(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    gaussian_clouds: Query<(
+    gaussian_clouds: Query<
         Entity,
-        &GaussianCloudHandle,
-        Without<NoiseMaterial>,
-    )>,
+        (
+            With<GaussianCloudHandle>,
+            Without<NoiseMaterial>,
+        ),
+    >,
 ) {
-    if gaussian_clouds.is_empty() {
-        return;
-    }
-
-    for (
-        entity,
-        cloud_handle,
-        _
-    ) in gaussian_clouds.iter() {
-        if Some(bevy::asset::LoadState::Loading) == asset_server.get_load_state(cloud_handle) {
-            continue;
-        }
-
+    for entity in gaussian_clouds.iter() {
         commands.entity(entity)
             .insert(NoiseMaterial::default());
     }
@@ -160,17 +154,19 @@ fn setup_noise_material(
 #[cfg(feature = "query_sparse")]
 fn setup_sparse_select(
     mut commands: Commands,
-    gaussian_cloud: Query<(
+    gaussian_cloud: Query<
         Entity,
-        &GaussianCloudHandle,
-        Without<SparseSelect>,
-    )>,
+        (
+            With<GaussianCloudHandle>,
+            Without<SparseSelect>,
+        ),
+    >,
 ) {
     if gaussian_cloud.is_empty() {
         return;
     }
 
-    commands.entity(gaussian_cloud.single().0)
+    commands.entity(gaussian_cloud.single())
         .insert(SparseSelect {
             completed: true,
             ..default()
@@ -313,7 +309,7 @@ fn press_o_save_selection(
 ) {
     if keys.just_pressed(KeyCode::KeyO) {
         log("saving selection");
-        select_inverse_events.send(SaveSelectionEvent);
+        select_inverse_events.send(SaveSelectionEvent::default());
     }
 }
 